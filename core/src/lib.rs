@@ -1,4 +1,5 @@
 use core::ffi::c_int;
+use serde::Deserialize;
 use serde_json::json;
 use std::{
     panic::{AssertUnwindSafe, catch_unwind},
@@ -14,13 +15,14 @@ use utilities::{
     get_peak::get_peak as get_peak_rs,
     get_peaks_from_chrom::get_peaks_from_chrom as get_peaks_from_chrom_rs,
     get_peaks_from_eic::get_peaks_from_eic as get_peaks_from_eic_rs,
+    peaks_bin::{encode_chrom_peaks_bin, encode_eic_peaks_bin},
     parse::{
         decode::{decode, metadata_to_json},
         encode::encode,
         parse_mzml::parse_mzml as parse_mzml_rs,
     },
     scan_for_peaks::ScanPeaksOptions,
-    structs::{DataXY, FromTo, Roi},
+    structs::{DataXY, FromTo, Peak, Roi},
 };
 
 use crate::utilities::structs::{ChromRoi, EicRoi};
@@ -56,6 +58,73 @@ unsafe extern "C" {
     fn js_log(ptr: *const u8, len: usize);
 }
 
+/// Per-thread diagnostic recorded alongside the error code every FFI
+/// function already returns. The code itself stays the stable ABI; this is
+/// the "why" a host can surface without the caller having to guess from a
+/// bare integer, retrieved through [`last_error`]. Thread-local rather than
+/// a shared `Mutex` since every export here is called synchronously from
+/// the thread that wants the answer, and callers on other threads (the
+/// `cores`-parallel ROI batches) set their own independent last error.
+struct LastError {
+    code: c_int,
+    message: String,
+    context: serde_json::Value,
+}
+
+thread_local! {
+    static LAST_ERROR: std::cell::RefCell<Option<LastError>> = const { std::cell::RefCell::new(None) };
+}
+
+fn set_last_error(code: c_int, message: impl Into<String>, context: serde_json::Value) -> c_int {
+    LAST_ERROR.with(|cell| {
+        *cell.borrow_mut() = Some(LastError {
+            code,
+            message: message.into(),
+            context,
+        });
+    });
+    code
+}
+
+/// Best-effort extraction of a message from a `catch_unwind` panic payload:
+/// covers `panic!("{}", s)` and `panic!(s)` (`&str`/`String`), which is what
+/// every panic site in this crate uses.
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        (*s).to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "panic".to_string()
+    }
+}
+
+/// Writes the last error recorded on the calling thread (by any export
+/// below) as `{"code":N,"message":"...","context":{...}}`. Returns
+/// `{"code":0,"message":"","context":null}` if nothing has failed yet on
+/// this thread. Does not change any existing export's return-code ABI -
+/// this is purely additional detail a host can fetch after seeing a
+/// non-zero code.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn last_error(out_json: *mut Buf) -> c_int {
+    if out_json.is_null() {
+        return ERR_INVALID_ARGS;
+    }
+    LAST_ERROR.with(|cell| {
+        let s = match &*cell.borrow() {
+            Some(e) => serde_json::json!({
+                "code": e.code,
+                "message": e.message,
+                "context": e.context,
+            })
+            .to_string(),
+            None => r#"{"code":0,"message":"","context":null}"#.to_string(),
+        };
+        write_buf(out_json, s.into_bytes().into_boxed_slice());
+    });
+    OK
+}
+
 #[inline]
 pub fn log_json<T: serde::Serialize>(v: &T) {
     if let Ok(s) = serde_json::to_string_pretty(v) {
@@ -94,11 +163,16 @@ pub unsafe extern "C" fn parse_mzml(
     out_data: *mut Buf,
 ) -> c_int {
     if data_ptr.is_null() || out_data.is_null() {
-        return ERR_INVALID_ARGS;
+        return set_last_error(
+            ERR_INVALID_ARGS,
+            "null argument",
+            json!({"data_ptr_null": data_ptr.is_null(), "out_data_null": out_data.is_null()}),
+        );
     }
     let res = catch_unwind(AssertUnwindSafe(|| -> Result<(), c_int> {
         let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
-        let parsed = parse_mzml_rs(data, false).map_err(|_| ERR_PARSE)?;
+        let parsed = parse_mzml_rs(data, false)
+            .map_err(|e| set_last_error(ERR_PARSE, e, json!({"data_len": data_len})))?;
         let bin = encode(&parsed);
         write_buf(out_data, bin.into_boxed_slice());
         Ok(())
@@ -106,7 +180,7 @@ pub unsafe extern "C" fn parse_mzml(
     match res {
         Ok(Ok(())) => OK,
         Ok(Err(code)) => code,
-        Err(_) => ERR_PANIC,
+        Err(e) => set_last_error(ERR_PANIC, panic_message(&*e), json!({})),
     }
 }
 
@@ -118,12 +192,23 @@ pub unsafe extern "C" fn parse_mzml_to_json(
     out_blob: *mut Buf,
 ) -> c_int {
     if data_ptr.is_null() || out_json.is_null() || out_blob.is_null() {
-        return ERR_INVALID_ARGS;
+        return set_last_error(
+            ERR_INVALID_ARGS,
+            "null argument",
+            json!({
+                "data_ptr_null": data_ptr.is_null(),
+                "out_json_null": out_json.is_null(),
+                "out_blob_null": out_blob.is_null(),
+            }),
+        );
     }
     let res = catch_unwind(AssertUnwindSafe(|| -> Result<(), c_int> {
         let data = unsafe { slice::from_raw_parts(data_ptr, data_len) };
-        let parsed = parse_mzml_rs(data, true).map_err(|_| ERR_PARSE)?;
-        let meta = metadata_to_json(&parsed).map_err(|_| ERR_PARSE)?;
+        let parsed = parse_mzml_rs(data, true)
+            .map_err(|e| set_last_error(ERR_PARSE, e, json!({"data_len": data_len})))?;
+        let meta = metadata_to_json(&parsed).map_err(|e| {
+            set_last_error(ERR_PARSE, e.to_string(), json!({"stage": "metadata_to_json"}))
+        })?;
         let blob = encode(&parsed);
 
         write_buf(out_json, meta.into_boxed_slice());
@@ -133,57 +218,92 @@ pub unsafe extern "C" fn parse_mzml_to_json(
     match res {
         Ok(Ok(())) => OK,
         Ok(Err(code)) => code,
-        Err(_) => ERR_PANIC,
+        Err(e) => set_last_error(ERR_PANIC, panic_message(&*e), json!({})),
     }
 }
 
-#[unsafe(no_mangle)]
-pub unsafe extern "C" fn get_peak(
-    x_ptr: *const f64,
-    y_ptr: *const f32,
-    len: usize,
-    rt: f64,
-    range: f64,
-    options: *const CPeakPOptions,
-    out_json: *mut Buf,
-) -> c_int {
-    if x_ptr.is_null() || y_ptr.is_null() || out_json.is_null() || len < 3 {
-        return ERR_INVALID_ARGS;
-    }
-    let res = catch_unwind(AssertUnwindSafe(|| -> Result<(), c_int> {
+/// Expands into a full `extern "C"` FFI wrapper for a single-record peak
+/// query: a null/`out_json` guard, a `catch_unwind` around the call, and a
+/// `serde_json` object built from the listed fields of the returned
+/// record. Written to kill the drift risk where two near-identical
+/// wrappers serialize a slightly different field set for the same kind of
+/// record — the field list is named once, here, instead of copy-pasted
+/// into every wrapper's `json!({...})`.
+///
+/// Deliberately scoped to single-record wrappers: the batch exports
+/// (`get_peaks_from_eic`/`get_peaks_from_chrom` and their `_json`/`_bin`
+/// twins) each build a `Vec` by walking caller-supplied arrays with their
+/// own per-item validation and id lookup, which doesn't reduce to "a call
+/// plus a field list" without reintroducing as much branching inside the
+/// macro as it would remove from the call site — so they keep their
+/// hand-written bodies.
+macro_rules! ffi_json_fn {
+    (
+        fn $name:ident($($arg:ident : $arg_ty:ty),+ $(,)?) -> Option<$rec_ty:ty>
+        extra_guard: $guard:expr,
+        call: $call:expr,
+        fields: [$($field:ident),+ $(,)?],
+        none: ($none_code:expr, $none_msg:expr, $none_ctx:expr) $(,)?
+    ) => {
+        #[unsafe(no_mangle)]
+        pub unsafe extern "C" fn $name($($arg: $arg_ty,)+ out_json: *mut Buf) -> c_int {
+            if out_json.is_null() || ($guard) {
+                return set_last_error(
+                    ERR_INVALID_ARGS,
+                    concat!("null argument calling ", stringify!($name)),
+                    json!({}),
+                );
+            }
+            let res = catch_unwind(AssertUnwindSafe(|| -> Result<(), c_int> {
+                let record: Option<$rec_ty> = $call;
+                let s = match record {
+                    Some(p) => {
+                        let mut fields = serde_json::Map::new();
+                        $(fields.insert(stringify!($field).to_string(), serde_json::json!(p.$field));)+
+                        serde_json::Value::Object(fields).to_string()
+                    }
+                    None => {
+                        set_last_error($none_code, $none_msg, $none_ctx);
+                        let mut fields = serde_json::Map::new();
+                        $(fields.insert(stringify!($field).to_string(), serde_json::json!(0));)+
+                        serde_json::Value::Object(fields).to_string()
+                    }
+                };
+                write_buf(out_json, s.into_bytes().into_boxed_slice());
+                Ok(())
+            }));
+            match res {
+                Ok(Ok(())) => OK,
+                Ok(Err(code)) => code,
+                Err(e) => set_last_error(ERR_PANIC, panic_message(&*e), json!({})),
+            }
+        }
+    };
+}
+
+ffi_json_fn! {
+    fn get_peak(
+        x_ptr: *const f64,
+        y_ptr: *const f32,
+        len: usize,
+        rt: f64,
+        range: f64,
+        options: *const CPeakPOptions,
+    ) -> Option<Peak>
+    extra_guard: x_ptr.is_null() || y_ptr.is_null() || len < 3,
+    call: {
         let xs = unsafe { slice::from_raw_parts(x_ptr, len) };
         let ys = unsafe { slice::from_raw_parts(y_ptr, len) };
         let data = DataXY {
             x: xs.to_vec(),
             y: ys.to_vec(),
         };
-
         let fp_opts = build_find_peaks_options(options);
         let roi = Roi { rt, window: range };
-        let peak = get_peak_rs(&data, roi, Some(fp_opts));
-
-        let s = match peak {
-            Some(p) => serde_json::json!({
-                "from": p.from,
-                "to": p.to,
-                "rt": p.rt,
-                "integral": p.integral,
-                "intensity": p.intensity,
-                "ratio": p.ratio,
-                "np": p.np
-            })
-            .to_string(),
-            None => r#"{"from":0,"to":0,"rt":0,"integral":0,"intensity":0,"ratio":0,"np":0}"#
-                .to_string(),
-        };
-        write_buf(out_json, s.into_bytes().into_boxed_slice());
-        Ok(())
-    }));
-    match res {
-        Ok(Ok(())) => OK,
-        Ok(Err(code)) => code,
-        Err(_) => ERR_PANIC,
-    }
+        get_peak_rs(&data, roi, Some(fp_opts))
+    },
+    fields: [from, to, rt, integral, intensity, ratio, np, noise, snr],
+    none: (OK, "no peak found in roi", json!({"rt": rt, "window": range})),
 }
 
 #[unsafe(no_mangle)]
@@ -211,7 +331,11 @@ pub unsafe extern "C" fn get_peaks_from_eic(
         || out_json.is_null()
         || n_items == 0
     {
-        return ERR_INVALID_ARGS;
+        return set_last_error(
+            ERR_INVALID_ARGS,
+            "null argument or n_items == 0",
+            json!({"n_items": n_items}),
+        );
     }
     let run = || -> Result<(), i32> {
         let bytes = unsafe { std::slice::from_raw_parts(bin_ptr, bin_len) };
@@ -281,7 +405,13 @@ pub unsafe extern "C" fn get_peaks_from_eic(
         };
         let fp = build_find_peaks_options(options);
         let peaks = get_peaks_from_eic_rs(bytes, window, items.as_slice(), Some(fp), cores)
-            .ok_or(ERR_PARSE)?;
+            .ok_or_else(|| {
+                set_last_error(
+                    ERR_PARSE,
+                    "decode or thread pool setup failed",
+                    json!({"n_items": n_items, "from": from_left, "to": to_right, "cores": cores}),
+                )
+            })?;
 
         let mut arr = Vec::with_capacity(peaks.len());
         for (id, ort, mz, p) in peaks {
@@ -297,14 +427,15 @@ pub unsafe extern "C" fn get_peaks_from_eic(
                 "noise": p.noise
             }));
         }
-        let s = serde_json::to_string(&arr).map_err(|_| ERR_PARSE)?;
+        let s = serde_json::to_string(&arr)
+            .map_err(|e| set_last_error(ERR_PARSE, e.to_string(), json!({})))?;
         write_buf(out_json, s.into_bytes().into_boxed_slice());
         Ok(())
     };
     match std::panic::catch_unwind(std::panic::AssertUnwindSafe(run)) {
         Ok(Ok(())) => OK,
         Ok(Err(c)) => c,
-        Err(_) => ERR_PANIC,
+        Err(e) => set_last_error(ERR_PANIC, panic_message(&*e), json!({})),
     }
 }
 
@@ -327,15 +458,24 @@ pub unsafe extern "C" fn get_peaks_from_chrom(
         || out_json.is_null()
         || n_items == 0
     {
-        return ERR_INVALID_ARGS;
+        return set_last_error(
+            ERR_INVALID_ARGS,
+            "null argument or n_items == 0",
+            json!({"n_items": n_items}),
+        );
     }
     let run = || -> Result<(), i32> {
         let bin = unsafe { std::slice::from_raw_parts(bin_ptr, bin_len) };
         let idxs = unsafe { std::slice::from_raw_parts(idxs_ptr, n_items) };
         let rts = unsafe { std::slice::from_raw_parts(rts_ptr, n_items) };
         let wins = unsafe { std::slice::from_raw_parts(ranges_ptr, n_items) };
-        let mzml = decode(bin).map_err(|_| ERR_PARSE)?;
-        let chroms = &mzml.run.as_ref().ok_or(ERR_PARSE)?.chromatograms;
+        let mzml = decode(bin)
+            .map_err(|e| set_last_error(ERR_PARSE, e, json!({"bin_len": bin_len})))?;
+        let chroms = &mzml
+            .run
+            .as_ref()
+            .ok_or_else(|| set_last_error(ERR_PARSE, "no run in mzml", json!({})))?
+            .chromatograms;
 
         let mut items = Vec::with_capacity(n_items);
         for i in 0..n_items {
@@ -369,8 +509,9 @@ pub unsafe extern "C" fn get_peaks_from_chrom(
         }
 
         let fp = build_find_peaks_options(options);
-        let list =
-            get_peaks_from_chrom_rs(&mzml, items.as_slice(), Some(fp), cores).ok_or(ERR_PARSE)?;
+        let list = get_peaks_from_chrom_rs(&mzml, items.as_slice(), Some(fp), cores).ok_or_else(
+            || set_last_error(ERR_PARSE, "thread pool setup failed", json!({"cores": cores})),
+        )?;
 
         let mut out = Vec::with_capacity(list.len());
         for (index, id, ort, rt, from_, to_, intensity, integral) in list {
@@ -385,14 +526,491 @@ pub unsafe extern "C" fn get_peaks_from_chrom(
                 "integral":  integral
             }));
         }
-        let s = serde_json::to_string(&out).map_err(|_| ERR_PARSE)?;
+        let s = serde_json::to_string(&out)
+            .map_err(|e| set_last_error(ERR_PARSE, e.to_string(), json!({})))?;
         write_buf(out_json, s.into_bytes().into_boxed_slice());
         Ok(())
     };
     match std::panic::catch_unwind(std::panic::AssertUnwindSafe(run)) {
         Ok(Ok(())) => OK,
         Ok(Err(c)) => c,
-        Err(_) => ERR_PANIC,
+        Err(e) => set_last_error(ERR_PANIC, panic_message(&*e), json!({})),
+    }
+}
+
+/// JSON request body for [`get_peaks_from_eic_json`]. Mirrors the
+/// parallel-pointer arguments of [`get_peaks_from_eic`] as a single document
+/// so callers that already have the ROIs as JSON (e.g. from JS/WASM) don't
+/// have to flatten them into side-by-side arrays first. Parsed with
+/// `serde_json` rather than simd-json: this crate has no build manifest to
+/// add a new dependency to, and `serde_json` is already what every other
+/// JSON site in this file uses, so staying on it keeps one parser for the
+/// whole FFI surface instead of two.
+#[derive(Deserialize)]
+struct EicJsonRequest {
+    window: JsonFromTo,
+    #[serde(default)]
+    options: Option<JsonPeakPOptions>,
+    #[serde(default)]
+    cores: usize,
+    items: Vec<EicJsonItem>,
+}
+
+#[derive(Deserialize)]
+struct EicJsonItem {
+    #[serde(default)]
+    id: String,
+    rt: f64,
+    mz: f64,
+    window: f64,
+}
+
+/// JSON request body for [`get_peaks_from_chrom_json`], the same JSON-in
+/// convention offered for [`get_peaks_from_chrom`].
+#[derive(Deserialize)]
+struct ChromJsonRequest {
+    #[serde(default)]
+    options: Option<JsonPeakPOptions>,
+    #[serde(default)]
+    cores: usize,
+    items: Vec<ChromJsonItem>,
+}
+
+#[derive(Deserialize)]
+struct ChromJsonItem {
+    idx: i64,
+    rt: f64,
+    window: f64,
+}
+
+#[derive(Deserialize)]
+struct JsonFromTo {
+    from: f64,
+    to: f64,
+}
+
+/// JSON-document twin of [`CPeakPOptions`]: same fields, but every one is
+/// optional since a JSON request can simply omit what it doesn't need
+/// instead of passing sentinel values across the ABI.
+#[derive(Deserialize)]
+struct JsonPeakPOptions {
+    integral_threshold: Option<f64>,
+    intensity_threshold: Option<f64>,
+    width_threshold: Option<usize>,
+    noise: Option<f64>,
+    auto_noise: Option<bool>,
+    allow_overlap: Option<bool>,
+    window_size: Option<usize>,
+    sn_ratio: Option<f64>,
+}
+
+/// Parses the request document at `req_ptr`/`req_len` and returns it, or
+/// `ERR_PARSE` if it isn't valid UTF-8 JSON matching `T`'s shape.
+fn parse_json_request<T: for<'de> Deserialize<'de>>(
+    req_ptr: *const u8,
+    req_len: usize,
+) -> Result<T, i32> {
+    let bytes = unsafe { std::slice::from_raw_parts(req_ptr, req_len) };
+    serde_json::from_slice(bytes)
+        .map_err(|e| set_last_error(ERR_PARSE, e.to_string(), json!({"req_len": req_len})))
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn get_peaks_from_eic_json(
+    bin_ptr: *const u8,
+    bin_len: usize,
+    req_ptr: *const u8,
+    req_len: usize,
+    out_json: *mut Buf,
+) -> i32 {
+    if bin_ptr.is_null() || req_ptr.is_null() || out_json.is_null() {
+        return set_last_error(ERR_INVALID_ARGS, "null argument", json!({}));
+    }
+    let run = || -> Result<(), i32> {
+        let bytes = unsafe { std::slice::from_raw_parts(bin_ptr, bin_len) };
+        let req: EicJsonRequest = parse_json_request(req_ptr, req_len)?;
+
+        let window = FromTo {
+            from: req.window.from,
+            to: req.window.to,
+        };
+        let items: Vec<EicRoi> = req
+            .items
+            .into_iter()
+            .map(|it| EicRoi {
+                id: it.id,
+                rt: it.rt,
+                mz: it.mz,
+                window: it.window,
+            })
+            .collect();
+        let fp = build_find_peaks_options_from_json(req.options.as_ref());
+        let peaks = get_peaks_from_eic_rs(bytes, window, items.as_slice(), Some(fp), req.cores)
+            .ok_or_else(|| {
+                set_last_error(
+                    ERR_PARSE,
+                    "decode or thread pool setup failed",
+                    json!({"bin_len": bin_len, "cores": req.cores}),
+                )
+            })?;
+
+        let mut arr = Vec::with_capacity(peaks.len());
+        for (id, ort, mz, p) in peaks {
+            arr.push(serde_json::json!({
+                "id": id,
+                "mz": mz,
+                "ort": ort,
+                "rt": p.rt,
+                "from": p.from,
+                "to": p.to,
+                "intensity": p.intensity,
+                "integral": p.integral,
+                "noise": p.noise
+            }));
+        }
+        let s = serde_json::to_string(&arr)
+            .map_err(|e| set_last_error(ERR_PARSE, e.to_string(), json!({})))?;
+        write_buf(out_json, s.into_bytes().into_boxed_slice());
+        Ok(())
+    };
+    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(run)) {
+        Ok(Ok(())) => OK,
+        Ok(Err(c)) => c,
+        Err(e) => set_last_error(ERR_PANIC, panic_message(&*e), json!({})),
+    }
+}
+
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn get_peaks_from_chrom_json(
+    bin_ptr: *const u8,
+    bin_len: usize,
+    req_ptr: *const u8,
+    req_len: usize,
+    out_json: *mut Buf,
+) -> i32 {
+    if bin_ptr.is_null() || req_ptr.is_null() || out_json.is_null() {
+        return set_last_error(ERR_INVALID_ARGS, "null argument", json!({}));
+    }
+    let run = || -> Result<(), i32> {
+        let bin = unsafe { std::slice::from_raw_parts(bin_ptr, bin_len) };
+        let req: ChromJsonRequest = parse_json_request(req_ptr, req_len)?;
+        let mzml = decode(bin)
+            .map_err(|e| set_last_error(ERR_PARSE, e, json!({"bin_len": bin_len})))?;
+        let chroms = &mzml
+            .run
+            .as_ref()
+            .ok_or_else(|| set_last_error(ERR_PARSE, "no run in mzml", json!({})))?
+            .chromatograms;
+
+        let items: Vec<ChromRoi> = req
+            .items
+            .into_iter()
+            .map(|it| {
+                let idx = if it.idx >= 0 { it.idx as usize } else { usize::MAX };
+                if idx >= chroms.len() {
+                    return ChromRoi {
+                        id: String::new(),
+                        idx,
+                        rt: 0.0,
+                        window: 0.0,
+                    };
+                }
+                ChromRoi {
+                    id: chroms[idx].id.clone(),
+                    idx,
+                    rt: it.rt,
+                    window: it.window,
+                }
+            })
+            .collect();
+
+        let fp = build_find_peaks_options_from_json(req.options.as_ref());
+        let list = get_peaks_from_chrom_rs(&mzml, items.as_slice(), Some(fp), req.cores)
+            .ok_or_else(|| {
+                set_last_error(ERR_PARSE, "thread pool setup failed", json!({"cores": req.cores}))
+            })?;
+
+        let mut out = Vec::with_capacity(list.len());
+        for (index, id, ort, rt, from_, to_, intensity, integral) in list {
+            out.push(serde_json::json!({
+                "index": index,
+                "id": id,
+                "ort": ort,
+                "rt": rt,
+                "from": from_,
+                "to": to_,
+                "intensity": intensity,
+                "integral": integral
+            }));
+        }
+        let s = serde_json::to_string(&out)
+            .map_err(|e| set_last_error(ERR_PARSE, e.to_string(), json!({})))?;
+        write_buf(out_json, s.into_bytes().into_boxed_slice());
+        Ok(())
+    };
+    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(run)) {
+        Ok(Ok(())) => OK,
+        Ok(Err(c)) => c,
+        Err(e) => set_last_error(ERR_PANIC, panic_message(&*e), json!({})),
+    }
+}
+
+/// Binary twin of [`get_peaks_from_eic`]: same pointer/array calling
+/// convention and the same underlying peak search, but the result is
+/// written to `out_bin` as the columnar layout documented in
+/// [`crate::utilities::peaks_bin`] instead of a `serde_json::Value` array.
+/// For tens of thousands of peaks, building and serializing one JSON
+/// object per record dominates runtime; callers that just want the
+/// numbers back can read each column directly and skip JSON entirely.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn get_peaks_from_eic_bin(
+    bin_ptr: *const u8,
+    bin_len: usize,
+    rts_ptr: *const f64,
+    mzs_ptr: *const f64,
+    ranges_ptr: *const f64,
+    ids_off_ptr: *const u32,
+    ids_len_ptr: *const u32,
+    ids_buf_ptr: *const u8,
+    ids_buf_len: usize,
+    n_items: usize,
+    from_left: f64,
+    to_right: f64,
+    options: *const CPeakPOptions,
+    cores: usize,
+    out_bin: *mut Buf,
+) -> i32 {
+    if bin_ptr.is_null()
+        || rts_ptr.is_null()
+        || mzs_ptr.is_null()
+        || ranges_ptr.is_null()
+        || out_bin.is_null()
+        || n_items == 0
+    {
+        return set_last_error(
+            ERR_INVALID_ARGS,
+            "null argument or n_items == 0",
+            json!({"n_items": n_items}),
+        );
+    }
+    let run = || -> Result<(), i32> {
+        let bytes = unsafe { std::slice::from_raw_parts(bin_ptr, bin_len) };
+        let rts = unsafe { std::slice::from_raw_parts(rts_ptr, n_items) };
+        let mzs = unsafe { std::slice::from_raw_parts(mzs_ptr, n_items) };
+        let ranges = unsafe { std::slice::from_raw_parts(ranges_ptr, n_items) };
+
+        let has_ids = !(ids_off_ptr.is_null()
+            || ids_len_ptr.is_null()
+            || ids_buf_ptr.is_null()
+            || ids_buf_len == 0);
+        let (offs, lens, ibuf) = if has_ids {
+            (
+                unsafe { std::slice::from_raw_parts(ids_off_ptr, n_items) },
+                unsafe { std::slice::from_raw_parts(ids_len_ptr, n_items) },
+                Some(unsafe { std::slice::from_raw_parts(ids_buf_ptr, ids_buf_len) }),
+            )
+        } else {
+            (&[][..], &[][..], None)
+        };
+
+        let mut items: Vec<EicRoi> = Vec::with_capacity(n_items);
+        for i in 0..n_items {
+            let rt = rts[i];
+            let mz = mzs[i];
+            let win = ranges[i];
+            let ok = rt.is_finite() && mz.is_finite() && win.is_finite() && win > 0.0;
+
+            let id = if let Some(buf) = ibuf {
+                if has_ids {
+                    let o = offs[i] as usize;
+                    let l = lens[i] as usize;
+                    if o.checked_add(l).map_or(true, |e| e > buf.len()) {
+                        String::new()
+                    } else {
+                        std::str::from_utf8(&buf[o..o + l])
+                            .unwrap_or("")
+                            .to_string()
+                    }
+                } else {
+                    String::new()
+                }
+            } else {
+                String::new()
+            };
+
+            if ok {
+                items.push(EicRoi {
+                    id,
+                    rt,
+                    mz,
+                    window: win,
+                });
+            } else {
+                items.push(EicRoi {
+                    id: String::new(),
+                    rt: 0.0,
+                    mz: 0.0,
+                    window: 0.0,
+                });
+            }
+        }
+
+        let window = FromTo {
+            from: from_left,
+            to: to_right,
+        };
+        let fp = build_find_peaks_options(options);
+        let peaks = get_peaks_from_eic_rs(bytes, window, items.as_slice(), Some(fp), cores)
+            .ok_or_else(|| {
+                set_last_error(
+                    ERR_PARSE,
+                    "decode or thread pool setup failed",
+                    json!({"n_items": n_items, "from": from_left, "to": to_right, "cores": cores}),
+                )
+            })?;
+
+        let mut ids = Vec::with_capacity(peaks.len());
+        let (mut mz_c, mut ort_c, mut rt_c, mut from_c, mut to_c, mut int_c, mut integ_c, mut noise_c) =
+            (
+                Vec::with_capacity(peaks.len()),
+                Vec::with_capacity(peaks.len()),
+                Vec::with_capacity(peaks.len()),
+                Vec::with_capacity(peaks.len()),
+                Vec::with_capacity(peaks.len()),
+                Vec::with_capacity(peaks.len()),
+                Vec::with_capacity(peaks.len()),
+                Vec::with_capacity(peaks.len()),
+            );
+        for (id, ort, mz, p) in peaks {
+            ids.push(id);
+            mz_c.push(mz);
+            ort_c.push(ort);
+            rt_c.push(p.rt);
+            from_c.push(p.from);
+            to_c.push(p.to);
+            int_c.push(p.intensity);
+            integ_c.push(p.integral);
+            noise_c.push(p.noise);
+        }
+        let bytes = encode_eic_peaks_bin(
+            &ids, &mz_c, &ort_c, &rt_c, &from_c, &to_c, &int_c, &integ_c, &noise_c,
+        );
+        write_buf(out_bin, bytes.into_boxed_slice());
+        Ok(())
+    };
+    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(run)) {
+        Ok(Ok(())) => OK,
+        Ok(Err(c)) => c,
+        Err(e) => set_last_error(ERR_PANIC, panic_message(&*e), json!({})),
+    }
+}
+
+/// Binary twin of [`get_peaks_from_chrom`], the same columnar alternative
+/// to JSON offered for [`get_peaks_from_eic_bin`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn get_peaks_from_chrom_bin(
+    bin_ptr: *const u8,
+    bin_len: usize,
+    idxs_ptr: *const u32,
+    rts_ptr: *const f64,
+    ranges_ptr: *const f64,
+    n_items: usize,
+    options: *const CPeakPOptions,
+    cores: usize,
+    out_bin: *mut Buf,
+) -> i32 {
+    if bin_ptr.is_null()
+        || idxs_ptr.is_null()
+        || rts_ptr.is_null()
+        || ranges_ptr.is_null()
+        || out_bin.is_null()
+        || n_items == 0
+    {
+        return set_last_error(
+            ERR_INVALID_ARGS,
+            "null argument or n_items == 0",
+            json!({"n_items": n_items}),
+        );
+    }
+    let run = || -> Result<(), i32> {
+        let bin = unsafe { std::slice::from_raw_parts(bin_ptr, bin_len) };
+        let idxs = unsafe { std::slice::from_raw_parts(idxs_ptr, n_items) };
+        let rts = unsafe { std::slice::from_raw_parts(rts_ptr, n_items) };
+        let wins = unsafe { std::slice::from_raw_parts(ranges_ptr, n_items) };
+        let mzml = decode(bin)
+            .map_err(|e| set_last_error(ERR_PARSE, e, json!({"bin_len": bin_len})))?;
+        let chroms = &mzml
+            .run
+            .as_ref()
+            .ok_or_else(|| set_last_error(ERR_PARSE, "no run in mzml", json!({})))?
+            .chromatograms;
+
+        let mut items = Vec::with_capacity(n_items);
+        for i in 0..n_items {
+            let iu = idxs[i];
+            if iu == u32::MAX {
+                items.push(ChromRoi {
+                    id: String::new(),
+                    idx: usize::MAX,
+                    rt: 0.0,
+                    window: 0.0,
+                });
+                continue;
+            }
+            let idx = iu as usize;
+            if idx >= chroms.len() {
+                items.push(ChromRoi {
+                    id: String::new(),
+                    idx,
+                    rt: 0.0,
+                    window: 0.0,
+                });
+                continue;
+            }
+            let id = chroms[idx].id.clone();
+            items.push(ChromRoi {
+                id,
+                idx,
+                rt: rts[i],
+                window: wins[i],
+            });
+        }
+
+        let fp = build_find_peaks_options(options);
+        let list = get_peaks_from_chrom_rs(&mzml, items.as_slice(), Some(fp), cores).ok_or_else(
+            || set_last_error(ERR_PARSE, "thread pool setup failed", json!({"cores": cores})),
+        )?;
+
+        let mut ids = Vec::with_capacity(list.len());
+        let (mut index_c, mut ort_c, mut rt_c, mut from_c, mut to_c, mut int_c, mut integ_c) = (
+            Vec::with_capacity(list.len()),
+            Vec::with_capacity(list.len()),
+            Vec::with_capacity(list.len()),
+            Vec::with_capacity(list.len()),
+            Vec::with_capacity(list.len()),
+            Vec::with_capacity(list.len()),
+            Vec::with_capacity(list.len()),
+        );
+        for (index, id, ort, rt, from_, to_, intensity, integral) in list {
+            ids.push(id);
+            index_c.push(index as f64);
+            ort_c.push(ort);
+            rt_c.push(rt);
+            from_c.push(from_);
+            to_c.push(to_);
+            int_c.push(intensity);
+            integ_c.push(integral);
+        }
+        let bytes = encode_chrom_peaks_bin(
+            &ids, &index_c, &ort_c, &rt_c, &from_c, &to_c, &int_c, &integ_c,
+        );
+        write_buf(out_bin, bytes.into_boxed_slice());
+        Ok(())
+    };
+    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(run)) {
+        Ok(Ok(())) => OK,
+        Ok(Err(c)) => c,
+        Err(e) => set_last_error(ERR_PANIC, panic_message(&*e), json!({})),
     }
 }
 
@@ -405,13 +1023,17 @@ pub extern "C" fn find_peaks(
     out_json: *mut Buf,
 ) -> c_int {
     if x_ptr.is_null() || y_ptr.is_null() || out_json.is_null() {
-        return ERR_INVALID_ARGS;
+        return set_last_error(ERR_INVALID_ARGS, "null argument", json!({}));
     }
     let run = || -> Result<(), c_int> {
         let xs = unsafe { slice::from_raw_parts(x_ptr, len) };
         let ys = unsafe { slice::from_raw_parts(y_ptr, len) };
         if xs.len() != ys.len() || xs.len() < 3 {
-            return Err(ERR_INVALID_ARGS);
+            return Err(set_last_error(
+                ERR_INVALID_ARGS,
+                "x/y length mismatch or len < 3",
+                json!({"x_len": xs.len(), "y_len": ys.len()}),
+            ));
         }
         let data = DataXY {
             x: xs.to_vec(),
@@ -430,18 +1052,20 @@ pub extern "C" fn find_peaks(
                     "intensity": p.intensity,
                     "ratio": p.ratio,
                     "np": p.np,
-                    "noise": p.noise
+                    "noise": p.noise,
+                    "snr": p.snr
                 })
             })
             .collect();
-        let s = serde_json::to_string(&list).map_err(|_| ERR_PARSE)?;
+        let s = serde_json::to_string(&list)
+            .map_err(|e| set_last_error(ERR_PARSE, e.to_string(), json!({})))?;
         write_buf(out_json, s.into_bytes().into_boxed_slice());
         Ok(())
     };
     match catch_unwind(AssertUnwindSafe(run)) {
         Ok(Ok(())) => OK,
         Ok(Err(code)) => code,
-        Err(_) => ERR_PANIC,
+        Err(e) => set_last_error(ERR_PANIC, panic_message(&*e), json!({})),
     }
 }
 
@@ -467,19 +1091,20 @@ pub unsafe extern "C" fn bin_to_json(
     out_json: *mut Buf,
 ) -> c_int {
     if bin_ptr.is_null() || out_json.is_null() {
-        return ERR_INVALID_ARGS;
+        return set_last_error(ERR_INVALID_ARGS, "null argument", json!({}));
     }
     let res = catch_unwind(AssertUnwindSafe(|| -> Result<(), c_int> {
         let bin = unsafe { slice::from_raw_parts(bin_ptr, bin_len) };
         let mzml = decode(bin);
-        let s = serde_json::to_string(&mzml).map_err(|_| ERR_PARSE)?;
+        let s = serde_json::to_string(&mzml)
+            .map_err(|e| set_last_error(ERR_PARSE, e.to_string(), json!({"bin_len": bin_len})))?;
         write_buf(out_json, s.into_bytes().into_boxed_slice());
         Ok(())
     }));
     match res {
         Ok(Ok(())) => OK,
         Ok(Err(code)) => code,
-        Err(_) => ERR_PANIC,
+        Err(e) => set_last_error(ERR_PANIC, panic_message(&*e), json!({})),
     }
 }
 
@@ -497,12 +1122,12 @@ pub unsafe extern "C" fn calculate_eic(
     out_y: *mut Buf,
 ) -> c_int {
     if bin_ptr.is_null() || target_ptr.is_null() || out_x.is_null() || out_y.is_null() {
-        return ERR_INVALID_ARGS;
+        return set_last_error(ERR_INVALID_ARGS, "null argument", json!({}));
     }
     let res = catch_unwind(AssertUnwindSafe(|| -> Result<(), c_int> {
         let bin = unsafe { slice::from_raw_parts(bin_ptr, bin_len) };
         let target = std::str::from_utf8(unsafe { slice::from_raw_parts(target_ptr, target_len) })
-            .map_err(|_| ERR_PARSE)?;
+            .map_err(|e| set_last_error(ERR_PARSE, e.to_string(), json!({"target_len": target_len})))?;
 
         let eic = calculate_eic_from_bin1(
             bin,
@@ -514,9 +1139,16 @@ pub unsafe extern "C" fn calculate_eic(
             EicOptions {
                 ppm_tolerance,
                 mz_tolerance,
+                ..Default::default()
             },
         )
-        .map_err(|_| ERR_PARSE)?;
+        .map_err(|e| {
+            set_last_error(
+                ERR_PARSE,
+                e,
+                json!({"target": target, "from": from_time, "to": to_time}),
+            )
+        })?;
 
         let x_bytes = f64_slice_to_u8_box(&eic.x);
         let y_bytes = f32_slice_to_u8_box(&eic.y);
@@ -527,7 +1159,7 @@ pub unsafe extern "C" fn calculate_eic(
     match res {
         Ok(Ok(())) => OK,
         Ok(Err(code)) => code,
-        Err(_) => ERR_PANIC,
+        Err(e) => set_last_error(ERR_PANIC, panic_message(&*e), json!({})),
     }
 }
 
@@ -584,6 +1216,8 @@ fn build_find_peaks_options(options: *const CPeakPOptions) -> FindPeaksOptions {
             scan_peaks_options: Some(ScanPeaksOptions {
                 epsilon: EPS,
                 window_size: ws,
+                polynomial: 3,
+                adaptive: None,
             }),
             get_boundaries_options: Some(BoundariesOptions {
                 ..Default::default()
@@ -612,13 +1246,78 @@ fn build_find_peaks_options(options: *const CPeakPOptions) -> FindPeaksOptions {
         width_threshold: width,
         noise,
         auto_noise,
+        auto_baseline: Some(false),
+        allow_overlap,
+        sn_ratio,
+        snr_threshold: None,
+        windowed_noise: None,
+    };
+    FindPeaksOptions {
+        scan_peaks_options: Some(ScanPeaksOptions {
+            epsilon: EPS,
+            window_size: ws,
+            polynomial: 3,
+            adaptive: None,
+        }),
+        get_boundaries_options: Some(BoundariesOptions {
+            ..Default::default()
+        }),
+        filter_peaks_options: Some(filter),
+    }
+}
+
+/// JSON-request equivalent of [`build_find_peaks_options`]: same defaulting
+/// rules, applied to the optional fields of [`JsonPeakPOptions`] instead of
+/// the `CPeakPOptions` C struct's sentinel-encoded ones.
+fn build_find_peaks_options_from_json(options: Option<&JsonPeakPOptions>) -> FindPeaksOptions {
+    let Some(o) = options else {
+        let ws = odd_at_least(17, 5, 17);
+        return FindPeaksOptions {
+            scan_peaks_options: Some(ScanPeaksOptions {
+                epsilon: EPS,
+                window_size: ws,
+                polynomial: 3,
+                adaptive: None,
+            }),
+            get_boundaries_options: Some(BoundariesOptions {
+                ..Default::default()
+            }),
+            filter_peaks_options: None,
+        };
+    };
+    let ws = odd_at_least(o.window_size.unwrap_or(17), 5, 17);
+    let integral = o
+        .integral_threshold
+        .filter(|v| v.is_finite() && *v >= 0.0);
+    let intensity = o
+        .intensity_threshold
+        .filter(|v| v.is_finite() && *v >= 0.0);
+    let width = o.width_threshold.filter(|&w| w > 0);
+    let noise = o.noise.filter(|v| v.is_finite() && *v > 0.0);
+    let auto_noise = Some(o.auto_noise.unwrap_or(false));
+    let allow_overlap = Some(o.allow_overlap.unwrap_or(false));
+    let sn_ratio = match o.sn_ratio {
+        Some(v) if v.is_finite() && v > 0.0 => Some(v),
+        _ => Some(1.5), // default value for s/n
+    };
+    let filter = FilterPeaksOptions {
+        integral_threshold: integral,
+        intensity_threshold: intensity,
+        width_threshold: width,
+        noise,
+        auto_noise,
+        auto_baseline: Some(false),
         allow_overlap,
         sn_ratio,
+        snr_threshold: None,
+        windowed_noise: None,
     };
     FindPeaksOptions {
         scan_peaks_options: Some(ScanPeaksOptions {
             epsilon: EPS,
             window_size: ws,
+            polynomial: 3,
+            adaptive: None,
         }),
         get_boundaries_options: Some(BoundariesOptions {
             ..Default::default()
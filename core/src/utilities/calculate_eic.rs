@@ -9,6 +9,9 @@ use crate::utilities::{
 pub struct EicOptions {
     pub ppm_tolerance: f64,
     pub mz_tolerance: f64,
+    /// Relative rank error for the `QuantileSummary` used by
+    /// `refine_mz_for_peak`'s weighted-median m/z estimate.
+    pub epsilon: f64,
 }
 
 impl Default for EicOptions {
@@ -16,6 +19,7 @@ impl Default for EicOptions {
         Self {
             ppm_tolerance: 20.0,
             mz_tolerance: 0.005,
+            epsilon: 0.01,
         }
     }
 }
@@ -104,6 +108,77 @@ pub fn compute_eic_for_mz(
     y
 }
 
+/// Like [`compute_eic_for_mz`] but for many `centers` at once, walking each
+/// scan's `mz`/`intensity` arrays a single time instead of re-scanning the
+/// whole run once per target. `centers` is sorted internally by tolerance
+/// window, then each scan's already-sorted `mz` array is swept alongside
+/// the sorted windows: a peak only needs to be checked against windows
+/// from the last unexhausted one onward, since both sequences are
+/// monotonic, so no per-target binary search restarts per peak. A peak can
+/// still fall inside more than one overlapping window, each accumulating
+/// it independently. The returned `Vec<Vec<f64>>` is in the same order as
+/// `centers`, not sorted order.
+pub fn compute_eics_for_mzs(
+    scans: &[CentroidScan],
+    rt_len: usize,
+    centers: &[f64],
+    opts: EicOptions,
+) -> Vec<Vec<f64>> {
+    if centers.is_empty() {
+        return Vec::new();
+    }
+
+    struct Window {
+        lo: f64,
+        hi: f64,
+        original_index: usize,
+    }
+
+    let mut windows: Vec<Window> = centers
+        .iter()
+        .enumerate()
+        .map(|(i, &center)| {
+            let tol_ppm = if opts.ppm_tolerance > 0.0 {
+                (opts.ppm_tolerance * 1e-6) * center
+            } else {
+                0.0
+            };
+            let tol = tol_ppm.max(opts.mz_tolerance.max(0.0));
+            if !(tol.is_finite()) || tol <= 0.0 {
+                panic!("[panic] invalid EIC tol for center={}", center);
+            }
+            Window {
+                lo: center - tol,
+                hi: center + tol,
+                original_index: i,
+            }
+        })
+        .collect();
+    windows.sort_by(|a, b| a.lo.partial_cmp(&b.lo).unwrap_or(Ordering::Equal));
+
+    let mut out: Vec<Vec<f64>> = vec![vec![0.0f64; rt_len]; centers.len()];
+
+    for (row, s) in scans.iter().enumerate() {
+        let mzs = &s.mz;
+        let ints = &s.intensity;
+        let mut start = 0usize;
+        for (j, &mz) in mzs.iter().enumerate() {
+            while start < windows.len() && windows[start].hi < mz {
+                start += 1;
+            }
+            let mut k = start;
+            while k < windows.len() && windows[k].lo <= mz {
+                if mz <= windows[k].hi {
+                    out[windows[k].original_index][row] += ints[j] as f64;
+                }
+                k += 1;
+            }
+        }
+    }
+
+    out
+}
+
 pub fn collect_ms1_scans(mzml: &MzML, time_window: FromTo) -> (Vec<f64>, Vec<CentroidScan>) {
     let mut scans = Vec::new();
     let mut total_points: usize = 0;
@@ -192,6 +267,82 @@ pub fn with_eic_apex_intensity(rt: &[f64], y: &[f64], mut p: Peak) -> Peak {
     p
 }
 
+/// Sub-sample refinement of [`with_eic_apex_intensity`]: locates the
+/// discrete apex within `[p.from, p.to]`, fits a parabola through it and
+/// its two RT neighbors to estimate the true apex RT and intensity, and
+/// subtracts a baseline estimated from the minimum intensity at the
+/// window's two edge samples. Falls back to the raw discrete max (same as
+/// `with_eic_apex_intensity`, baseline-subtracted) when the apex sits at a
+/// window boundary or the three points are collinear, since neither case
+/// can support a parabola fit.
+pub fn with_eic_apex_intensity_refined(rt: &[f64], y: &[f64], mut p: Peak) -> Peak {
+    let i0 = lower_bound(rt, p.from);
+    let mut i1 = upper_bound(rt, p.to);
+    if i1 > y.len() {
+        i1 = y.len();
+    }
+    if i0 >= i1 || i0 >= y.len() {
+        return p;
+    }
+
+    let mut apex = i0;
+    for i in i0..i1 {
+        if y[i] > y[apex] {
+            apex = i;
+        }
+    }
+    if !y[apex].is_finite() || y[apex] <= 0.0 {
+        return p;
+    }
+
+    let baseline = y[i0].min(y[i1 - 1]).max(0.0);
+
+    if apex == i0 || apex + 1 >= i1 {
+        p.rt = rt[apex];
+        p.intensity = (y[apex] - baseline).max(0.0);
+        p.baseline = baseline;
+        return p;
+    }
+
+    let (x0, x1, x2) = (rt[apex - 1], rt[apex], rt[apex + 1]);
+    let (y0, y1, y2) = (y[apex - 1], y[apex], y[apex + 1]);
+
+    let d0 = (x0 - x1) * (x0 - x2);
+    let d1 = (x1 - x0) * (x1 - x2);
+    let d2 = (x2 - x0) * (x2 - x1);
+
+    if d0 == 0.0 || d1 == 0.0 || d2 == 0.0 {
+        p.rt = x1;
+        p.intensity = (y1 - baseline).max(0.0);
+        p.baseline = baseline;
+        return p;
+    }
+
+    let a = y0 / d0 + y1 / d1 + y2 / d2;
+    if a == 0.0 || !a.is_finite() {
+        p.rt = x1;
+        p.intensity = (y1 - baseline).max(0.0);
+        p.baseline = baseline;
+        return p;
+    }
+    let b = -y0 * (x1 + x2) / d0 - y1 * (x0 + x2) / d1 - y2 * (x0 + x1) / d2;
+    let c = y0 * x1 * x2 / d0 + y1 * x0 * x2 / d1 + y2 * x0 * x1 / d2;
+
+    let apex_rt = -b / (2.0 * a);
+    if !apex_rt.is_finite() || apex_rt < x0 || apex_rt > x2 {
+        p.rt = x1;
+        p.intensity = (y1 - baseline).max(0.0);
+        p.baseline = baseline;
+        return p;
+    }
+
+    let apex_y = a * apex_rt * apex_rt + b * apex_rt + c;
+    p.rt = apex_rt;
+    p.intensity = (apex_y - baseline).max(0.0);
+    p.baseline = baseline;
+    p
+}
+
 #[inline]
 pub fn lower_bound(a: &[f64], x: f64) -> usize {
     let mut lo = 0usize;
@@ -0,0 +1,86 @@
+use std::io::{BufRead, BufReader, Read, Write};
+
+use crate::utilities::structs::DataXY;
+
+/// Options for [`DataXY::from_csv_reader`] / [`DataXY::to_csv_writer`].
+#[derive(Clone, Copy, Debug)]
+pub struct CsvOptions {
+    pub delimiter: u8,
+    pub header: bool,
+    pub x_col: usize,
+    pub y_col: usize,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            header: true,
+            x_col: 0,
+            y_col: 1,
+        }
+    }
+}
+
+impl DataXY {
+    /// Parse `x`/`y` columns out of a CSV-ish reader. Blank lines are
+    /// skipped; a malformed number reports its 1-based line and column.
+    /// Numbers may use scientific notation (`1.2e-3`), since that's just
+    /// Rust's own `f64`/`f32` parser.
+    pub fn from_csv_reader<R: Read>(r: R, opts: CsvOptions) -> Result<DataXY, String> {
+        let delim = opts.delimiter as char;
+        let mut x = Vec::new();
+        let mut y = Vec::new();
+        let mut header_skipped = !opts.header;
+
+        for (line_no, line) in BufReader::new(r).lines().enumerate() {
+            let line_no = line_no + 1;
+            let line = line.map_err(|e| format!("line {line_no}: {e}"))?;
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+            if !header_skipped {
+                header_skipped = true;
+                continue;
+            }
+
+            let fields: Vec<&str> = trimmed.split(delim).collect();
+            let max_col = opts.x_col.max(opts.y_col);
+            if fields.len() <= max_col {
+                return Err(format!(
+                    "line {line_no}: expected at least {} columns, got {}",
+                    max_col + 1,
+                    fields.len()
+                ));
+            }
+
+            let xs = fields[opts.x_col].trim();
+            let xv: f64 = xs.parse().map_err(|_| {
+                format!("line {line_no}, column {}: invalid number '{xs}'", opts.x_col + 1)
+            })?;
+            let ys = fields[opts.y_col].trim();
+            let yv: f32 = ys.parse().map_err(|_| {
+                format!("line {line_no}, column {}: invalid number '{ys}'", opts.y_col + 1)
+            })?;
+
+            x.push(xv);
+            y.push(yv);
+        }
+
+        Ok(DataXY { x, y })
+    }
+
+    /// Write `x`/`y` as two delimited columns, with an optional header row.
+    pub fn to_csv_writer<W: Write>(&self, mut w: W, opts: CsvOptions) -> Result<(), String> {
+        let delim = opts.delimiter as char;
+        if opts.header {
+            writeln!(w, "x{delim}y").map_err(|e| e.to_string())?;
+        }
+        for i in 0..self.x.len() {
+            let y = self.y.get(i).copied().unwrap_or(0.0);
+            writeln!(w, "{}{delim}{}", self.x[i], y).map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+}
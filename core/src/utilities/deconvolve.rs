@@ -0,0 +1,211 @@
+/// One resolved component of a Gaussian-mixture deconvolution.
+#[derive(Clone, Copy, Debug)]
+pub struct GaussianComponent {
+    pub weight: f64,
+    pub mu: f64,
+    pub sigma: f64,
+    pub amplitude: f64,
+    pub area: f64,
+}
+
+pub(crate) const SQRT_2PI: f64 = 2.5066282746310002;
+
+#[inline]
+pub(crate) fn gaussian_density(x: f64, mu: f64, sigma: f64) -> f64 {
+    let s = sigma.max(1e-9);
+    (-0.5 * ((x - mu) / s).powi(2)).exp() / (s * SQRT_2PI)
+}
+
+/// Indices of local maxima of `ys`, merging any within `min_sep` of `xs` of a
+/// stronger neighbor so near-duplicate apices collapse into one seed.
+pub fn detect_local_maxima(xs: &[f64], ys: &[f32], min_sep: f64) -> Vec<usize> {
+    let n = ys.len();
+    if n < 3 {
+        return Vec::new();
+    }
+    let mut raw = Vec::new();
+    for i in 1..n - 1 {
+        if ys[i] >= ys[i - 1] && ys[i] >= ys[i + 1] && ys[i] > ys[i - 1].min(ys[i + 1]) {
+            raw.push(i);
+        }
+    }
+    raw.sort_by(|&a, &b| ys[b].partial_cmp(&ys[a]).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut kept: Vec<usize> = Vec::new();
+    for i in raw {
+        if kept.iter().all(|&k| (xs[i] - xs[k]).abs() >= min_sep) {
+            kept.push(i);
+        }
+    }
+    kept.sort();
+    kept
+}
+
+/// EM refinement of a weighted Gaussian mixture over samples `(xs, ys)`, with
+/// `ys` treated as unnormalized intensity weights. Seeds from `mus0`/`sigma0`
+/// with equal weights, iterates until the largest weight change drops below
+/// `tol` or `max_iter` is reached, then returns each component's fitted
+/// `(weight, mu, sigma)` plus the amplitude/area implied by `total_integral`.
+pub fn em_gaussian_mixture(
+    xs: &[f64],
+    ys: &[f32],
+    mus0: &[f64],
+    sigma0: f64,
+    total_integral: f64,
+    tol: f64,
+    max_iter: usize,
+) -> Vec<GaussianComponent> {
+    let k = mus0.len();
+    let n = xs.len();
+    if k == 0 || n == 0 {
+        return Vec::new();
+    }
+
+    let mut weights = vec![1.0 / k as f64; k];
+    let mut mus = mus0.to_vec();
+    let mut sigmas = vec![sigma0.max(1e-6); k];
+
+    let y64: Vec<f64> = ys.iter().map(|&v| v.max(0.0) as f64).collect();
+
+    for _ in 0..max_iter.max(1) {
+        // E-step: responsibilities r[i][c].
+        let mut resp = vec![vec![0.0; k]; n];
+        for i in 0..n {
+            let mut denom = 0.0;
+            for c in 0..k {
+                let v = weights[c] * gaussian_density(xs[i], mus[c], sigmas[c]);
+                resp[i][c] = v;
+                denom += v;
+            }
+            if denom > 0.0 {
+                for c in 0..k {
+                    resp[i][c] /= denom;
+                }
+            } else {
+                for c in 0..k {
+                    resp[i][c] = 1.0 / k as f64;
+                }
+            }
+        }
+
+        // M-step: weighted moments.
+        let mut nk = vec![0.0; k];
+        let mut new_mus = vec![0.0; k];
+        for i in 0..n {
+            for c in 0..k {
+                let w = y64[i] * resp[i][c];
+                nk[c] += w;
+                new_mus[c] += w * xs[i];
+            }
+        }
+        for c in 0..k {
+            if nk[c] > 1e-12 {
+                new_mus[c] /= nk[c];
+            } else {
+                new_mus[c] = mus[c];
+            }
+        }
+
+        let mut new_sigmas = vec![0.0; k];
+        for i in 0..n {
+            for c in 0..k {
+                let w = y64[i] * resp[i][c];
+                new_sigmas[c] += w * (xs[i] - new_mus[c]).powi(2);
+            }
+        }
+        for c in 0..k {
+            new_sigmas[c] = if nk[c] > 1e-12 {
+                (new_sigmas[c] / nk[c]).sqrt().max(1e-6)
+            } else {
+                sigmas[c]
+            };
+        }
+
+        let total_nk: f64 = nk.iter().sum();
+        let new_weights: Vec<f64> = if total_nk > 0.0 {
+            nk.iter().map(|&v| v / total_nk).collect()
+        } else {
+            weights.clone()
+        };
+
+        let max_change = new_weights
+            .iter()
+            .zip(&weights)
+            .fold(0.0, |m, (&a, &b)| m.max((a - b).abs()));
+
+        weights = new_weights;
+        mus = new_mus;
+        sigmas = new_sigmas;
+
+        if max_change < tol {
+            break;
+        }
+    }
+
+    weights
+        .iter()
+        .zip(&mus)
+        .zip(&sigmas)
+        .map(|((&w, &mu), &sigma)| {
+            let area = w * total_integral;
+            let amplitude = area / (sigma * SQRT_2PI);
+            GaussianComponent {
+                weight: w,
+                mu,
+                sigma,
+                amplitude,
+                area,
+            }
+        })
+        .collect()
+}
+
+/// Detect overlapping apices in `(xs, ys)` and, if two or more are found,
+/// resolve them into separate Gaussian components via EM. Returns `None`
+/// when the region looks single-peaked (fewer than 2 seeds, or pruning a
+/// spurious/under-weight component leaves fewer than 2), so callers fall
+/// back to the ordinary single-peak candidate.
+pub fn deconvolve_region(
+    xs: &[f64],
+    ys: &[f32],
+    total_integral: f64,
+    min_weight: f64,
+    min_sep: f64,
+) -> Option<Vec<GaussianComponent>> {
+    let seeds = detect_local_maxima(xs, ys, min_sep);
+    if seeds.len() < 2 {
+        return None;
+    }
+    let mus0: Vec<f64> = seeds.iter().map(|&i| xs[i]).collect();
+    let mut spacing = 0.0;
+    for w in mus0.windows(2) {
+        spacing += (w[1] - w[0]).abs();
+    }
+    let sigma0 = (spacing / (mus0.len() - 1) as f64 / 2.0).max(min_sep / 2.0);
+
+    let mut components = em_gaussian_mixture(xs, ys, &mus0, sigma0, total_integral, 1e-4, 100);
+    components.retain(|c| c.weight >= min_weight);
+
+    // Merge components that collapsed onto each other during EM.
+    components.sort_by(|a, b| a.mu.partial_cmp(&b.mu).unwrap_or(std::cmp::Ordering::Equal));
+    let mut merged: Vec<GaussianComponent> = Vec::with_capacity(components.len());
+    for c in components {
+        if let Some(last) = merged.last_mut() {
+            if (c.mu - last.mu).abs() < min_sep {
+                let w = last.weight + c.weight;
+                last.mu = (last.mu * last.weight + c.mu * c.weight) / w;
+                last.sigma = (last.sigma * last.weight + c.sigma * c.weight) / w;
+                last.weight = w;
+                last.area += c.area;
+                last.amplitude = last.area / (last.sigma * SQRT_2PI);
+                continue;
+            }
+        }
+        merged.push(c);
+    }
+
+    if merged.len() < 2 {
+        return None;
+    }
+    Some(merged)
+}
@@ -0,0 +1,159 @@
+use crate::utilities::calculate_eic::{CentroidScan, EicOptions, lower_bound, upper_bound};
+use std::collections::VecDeque;
+
+/// A chromatographic feature discovered by [`detect_mass_traces`] without a
+/// known `target_mass`: a connected region of peaks across consecutive MS1
+/// scans, summarized the same way a targeted EIC peak would be.
+#[derive(Clone, Debug)]
+pub struct MassTrace {
+    pub mz: f64,
+    pub rt_from: f64,
+    pub rt_to: f64,
+    pub intensity: f64,
+    pub apex_intensity: f64,
+    pub n_scans: usize,
+}
+
+pub struct DetectMassTracesOptions {
+    /// Tolerance used to decide whether a peak in an adjacent scan belongs
+    /// to the same trace as the current peak.
+    pub eic_options: EicOptions,
+    /// Peaks at or below this intensity are never used as seeds or
+    /// neighbors, same role as the noise floor in `find_noise_level`.
+    pub noise_threshold: f64,
+    /// Minimum number of connected scans for a region to be reported.
+    pub min_scans: usize,
+}
+
+impl Default for DetectMassTracesOptions {
+    fn default() -> Self {
+        Self {
+            eic_options: EicOptions::default(),
+            noise_threshold: 0.0,
+            min_scans: 3,
+        }
+    }
+}
+
+/// Untargeted peak picking over stacked MS1 scans (see `collect_ms1_scans`):
+/// each scan is a row in retention-time order and its centroided peaks are
+/// points along the m/z axis. Region growing (queue-based flood fill) picks
+/// the highest-intensity unvisited peak as a seed and connects it to peaks
+/// in the previous/next scan whose m/z falls within `eic_options`'
+/// tolerance and whose intensity clears `noise_threshold`; because m/z
+/// grids aren't aligned across scans, "neighbor" is tolerance matching via
+/// `lower_bound`/`upper_bound`, not integer grid index. A connected region
+/// spanning at least `min_scans` becomes a [`MassTrace`].
+pub fn detect_mass_traces(
+    scans: &[CentroidScan],
+    options: Option<DetectMassTracesOptions>,
+) -> Vec<MassTrace> {
+    let opts = options.unwrap_or_default();
+    if scans.is_empty() {
+        return Vec::new();
+    }
+
+    let mut visited: Vec<Vec<bool>> = scans.iter().map(|s| vec![false; s.mz.len()]).collect();
+
+    let mut seeds: Vec<(usize, usize)> = Vec::new();
+    for (si, s) in scans.iter().enumerate() {
+        for (pi, &inten) in s.intensity.iter().enumerate() {
+            if inten > opts.noise_threshold {
+                seeds.push((si, pi));
+            }
+        }
+    }
+    seeds.sort_by(|&(sa, pa), &(sb, pb)| {
+        scans[sb].intensity[pb]
+            .partial_cmp(&scans[sa].intensity[pa])
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let mut traces = Vec::new();
+
+    for (seed_scan, seed_peak) in seeds {
+        if visited[seed_scan][seed_peak] {
+            continue;
+        }
+
+        let mut region: Vec<(usize, usize)> = Vec::new();
+        let mut queue: VecDeque<(usize, usize)> = VecDeque::new();
+        visited[seed_scan][seed_peak] = true;
+        queue.push_back((seed_scan, seed_peak));
+
+        while let Some((si, pi)) = queue.pop_front() {
+            region.push((si, pi));
+            let mz = scans[si].mz[pi];
+
+            for &ni in &[si.wrapping_sub(1), si + 1] {
+                if ni == usize::MAX || ni >= scans.len() {
+                    continue;
+                }
+                for candidate in neighbors_within_tolerance(&scans[ni], mz, opts.eic_options) {
+                    if scans[ni].intensity[candidate] <= opts.noise_threshold
+                        || visited[ni][candidate]
+                    {
+                        continue;
+                    }
+                    visited[ni][candidate] = true;
+                    queue.push_back((ni, candidate));
+                }
+            }
+        }
+
+        let distinct_scans: std::collections::BTreeSet<usize> =
+            region.iter().map(|&(si, _)| si).collect();
+        if distinct_scans.len() < opts.min_scans {
+            continue;
+        }
+
+        let rt_from = distinct_scans
+            .iter()
+            .map(|&si| scans[si].rt)
+            .fold(f64::INFINITY, f64::min);
+        let rt_to = distinct_scans
+            .iter()
+            .map(|&si| scans[si].rt)
+            .fold(f64::NEG_INFINITY, f64::max);
+
+        let mut weighted_mz_sum = 0.0;
+        let mut intensity_sum = 0.0;
+        let mut apex_intensity = 0.0;
+        for &(si, pi) in &region {
+            let mz = scans[si].mz[pi];
+            let inten = scans[si].intensity[pi];
+            weighted_mz_sum += mz * inten;
+            intensity_sum += inten;
+            if inten > apex_intensity {
+                apex_intensity = inten;
+            }
+        }
+        if intensity_sum <= 0.0 {
+            continue;
+        }
+
+        traces.push(MassTrace {
+            mz: weighted_mz_sum / intensity_sum,
+            rt_from,
+            rt_to,
+            intensity: intensity_sum,
+            apex_intensity,
+            n_scans: distinct_scans.len(),
+        });
+    }
+
+    traces.sort_by(|a, b| a.rt_from.partial_cmp(&b.rt_from).unwrap_or(std::cmp::Ordering::Equal));
+    traces
+}
+
+fn neighbors_within_tolerance(scan: &CentroidScan, mz: f64, opts: EicOptions) -> Vec<usize> {
+    let tol_ppm = if opts.ppm_tolerance > 0.0 {
+        (opts.ppm_tolerance * 1e-6) * mz
+    } else {
+        0.0
+    };
+    let tol = tol_ppm.max(opts.mz_tolerance.max(0.0));
+    let lo = lower_bound(&scan.mz, mz - tol);
+    let hi = upper_bound(&scan.mz, mz + tol);
+    (lo..hi).collect()
+}
@@ -0,0 +1,72 @@
+use std::cmp::Ordering;
+
+use crate::utilities::calculate_eic::{CentroidScan, lower_bound, upper_bound};
+
+/// A scan-indexed LC-MS experiment: an ordered set of spectra, each carrying
+/// an RT and a list of (m/z, intensity) points sorted by m/z. Mirrors the
+/// shape OpenMS's `MSExperiment` gives its range-iteration API.
+#[derive(Clone, Debug, Default)]
+pub struct Experiment {
+    scans: Vec<CentroidScan>,
+}
+
+impl Experiment {
+    pub fn from_scans(mut scans: Vec<CentroidScan>) -> Self {
+        scans.sort_by(|a, b| a.rt.partial_cmp(&b.rt).unwrap_or(Ordering::Equal));
+        Self { scans }
+    }
+
+    pub fn scans(&self) -> &[CentroidScan] {
+        &self.scans
+    }
+
+    /// Iterate every `(rt, mz, intensity)` point within the
+    /// `[rt_min, rt_max] x [mz_min, mz_max]` box, scan by scan in RT order.
+    /// Analogous to OpenMS's `areaBegin`/`areaEnd` range iterators.
+    pub fn area(&self, rt_min: f64, rt_max: f64, mz_min: f64, mz_max: f64) -> AreaIter<'_> {
+        let rts: Vec<f64> = self.scans.iter().map(|s| s.rt).collect();
+        let scan_i = lower_bound(&rts, rt_min);
+        let scan_end = upper_bound(&rts, rt_max).min(self.scans.len());
+        AreaIter {
+            scans: &self.scans,
+            scan_i,
+            scan_end,
+            point_i: 0,
+            started: false,
+            mz_min,
+            mz_max,
+        }
+    }
+}
+
+pub struct AreaIter<'a> {
+    scans: &'a [CentroidScan],
+    scan_i: usize,
+    scan_end: usize,
+    point_i: usize,
+    started: bool,
+    mz_min: f64,
+    mz_max: f64,
+}
+
+impl Iterator for AreaIter<'_> {
+    type Item = (f64, f64, f64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.scan_i < self.scan_end {
+            let scan = &self.scans[self.scan_i];
+            if !self.started {
+                self.point_i = lower_bound(&scan.mz, self.mz_min);
+                self.started = true;
+            }
+            if self.point_i < scan.mz.len() && scan.mz[self.point_i] <= self.mz_max {
+                let item = (scan.rt, scan.mz[self.point_i], scan.intensity[self.point_i]);
+                self.point_i += 1;
+                return Some(item);
+            }
+            self.scan_i += 1;
+            self.started = false;
+        }
+        None
+    }
+}
@@ -4,11 +4,12 @@ use crate::utilities::calculate_eic::{
 };
 use crate::utilities::find_peaks::{FindPeaksOptions, find_peaks};
 use crate::utilities::parse::parse_mzml::MzML;
+use crate::utilities::quantile_summary::QuantileSummary;
 use crate::utilities::structs::{DataXY, FromTo, Peak};
 use rayon::ThreadPoolBuilder;
 use rayon::prelude::*;
 use std::cmp::Ordering;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 #[derive(Clone, Debug)]
 pub struct Feature {
@@ -18,8 +19,12 @@ pub struct Feature {
     pub from: f64,
     pub to: f64,
     pub np: usize,
+    /// Largest centroid intensity over the median of the nonzero centroid
+    /// intensities in the m/z refinement window, from [`refine_mz_for_peak`].
+    pub snr: f64,
 }
 
+#[derive(Clone, Copy)]
 pub struct MzScanGrid {
     pub mz_min: f64,
     pub mz_max: f64,
@@ -36,6 +41,7 @@ impl Default for MzScanGrid {
     }
 }
 
+#[derive(Clone)]
 pub struct FindFeaturesOptions {
     pub scan_eic_options: Option<EicOptions>,
     pub eic_options: Option<EicOptions>,
@@ -50,10 +56,12 @@ impl Default for FindFeaturesOptions {
             scan_eic_options: Some(EicOptions {
                 ppm_tolerance: 10.0,
                 mz_tolerance: 0.003,
+                ..Default::default()
             }),
             eic_options: Some(EicOptions {
                 ppm_tolerance: 20.0,
                 mz_tolerance: 0.005,
+                ..Default::default()
             }),
             find_peaks: Some(FindPeaksOptions::default()),
             mz_scan_grid: Some(MzScanGrid::default()),
@@ -131,7 +139,7 @@ pub fn find_features(
     pool.install(|| {
         let t1 = Instant::now();
 
-        let masses: Vec<f64> = grid
+        let masses: Vec<(f64, f64)> = grid
             .par_iter()
             .map(|&m| {
                 let y0 = compute_eic_for_mz(&scans, time.len(), &m, scan_eic_options);
@@ -172,7 +180,7 @@ pub fn find_features(
             panic!("[panic] refine_mz_for_peak returned empty list");
         }
 
-        let unique_masses: Vec<f64> = dedup_masses_dynamic(masses, eic_options);
+        let unique_masses: Vec<(f64, f64)> = dedup_masses_dynamic(masses, eic_options);
         eprintln!("[find_features] unique_masses={}", unique_masses.len());
         if unique_masses.is_empty() {
             eprintln!("[warn] no unique masses after dedup");
@@ -182,7 +190,7 @@ pub fn find_features(
 
         let mut features_raw: Vec<Feature> = unique_masses
             .par_iter()
-            .flat_map(|&mz| {
+            .flat_map(|&(mz, snr)| {
                 let y = compute_eic_for_mz(&scans, time.len(), &mz, eic_options);
                 let data = DataXY { x: time.clone(), y };
                 let peaks = find_peaks(&data, Some(find_peak_options.clone()));
@@ -205,6 +213,7 @@ pub fn find_features(
                         from: p.from,
                         to: p.to,
                         np: p.np,
+                        snr,
                     })
                     .collect::<Vec<_>>()
             })
@@ -252,18 +261,18 @@ pub fn find_features(
     })
 }
 
-fn dedup_masses_dynamic(mut ms: Vec<f64>, opts: EicOptions) -> Vec<f64> {
+fn dedup_masses_dynamic(mut ms: Vec<(f64, f64)>, opts: EicOptions) -> Vec<(f64, f64)> {
     if ms.is_empty() {
         return ms;
     }
-    ms.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    ms.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
     let mut out = Vec::with_capacity(ms.len());
     let mut last = ms[0];
     out.push(last);
-    for &m in ms.iter().skip(1) {
-        if !mass_close_dynamic(m, last, opts) {
-            out.push(m);
-            last = m;
+    for &(m, snr) in ms.iter().skip(1) {
+        if !mass_close_dynamic(m, last.0, opts) {
+            out.push((m, snr));
+            last = (m, snr);
         }
     }
     out
@@ -288,12 +297,12 @@ fn refine_mz_for_peak(
     rt_from: f64,
     rt_to: f64,
     opts: EicOptions,
-) -> f64 {
+) -> (f64, f64) {
     let i0 = lower_bound(rt, rt_from);
     let i1 = upper_bound(rt, rt_to).min(scans.len());
     if i0 >= i1 {
         eprintln!("[warn] refine window has no scans for m={}", approx);
-        return approx;
+        return (approx, 0.0);
     }
 
     let tol_ppm = if opts.ppm_tolerance > 0.0 {
@@ -313,20 +322,15 @@ fn refine_mz_for_peak(
         panic!("[panic] invalid refine span for m={}", approx);
     }
 
-    let bin_da = (span / 400.0).max(1e-9);
-    if !(bin_da.is_finite()) || bin_da <= 0.0 {
-        panic!("[panic] invalid bin_da for m={}", approx);
-    }
-
-    let n_bins = ((span / bin_da).ceil() as usize).saturating_add(1);
-    if n_bins == 0 {
-        panic!("[panic] zero bins for m={}", approx);
-    }
-    if n_bins > 20_000_000 {
-        panic!("[panic] too many bins: {} for m={}", n_bins, approx);
-    }
-
-    let mut bins = vec![0.0f64; n_bins];
+    // Intensity-weighted approximate median m/z over the window, instead
+    // of the argmax of a fixed-width histogram: each centroid m/z is fed
+    // into the quantile summary with its intensity as a rank weight, so a
+    // few spurious high bins can't pull the estimate the way a coarse
+    // histogram's winning bin could, and the result isn't quantized to any
+    // bin width.
+    let mut qs = QuantileSummary::new(opts.epsilon);
+    let mut intensities: Vec<f64> = Vec::new();
+    let mut max_intensity = 0.0f64;
 
     for s in i0..i1 {
         let mzs = &scans[s].mz;
@@ -340,12 +344,10 @@ fn refine_mz_for_peak(
             }
             let it = ints[j] as f64;
             if it.is_finite() && it > 0.0 && m.is_finite() {
-                let idx_f = (m - lo) / bin_da;
-                if idx_f.is_finite() {
-                    let idx = idx_f.floor() as isize;
-                    if idx >= 0 && (idx as usize) < n_bins {
-                        bins[idx as usize] += it;
-                    }
+                qs.update_weighted(m, it);
+                intensities.push(it);
+                if it > max_intensity {
+                    max_intensity = it;
                 }
             }
             j += 1;
@@ -356,53 +358,36 @@ fn refine_mz_for_peak(
         }
     }
 
-    if bins.iter().all(|&v| v <= 0.0) {
-        return approx;
+    if qs.is_empty() {
+        return (approx, 0.0);
     }
 
-    let w_da = tol;
-    let mut w_bins = (w_da / bin_da).round() as isize;
-    if w_bins < 1 {
-        w_bins = 1;
+    let mz = qs.query(0.5);
+    if !mz.is_finite() {
+        eprintln!("[warn] non-finite refined mz for m={}", approx);
+        return (approx, 0.0);
     }
-    let w_bins = w_bins as usize;
 
-    let mut ps = vec![0.0f64; n_bins + 1];
-    for i in 0..n_bins {
-        ps[i + 1] = ps[i] + bins[i];
-    }
+    let median = median_of_nonzero(&intensities);
+    let snr = if median > 0.0 { max_intensity / median } else { 0.0 };
 
-    let mut best_sum = -1.0f64;
-    let mut best_i = 0usize;
-    if n_bins >= w_bins {
-        for i in 0..=(n_bins - w_bins) {
-            let s = ps[i + w_bins] - ps[i];
-            if s > best_sum {
-                best_sum = s;
-                best_i = i;
-            }
-        }
-    } else {
-        best_i = 0;
-    }
+    (mz, snr)
+}
 
-    let start = best_i;
-    let end = (best_i + w_bins).min(n_bins);
-    let mut max_v = -1.0f64;
-    let mut max_k = start;
-    for k in start..end {
-        if bins[k] > max_v {
-            max_v = bins[k];
-            max_k = k;
-        }
+/// Median of the nonzero values in `xs`, `0.0` if there are none. Used by
+/// [`refine_mz_for_peak`] as the noise estimate for its per-feature SNR.
+fn median_of_nonzero(xs: &[f64]) -> f64 {
+    let mut vals: Vec<f64> = xs.iter().copied().filter(|&v| v > 0.0).collect();
+    if vals.is_empty() {
+        return 0.0;
     }
-
-    let mz = lo + (max_k as f64 + 0.5) * bin_da;
-    if !mz.is_finite() {
-        eprintln!("[warn] non-finite refined mz for m={}", approx);
-        return approx;
+    vals.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    let mid = vals.len() / 2;
+    if vals.len() % 2 == 0 {
+        (vals[mid - 1] + vals[mid]) / 2.0
+    } else {
+        vals[mid]
     }
-    mz
 }
 
 fn build_mz_grid(start: f64, end: f64, step_da: f64) -> Vec<f64> {
@@ -606,3 +591,414 @@ fn dedup_features_dynamic_ppm(
     }
     final_out
 }
+
+/// Average spacing in Da between consecutive isotope peaks of a
+/// singly-charged ion, divided by `z` for higher charge states.
+const ISOTOPE_SPACING_DA: f64 = 1.00286;
+
+/// Options for [`group_isotope_envelopes`].
+#[derive(Clone, Copy, Debug)]
+pub struct IsotopeGroupingOptions {
+    /// Highest charge state tried when looking for isotope spacing.
+    pub max_charge: usize,
+    /// Chains shorter than this (including single, charge-unknown
+    /// features) are still emitted, just with `charge: 0`.
+    pub min_envelope_len: usize,
+    /// Minimum [`rt_overlap_fraction_min`] for two features to be
+    /// considered co-eluting and bucketed together.
+    pub rt_overlap_threshold: f64,
+}
+
+impl Default for IsotopeGroupingOptions {
+    fn default() -> Self {
+        Self {
+            max_charge: 3,
+            min_envelope_len: 2,
+            rt_overlap_threshold: 0.80,
+        }
+    }
+}
+
+/// A chain of co-eluting, evenly-spaced `Feature`s taken to be one
+/// isotopic envelope, from [`group_isotope_envelopes`]. `charge: 0` means
+/// no chain of at least `min_envelope_len` peaks was found starting here;
+/// the feature passes through alone as a charge-unknown envelope.
+#[derive(Clone, Debug)]
+pub struct IsotopeEnvelope {
+    pub monoisotopic_mz: f64,
+    pub charge: usize,
+    pub features: Vec<Feature>,
+    pub total_intensity: f64,
+}
+
+/// Groups co-eluting features into isotopic envelopes and assigns a
+/// charge state, meant to run as an optional post-processing pass after
+/// [`dedup_features_dynamic_ppm`]'s output.
+///
+/// Features are bucketed by RT-window overlap (chained, like
+/// [`dedup_features_dynamic_ppm`]'s own clustering), then within each
+/// bucket every feature is tried as a chain seed at every charge
+/// `1..=max_charge`: walking forward through the m/z-sorted bucket, the
+/// nearest peak within tolerance of the previous chain member's
+/// `mz + 1.00286 / z` is picked greedily, rejecting an isotope whose intensity overshoots its
+/// predecessor by more than 50% (an implausible jump for a decaying
+/// isotope envelope). Overlapping candidate chains are resolved by
+/// keeping the longest one first, then the one with the higher summed
+/// intensity, mirroring [`better`]'s tiebreak; once a feature is claimed
+/// by a chain it can't join another. Anything left unclaimed — including
+/// every feature when `max_charge` or `min_envelope_len` rule out all
+/// chains — passes through as its own single-feature, charge-unknown
+/// envelope.
+pub fn group_isotope_envelopes(
+    features: &[Feature],
+    eic: EicOptions,
+    options: Option<IsotopeGroupingOptions>,
+) -> Vec<IsotopeEnvelope> {
+    let opts = options.unwrap_or_default();
+    if features.is_empty() {
+        return Vec::new();
+    }
+
+    let mut sorted: Vec<Feature> = features.to_vec();
+    sorted.sort_by(|a, b| a.rt.partial_cmp(&b.rt).unwrap_or(Ordering::Equal));
+
+    let mut buckets: Vec<Vec<Feature>> = Vec::new();
+    let mut current: Vec<Feature> = Vec::new();
+    for f in sorted.into_iter() {
+        if current.is_empty() {
+            current.push(f);
+            continue;
+        }
+        let last = current.last().unwrap();
+        let overlap = rt_overlap_fraction_min(last.from, last.to, f.from, f.to);
+        if overlap >= opts.rt_overlap_threshold {
+            current.push(f);
+        } else {
+            buckets.push(std::mem::take(&mut current));
+            current.push(f);
+        }
+    }
+    if !current.is_empty() {
+        buckets.push(current);
+    }
+
+    let mut envelopes = Vec::new();
+    for bucket in buckets {
+        envelopes.extend(group_isotope_bucket(bucket, eic, &opts));
+    }
+    envelopes
+}
+
+struct IsotopeChain {
+    indices: Vec<usize>,
+    charge: usize,
+}
+
+fn group_isotope_bucket(
+    mut bucket: Vec<Feature>,
+    eic: EicOptions,
+    opts: &IsotopeGroupingOptions,
+) -> Vec<IsotopeEnvelope> {
+    bucket.sort_by(|a, b| a.mz.partial_cmp(&b.mz).unwrap_or(Ordering::Equal));
+    let n = bucket.len();
+    if n == 0 {
+        return Vec::new();
+    }
+
+    let mut chains: Vec<IsotopeChain> = Vec::new();
+    for start in 0..n {
+        for z in 1..=opts.max_charge.max(1) {
+            let spacing = ISOTOPE_SPACING_DA / z as f64;
+            let mut indices = vec![start];
+            let mut cur = start;
+            loop {
+                let cur_mz = bucket[cur].mz;
+                let cur_intensity = bucket[cur].intensity;
+                let target = cur_mz + spacing;
+                let tol_ppm = if eic.ppm_tolerance > 0.0 {
+                    eic.ppm_tolerance * 1e-6 * target
+                } else {
+                    0.0
+                };
+                let tol = tol_ppm.max(eic.mz_tolerance.max(0.0));
+
+                let mut best: Option<usize> = None;
+                let mut best_d = f64::INFINITY;
+                for cand in (cur + 1)..n {
+                    let d = bucket[cand].mz - target;
+                    if d > tol {
+                        break; // sorted by m/z: nothing further can be closer
+                    }
+                    if d.abs() > tol {
+                        continue;
+                    }
+                    if bucket[cand].intensity > cur_intensity * 1.5 {
+                        continue;
+                    }
+                    if d.abs() < best_d {
+                        best_d = d.abs();
+                        best = Some(cand);
+                    }
+                }
+                match best {
+                    Some(next) => {
+                        indices.push(next);
+                        cur = next;
+                    }
+                    None => break,
+                }
+            }
+            if indices.len() >= opts.min_envelope_len.max(1) {
+                chains.push(IsotopeChain { indices, charge: z });
+            }
+        }
+    }
+
+    chains.sort_by(|a, b| {
+        b.indices.len().cmp(&a.indices.len()).then_with(|| {
+            let sa: f64 = a.indices.iter().map(|&i| bucket[i].intensity).sum();
+            let sb: f64 = b.indices.iter().map(|&i| bucket[i].intensity).sum();
+            sb.partial_cmp(&sa).unwrap_or(Ordering::Equal)
+        })
+    });
+
+    let mut claimed = vec![false; n];
+    let mut envelopes = Vec::new();
+    for chain in chains {
+        if chain.indices.iter().any(|&i| claimed[i]) {
+            continue;
+        }
+        for &i in &chain.indices {
+            claimed[i] = true;
+        }
+        let chain_features: Vec<Feature> = chain.indices.iter().map(|&i| bucket[i].clone()).collect();
+        let total_intensity: f64 = chain_features.iter().map(|f| f.intensity).sum();
+        let monoisotopic_mz = chain_features[0].mz;
+        envelopes.push(IsotopeEnvelope {
+            monoisotopic_mz,
+            charge: chain.charge,
+            features: chain_features,
+            total_intensity,
+        });
+    }
+
+    for (i, f) in bucket.into_iter().enumerate() {
+        if !claimed[i] {
+            envelopes.push(IsotopeEnvelope {
+                monoisotopic_mz: f.mz,
+                charge: 0,
+                total_intensity: f.intensity,
+                features: vec![f],
+            });
+        }
+    }
+
+    envelopes
+}
+
+/// The tunable subset of [`FindFeaturesOptions`] that
+/// [`optimize_find_features_options`] searches over, flattened into plain
+/// `f64`s so simulated annealing can perturb one field at a time without
+/// threading `Option<...>` unwrapping through every step.
+#[derive(Clone, Copy, Debug)]
+struct ParamVector {
+    scan_ppm: f64,
+    scan_mz_tol: f64,
+    eic_ppm: f64,
+    eic_mz_tol: f64,
+    step_size: f64,
+    scan_width_threshold: f64,
+    integral_threshold: f64,
+    intensity_threshold: f64,
+}
+
+impl ParamVector {
+    fn from_options(opts: &FindFeaturesOptions) -> Self {
+        let scan_eic = opts.scan_eic_options.unwrap_or_default();
+        let eic = opts.eic_options.unwrap_or_default();
+        let grid = opts.mz_scan_grid.unwrap_or_default();
+        let filter = opts
+            .find_peaks
+            .as_ref()
+            .and_then(|fp| fp.filter_peaks_options)
+            .unwrap_or_default();
+        Self {
+            scan_ppm: scan_eic.ppm_tolerance,
+            scan_mz_tol: scan_eic.mz_tolerance,
+            eic_ppm: eic.ppm_tolerance,
+            eic_mz_tol: eic.mz_tolerance,
+            step_size: grid.step_size,
+            scan_width_threshold: opts.scan_width_threshold.unwrap_or(5) as f64,
+            integral_threshold: filter.integral_threshold.unwrap_or(0.0),
+            intensity_threshold: filter.intensity_threshold.unwrap_or(0.0),
+        }
+    }
+
+    fn to_options(self, base: &FindFeaturesOptions) -> FindFeaturesOptions {
+        let mut opts = base.clone();
+
+        let mut scan_eic = opts.scan_eic_options.unwrap_or_default();
+        scan_eic.ppm_tolerance = self.scan_ppm;
+        scan_eic.mz_tolerance = self.scan_mz_tol;
+        opts.scan_eic_options = Some(scan_eic);
+
+        let mut eic = opts.eic_options.unwrap_or_default();
+        eic.ppm_tolerance = self.eic_ppm;
+        eic.mz_tolerance = self.eic_mz_tol;
+        opts.eic_options = Some(eic);
+
+        let mut grid = opts.mz_scan_grid.unwrap_or_default();
+        grid.step_size = self.step_size;
+        opts.mz_scan_grid = Some(grid);
+
+        opts.scan_width_threshold = Some(self.scan_width_threshold.round().max(1.0) as usize);
+
+        let mut fp = opts.find_peaks.unwrap_or_default();
+        let mut filter = fp.filter_peaks_options.unwrap_or_default();
+        filter.integral_threshold = Some(self.integral_threshold.max(0.0));
+        filter.intensity_threshold = Some(self.intensity_threshold.max(0.0));
+        fp.filter_peaks_options = Some(filter);
+        opts.find_peaks = Some(fp);
+
+        opts
+    }
+
+    /// Perturbs one randomly chosen field by a relative jitter of up to
+    /// ±10% (the width threshold is perturbed in absolute bin units
+    /// instead, since a relative jitter on a small integer barely moves
+    /// it), returning a new candidate vector.
+    fn perturb(self, state: &mut u64) -> Self {
+        let mut v = self;
+        let idx = (lcg_next(state) as usize) % 8;
+        let jitter = (lcg_f64(state) - 0.5) * 0.2;
+        match idx {
+            0 => v.scan_ppm = (v.scan_ppm * (1.0 + jitter)).max(0.1),
+            1 => v.scan_mz_tol = (v.scan_mz_tol * (1.0 + jitter)).max(1e-5),
+            2 => v.eic_ppm = (v.eic_ppm * (1.0 + jitter)).max(0.1),
+            3 => v.eic_mz_tol = (v.eic_mz_tol * (1.0 + jitter)).max(1e-5),
+            4 => v.step_size = (v.step_size * (1.0 + jitter)).max(1e-5),
+            5 => v.scan_width_threshold = (v.scan_width_threshold + jitter * 10.0).max(1.0),
+            6 => v.integral_threshold = (v.integral_threshold * (1.0 + jitter)).max(0.0),
+            _ => v.intensity_threshold = (v.intensity_threshold * (1.0 + jitter)).max(0.0),
+        }
+        v
+    }
+}
+
+/// Deterministic LCG (same constants used crate-wide for seeded sampling,
+/// see [`crate::utilities::kmeans`]).
+#[inline]
+fn lcg_next(state: &mut u64) -> u64 {
+    *state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+    *state
+}
+
+#[inline]
+fn lcg_f64(state: &mut u64) -> f64 {
+    (lcg_next(state) >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Count of `reference` features matched by a candidate within m/z
+/// tolerance ([`mass_close_for_dedup`]) and RT overlap
+/// ([`rt_overlap_fraction`]) of at least `min_rt_overlap`, minus
+/// `EXTRA_PENALTY` for every candidate that matched nothing in
+/// `reference` — so the optimizer can't buy recall by over-calling
+/// features indiscriminately.
+fn score_against_reference(
+    candidates: &[Feature],
+    reference: &[Feature],
+    eic: EicOptions,
+    min_rt_overlap: f64,
+) -> f64 {
+    const EXTRA_PENALTY: f64 = 0.25;
+
+    let mut candidate_matched = vec![false; candidates.len()];
+    let mut matched_refs = 0usize;
+    for r in reference {
+        let mut found = false;
+        for (i, c) in candidates.iter().enumerate() {
+            if mass_close_for_dedup(r.mz, c.mz, eic)
+                && rt_overlap_fraction(r.from, r.to, c.from, c.to) >= min_rt_overlap
+            {
+                candidate_matched[i] = true;
+                found = true;
+            }
+        }
+        if found {
+            matched_refs += 1;
+        }
+    }
+    let extra = candidate_matched.iter().filter(|&&m| !m).count();
+    matched_refs as f64 - EXTRA_PENALTY * extra as f64
+}
+
+/// Simulated-annealing search over [`FindFeaturesOptions`]'s main
+/// tolerances (`scan_eic_options`/`eic_options` `ppm_tolerance` and
+/// `mz_tolerance`, `mz_scan_grid.step_size`, `scan_width_threshold`, and
+/// the `FindPeaksOptions` integral/intensity thresholds) that maximizes
+/// agreement with a user-supplied `reference` feature list, so a pipeline
+/// can be calibrated against a gold-standard dataset instead of by hand.
+///
+/// On each iteration one parameter is perturbed, `find_features` is
+/// rerun, and the candidate is accepted if it scores higher or, if not,
+/// with probability `exp((new_score - score) / temperature)` — letting
+/// the search escape local optima early on while it still accepts
+/// occasional regressions. The temperature cools geometrically from `T0`
+/// to `T1` over `budget`; once the budget elapses, the best-scoring
+/// options seen are returned (which may not be the last state visited,
+/// since annealing can end on an accepted-but-worse step).
+pub fn optimize_find_features_options(
+    mzml: &MzML,
+    time_window: FromTo,
+    reference: &[Feature],
+    budget: Duration,
+    cores: usize,
+) -> FindFeaturesOptions {
+    const T0: f64 = 1.0;
+    const T1: f64 = 0.01;
+    const MIN_RT_OVERLAP: f64 = 0.80;
+
+    let base = FindFeaturesOptions::default();
+    let mut state = ParamVector::from_options(&base);
+    let mut rng: u64 = 0x9E3779B97F4A7C15;
+
+    let run_and_score = |p: ParamVector| -> f64 {
+        let opts = p.to_options(&base);
+        let eic = opts.eic_options.unwrap_or_default();
+        let found = find_features(mzml, time_window, Some(opts), cores);
+        score_against_reference(&found, reference, eic, MIN_RT_OVERLAP)
+    };
+
+    let mut score = run_and_score(state);
+    let mut best = state;
+    let mut best_score = score;
+
+    let t0 = Instant::now();
+    let budget_secs = budget.as_secs_f64().max(1e-9);
+
+    while t0.elapsed() < budget {
+        let elapsed_fraction = (t0.elapsed().as_secs_f64() / budget_secs).min(1.0);
+        let temperature = T0 * (T1 / T0).powf(elapsed_fraction);
+
+        let candidate = state.perturb(&mut rng);
+        let candidate_score = run_and_score(candidate);
+
+        let accept = if candidate_score > score {
+            true
+        } else {
+            let p = ((candidate_score - score) / temperature).exp();
+            lcg_f64(&mut rng) < p
+        };
+
+        if accept {
+            state = candidate;
+            score = candidate_score;
+            if score > best_score {
+                best = state;
+                best_score = score;
+            }
+        }
+    }
+
+    best.to_options(&base)
+}
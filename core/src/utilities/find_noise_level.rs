@@ -1,12 +1,125 @@
+use rayon::{ThreadPoolBuilder, prelude::*};
+
 use crate::utilities::kmeans::{Point, kmeans};
+use crate::utilities::quantile_summary::QuantileSummary;
 use crate::utilities::utilities::is_finite_positive;
 
+/// Options for [`find_noise_level_with_options`].
+#[derive(Clone, Copy, Debug)]
+pub struct NoiseOptions {
+    /// Allowed rank error of the [`QuantileSummary`] used for the per-window
+    /// `q_low`/`q_cap` estimates and the final pool quantile, in place of
+    /// sorting the whole window/pool.
+    pub epsilon: f64,
+}
+
+impl Default for NoiseOptions {
+    fn default() -> Self {
+        Self { epsilon: 0.01 }
+    }
+}
+
+/// Options for the position-dependent noise estimator, [`windowed_noise_level`].
+#[derive(Clone, Copy, Debug)]
+pub struct WindowedNoiseOptions {
+    /// Number of samples per sliding block.
+    pub block: usize,
+    /// Allowed rank error of the underlying [`QuantileSummary`].
+    pub epsilon: f64,
+    /// Percentile of first-difference magnitudes reported as the block's noise.
+    pub percentile: f64,
+}
+
+impl Default for WindowedNoiseOptions {
+    fn default() -> Self {
+        Self {
+            block: 256,
+            epsilon: 0.01,
+            percentile: 0.25,
+        }
+    }
+}
+
+/// Position-dependent noise estimate, one value per sample in `y`, for
+/// traces whose baseline noise is heteroscedastic (e.g. rising with signal).
+/// Each non-overlapping block of `opts.block` samples gets its own noise
+/// level: the `opts.percentile`-th quantile of that block's first-difference
+/// magnitudes, estimated via a [`QuantileSummary`] rather than a full sort.
+/// Blocks shorter than 8 samples use the plain median of their
+/// first-difference magnitudes instead; traces with fewer than 2 samples
+/// get `0.0` everywhere (an inert noise floor the caller should treat as
+/// "no estimate").
+pub fn windowed_noise_level(y: &[f64], opts: WindowedNoiseOptions) -> Vec<f64> {
+    let n = y.len();
+    let mut out = vec![0.0; n];
+    if n < 2 {
+        return out;
+    }
+    let block = opts.block.max(1);
+
+    let mut i = 0usize;
+    while i < n {
+        let end = (i + block).min(n);
+        let block_noise = if end - i < 8 {
+            // Too few samples for a stable quantile; fall back to the raw
+            // MAD-style spread of first-differences in this short block.
+            let diffs: Vec<f64> = (i.max(1)..end).map(|k| (y[k] - y[k - 1]).abs()).collect();
+            median_of(diffs)
+        } else {
+            let mut summary = QuantileSummary::new(opts.epsilon);
+            for k in i.max(1)..end {
+                summary.update((y[k] - y[k - 1]).abs());
+            }
+            let v = summary.query(opts.percentile);
+            if v.is_finite() { v } else { 0.0 }
+        };
+        for slot in out[i..end].iter_mut() {
+            *slot = block_noise;
+        }
+        i = end;
+    }
+    out
+}
+
+fn median_of(mut v: Vec<f64>) -> f64 {
+    if v.is_empty() {
+        return 0.0;
+    }
+    v.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let mid = v.len() / 2;
+    if v.len() % 2 == 0 {
+        0.5 * (v[mid - 1] + v[mid])
+    } else {
+        v[mid]
+    }
+}
+
 pub fn find_noise_level(intensities: &[f32]) -> f32 {
+    find_noise_level_with_options(intensities, None)
+}
+
+/// Same as [`find_noise_level`], with the [`QuantileSummary`] rank error
+/// used for the window/pool quantile estimates configurable via `options`.
+pub fn find_noise_level_with_options(intensities: &[f32], options: Option<NoiseOptions>) -> f32 {
+    find_noise_level_with_cores(intensities, options, 1)
+}
+
+/// Same as [`find_noise_level_with_options`], spreading the per-window and
+/// pool `QuantileSummary` work across `cores` rayon threads the way
+/// [`crate::utilities::get_peaks_from_eic::get_peaks_from_eic`] parallelizes
+/// over ROIs. Falls back to the serial path when `cores <= 1` or the window
+/// count is too small to be worth the thread-pool setup.
+pub fn find_noise_level_with_cores(
+    intensities: &[f32],
+    options: Option<NoiseOptions>,
+    cores: usize,
+) -> f32 {
     let n = intensities.len();
     if n == 0 {
         return f32::INFINITY;
     }
-    let est = noise_from_series(intensities);
+    let epsilon = options.unwrap_or_default().epsilon;
+    let est = noise_from_series(intensities, epsilon, cores);
     if est.is_finite() && est > 0.0 {
         est
     } else {
@@ -14,13 +127,13 @@ pub fn find_noise_level(intensities: &[f32]) -> f32 {
     }
 }
 
-fn noise_from_series(y: &[f32]) -> f32 {
+fn noise_from_series(y: &[f32], epsilon: f64, cores: usize) -> f32 {
     let n = y.len();
     if n < 128 {
         return f32::INFINITY;
     }
     let (w, s) = window_plan(n);
-    let (bas, caps, spans) = window_low_quantiles(y, w, s, 0.20, 0.30);
+    let (bas, caps, spans) = window_low_quantiles(y, w, s, 0.20, 0.30, epsilon, cores);
     if bas.len() < 4 {
         let mut lows = Vec::<f32>::new();
         for (i, (a, b)) in spans.iter().enumerate() {
@@ -32,7 +145,7 @@ fn noise_from_series(y: &[f32]) -> f32 {
             }
         }
         if lows.is_empty() {
-            let mut v: Vec<f32> = y
+            let v: Vec<f32> = y
                 .iter()
                 .copied()
                 .filter(|x| is_finite_positive(*x))
@@ -40,11 +153,9 @@ fn noise_from_series(y: &[f32]) -> f32 {
             if v.is_empty() {
                 return f32::INFINITY;
             }
-            v.sort_by(|a, b| a.partial_cmp(b).unwrap());
-            return vec_quantile_sorted(&v, 0.80);
+            return approx_quantile(&v, 0.80, epsilon);
         }
-        lows.sort_by(|a, b| a.partial_cmp(b).unwrap());
-        return vec_quantile_sorted(&lows, 0.995);
+        return approx_quantile(&lows, 0.995, epsilon);
     }
 
     let mut logs = Vec::<f32>::with_capacity(bas.len());
@@ -56,26 +167,12 @@ fn noise_from_series(y: &[f32]) -> f32 {
     let thr_log = if let Some((m0, m1)) = sep {
         let d = (m1 - m0).abs();
         if d < 0.12 {
-            vec_quantile_sorted(
-                &{
-                    let mut t = logs.clone();
-                    t.sort_by(|a, b| a.partial_cmp(b).unwrap());
-                    t
-                },
-                0.75,
-            )
+            approx_quantile(&logs, 0.75, epsilon)
         } else {
             0.5 * (m0 + m1)
         }
     } else {
-        vec_quantile_sorted(
-            &{
-                let mut t = logs.clone();
-                t.sort_by(|a, b| a.partial_cmp(b).unwrap());
-                t
-            },
-            0.75,
-        )
+        approx_quantile(&logs, 0.75, epsilon)
     };
 
     let thr = 10f32.powf(thr_log);
@@ -90,9 +187,9 @@ fn noise_from_series(y: &[f32]) -> f32 {
     }
 
     let mut pool = if any > 0 {
-        pool_from_windows(y, &spans, &caps, &sel)
+        pooled_summary(y, &spans, &caps, &sel, epsilon, cores)
     } else {
-        Vec::new()
+        QuantileSummary::new(epsilon)
     };
 
     if pool.len() < y.len() / 200 {
@@ -105,7 +202,7 @@ fn noise_from_series(y: &[f32]) -> f32 {
         for k in (idx.len().saturating_sub(keep))..idx.len() {
             sel[idx[k]] = true;
         }
-        pool = pool_from_windows(y, &spans, &caps, &sel);
+        pool = pooled_summary(y, &spans, &caps, &sel, epsilon, cores);
     }
 
     if pool.is_empty() {
@@ -119,7 +216,7 @@ fn noise_from_series(y: &[f32]) -> f32 {
             }
         }
         if lows.is_empty() {
-            let mut v: Vec<f32> = y
+            let v: Vec<f32> = y
                 .iter()
                 .copied()
                 .filter(|x| is_finite_positive(*x))
@@ -127,15 +224,12 @@ fn noise_from_series(y: &[f32]) -> f32 {
             if v.is_empty() {
                 return f32::INFINITY;
             }
-            v.sort_by(|a, b| a.partial_cmp(b).unwrap());
-            return vec_quantile_sorted(&v, 0.80);
+            return approx_quantile(&v, 0.80, epsilon);
         }
-        lows.sort_by(|a, b| a.partial_cmp(b).unwrap());
-        return vec_quantile_sorted(&lows, 0.995);
+        return approx_quantile(&lows, 0.995, epsilon);
     }
 
-    pool.sort_by(|a, b| a.partial_cmp(b).unwrap());
-    vec_quantile_sorted(&pool, 0.995)
+    pool.query(0.995) as f32
 }
 
 fn kmeans_1d_logs(x: &[f32], _iters: usize) -> Option<(f32, f32)> {
@@ -178,23 +272,20 @@ fn kmeans_1d_logs(x: &[f32], _iters: usize) -> Option<(f32, f32)> {
     }
 }
 
-#[inline]
-fn vec_quantile_sorted(v: &[f32], q: f32) -> f32 {
+/// Estimate the `q`-quantile of `v` via a one-pass [`QuantileSummary`]
+/// instead of sorting the whole slice.
+fn approx_quantile(v: &[f32], q: f32, epsilon: f64) -> f32 {
     if v.is_empty() {
         return f32::NAN;
     }
     if v.len() == 1 {
         return v[0];
     }
-    let r = (q.clamp(0.0, 1.0) * (v.len() as f32 - 1.0)) as f32;
-    let i = r.floor() as usize;
-    let j = r.ceil() as usize;
-    if i == j {
-        v[i]
-    } else {
-        let w = r - i as f32;
-        v[i] * (1.0 - w) + v[j] * w
+    let mut summary = QuantileSummary::new(epsilon);
+    for &x in v {
+        summary.update(x as f64);
     }
+    summary.query(q as f64) as f32
 }
 
 fn window_plan(n: usize) -> (usize, usize) {
@@ -212,57 +303,102 @@ fn window_low_quantiles(
     s: usize,
     q_low: f32,
     q_cap: f32,
+    epsilon: f64,
+    cores: usize,
 ) -> (Vec<f32>, Vec<f32>, Vec<(usize, usize)>) {
-    let mut bas = Vec::<f32>::new();
-    let mut caps = Vec::<f32>::new();
-    let mut spans = Vec::<(usize, usize)>::new();
     let n = y.len();
     if n == 0 {
-        return (bas, caps, spans);
+        return (Vec::new(), Vec::new(), Vec::new());
     }
+
+    let mut windows = Vec::<(usize, usize)>::new();
     let mut i = 0usize;
     while i < n {
         let a = i;
         let b = (i + w).min(n);
-        let mut v: Vec<f32> = y[a..b]
-            .iter()
-            .copied()
-            .filter(|x| is_finite_positive(*x))
-            .collect();
-        if v.len() >= 32 {
-            v.sort_by(|a, b| a.partial_cmp(b).unwrap());
-            let p20 = vec_quantile_sorted(&v, q_low);
-            let p30 = vec_quantile_sorted(&v, q_cap).max(p20);
-            if p20.is_finite() && p20 > 0.0 {
-                bas.push(p20);
-                caps.push(p30);
-                spans.push((a, b));
-            }
-        }
+        windows.push((a, b));
         if b == n {
             break;
         }
         i = a + s;
     }
+
+    let local = |&(a, b): &(usize, usize)| -> Option<(f32, f32, (usize, usize))> {
+        let mut summary = QuantileSummary::new(epsilon);
+        let mut count = 0usize;
+        for &x in &y[a..b] {
+            if is_finite_positive(x) {
+                summary.update(x as f64);
+                count += 1;
+            }
+        }
+        if count < 32 {
+            return None;
+        }
+        let p20 = summary.query(q_low as f64) as f32;
+        let p30 = (summary.query(q_cap as f64) as f32).max(p20);
+        (p20.is_finite() && p20 > 0.0).then_some((p20, p30, (a, b)))
+    };
+
+    let results: Vec<Option<(f32, f32, (usize, usize))>> =
+        if cores > 1 && windows.len() >= 2 {
+            match ThreadPoolBuilder::new().num_threads(cores).build() {
+                Ok(pool) => pool.install(|| windows.par_iter().map(local).collect()),
+                Err(_) => windows.iter().map(local).collect(),
+            }
+        } else {
+            windows.iter().map(local).collect()
+        };
+
+    let mut bas = Vec::with_capacity(results.len());
+    let mut caps = Vec::with_capacity(results.len());
+    let mut spans = Vec::with_capacity(results.len());
+    for (p20, p30, span) in results.into_iter().flatten() {
+        bas.push(p20);
+        caps.push(p30);
+        spans.push(span);
+    }
     (bas, caps, spans)
 }
 
-fn pool_from_windows(
+/// Build the combined [`QuantileSummary`] of every sample at or below its
+/// window's cap, across the windows selected by `selector`, reducing the
+/// per-window summaries with [`QuantileSummary::merge`] instead of
+/// collecting and sorting a flat pool.
+fn pooled_summary(
     y: &[f32],
     spans: &[(usize, usize)],
     caps: &[f32],
     selector: &[bool],
-) -> Vec<f32> {
-    let mut pool = Vec::<f32>::new();
-    for (idx, &(a, b)) in spans.iter().enumerate() {
-        if selector[idx] {
-            let cap = caps[idx];
-            for &v in &y[a..b] {
-                if is_finite_positive(v) && v <= cap {
-                    pool.push(v);
-                }
+    epsilon: f64,
+    cores: usize,
+) -> QuantileSummary {
+    let selected: Vec<usize> = (0..spans.len()).filter(|&i| selector[i]).collect();
+
+    let local = |&idx: &usize| -> QuantileSummary {
+        let (a, b) = spans[idx];
+        let cap = caps[idx];
+        let mut summary = QuantileSummary::new(epsilon);
+        for &v in &y[a..b] {
+            if is_finite_positive(v) && v <= cap {
+                summary.update(v as f64);
             }
         }
+        summary
+    };
+
+    if cores > 1 && selected.len() >= 2 {
+        if let Ok(pool) = ThreadPoolBuilder::new().num_threads(cores).build() {
+            return pool.install(|| {
+                selected
+                    .par_iter()
+                    .map(local)
+                    .reduce(|| QuantileSummary::new(epsilon), QuantileSummary::merge)
+            });
+        }
     }
-    pool
+    selected
+        .iter()
+        .map(local)
+        .fold(QuantileSummary::new(epsilon), QuantileSummary::merge)
 }
@@ -2,12 +2,16 @@ use std::cmp::Ordering;
 
 use crate::utilities::calculate_baseline::{BaselineOptions, calculate_baseline};
 use crate::utilities::closest_index;
-use crate::utilities::find_noise_level::find_noise_level;
+use crate::utilities::find_noise_level::{WindowedNoiseOptions, find_noise_level, windowed_noise_level};
 use crate::utilities::get_boundaries::{Boundaries, BoundariesOptions, get_boundaries};
 
+use crate::utilities::deconvolve::{SQRT_2PI, deconvolve_region, gaussian_density};
+use crate::utilities::fit_peak_shape::fit_peak_shape;
+use crate::utilities::nnls::nnls;
 use crate::utilities::scan_for_peaks::{ScanPeaksOptions, scan_for_peaks_across_windows};
-use crate::utilities::structs::{DataXY, Peak};
-use crate::utilities::utilities::xy_integration;
+use crate::utilities::sgg::{SggOptions, sgg};
+use crate::utilities::structs::{DataXY, Peak, PeakFit, PeakModel};
+use crate::utilities::utilities::{min_sep, odd_in_range, xy_integration};
 
 const DEFAULT_WINDOW_SIZES: &[usize] = &[5, 7, 9, 11, 13, 15, 17, 19, 21, 23, 25, 27, 29, 31, 33];
 
@@ -21,6 +25,13 @@ pub struct FilterPeaksOptions {
     pub auto_baseline: Option<bool>,
     pub allow_overlap: Option<bool>,
     pub sn_ratio: Option<f64>,
+    /// Minimum locally-adaptive signal-to-noise ratio (see [`Peak::snr`]); peaks
+    /// below this are dropped, complementing the global `noise`/`auto_noise` gate.
+    pub snr_threshold: Option<f32>,
+    /// When set alongside `auto_noise`, noise is estimated per sliding block
+    /// instead of once for the whole trace, so each peak's `noise`/cutoff
+    /// reflects its own neighborhood on heteroscedastic baselines.
+    pub windowed_noise: Option<WindowedNoiseOptions>,
 }
 
 impl Default for FilterPeaksOptions {
@@ -34,6 +45,8 @@ impl Default for FilterPeaksOptions {
             auto_baseline: Some(false),
             allow_overlap: Some(false),
             sn_ratio: Some(1.0),
+            snr_threshold: None,
+            windowed_noise: None,
         }
     }
 }
@@ -48,6 +61,8 @@ struct PeakCandidate {
     number_of_points: usize,
     ratio: f64,
     noise: f64,
+    fit: Option<PeakFit>,
+    snr: f64,
 }
 
 impl From<PeakCandidate> for Peak {
@@ -61,6 +76,9 @@ impl From<PeakCandidate> for Peak {
             ratio: c.ratio,
             np: c.number_of_points,
             noise: c.noise,
+            fit: c.fit,
+            snr: c.snr,
+            baseline: 0.0,
         }
     }
 }
@@ -71,6 +89,13 @@ pub struct FindPeaksOptions {
     pub filter_peaks_options: Option<FilterPeaksOptions>,
     pub scan_peaks_options: Option<ScanPeaksOptions>,
     pub baseline_options: Option<BaselineOptions>,
+    /// When set, refit every detected region against this parametric shape
+    /// and report the fitted center/amplitude/width/score on the peak.
+    pub peak_model: Option<PeakModel>,
+    /// When `true`, a merged region with two or more local apices is resolved
+    /// into one peak per Gaussian-mixture component instead of being
+    /// collapsed/suppressed down to a single peak.
+    pub deconvolve: Option<bool>,
 }
 impl Default for FindPeaksOptions {
     fn default() -> Self {
@@ -79,6 +104,8 @@ impl Default for FindPeaksOptions {
             filter_peaks_options: Some(FilterPeaksOptions::default()),
             scan_peaks_options: Some(ScanPeaksOptions::default()),
             baseline_options: Some(BaselineOptions::default()),
+            peak_model: None,
+            deconvolve: None,
         }
     }
 }
@@ -87,6 +114,8 @@ pub fn find_peaks(data: &DataXY, options: Option<FindPeaksOptions>) -> Vec<Peak>
     let o = options.unwrap_or_default();
     let filter_opts = o.filter_peaks_options.unwrap_or_default();
     let base_opts = o.baseline_options.unwrap_or_default();
+    let peak_model = o.peak_model;
+    let deconvolve = o.deconvolve.unwrap_or(false);
 
     let y64: Vec<f64> = data.y.clone();
     let floor = if filter_opts.auto_baseline.unwrap_or(false) {
@@ -114,6 +143,14 @@ pub fn find_peaks(data: &DataXY, options: Option<FindPeaksOptions>) -> Vec<Peak>
         filter_opts.noise.unwrap_or(0.0).max(0.0)
     };
 
+    // `noise_at[i]` is the cutoff applied around sample `i`: a single global
+    // scalar ordinarily, or a per-block estimate when `windowed_noise` is
+    // set, so heteroscedastic traces don't share one noise floor end to end.
+    let noise_at: Vec<f64> = match (auto_noise, filter_opts.windowed_noise) {
+        (true, Some(wopt)) => windowed_noise_level(&y_center, wopt),
+        _ => vec![noise; y_center.len()],
+    };
+
     let normalized_data = DataXY {
         x: data.x.clone(),
         y: y_center,
@@ -129,32 +166,71 @@ pub fn find_peaks(data: &DataXY, options: Option<FindPeaksOptions>) -> Vec<Peak>
     }
 
     let mut bopt = o.get_boundaries_options.unwrap_or_default();
-    bopt.noise = noise;
+    let smoothed = smoothed_signal(data);
     let mut candidates: Vec<PeakCandidate> = Vec::with_capacity(positions.len());
     for seed_rt in positions {
-        let b = get_boundaries(&normalized_data, seed_rt, Some(bopt));
         let seed_idx = closest_index(&normalized_data.x, seed_rt);
+        let local_noise = noise_at.get(seed_idx).copied().unwrap_or(noise);
+        bopt.noise = local_noise;
+        let b = get_boundaries(&normalized_data, seed_rt, Some(bopt));
         let (rt, apex_y) = apex_in_window(&normalized_data, &b)
             .unwrap_or((normalized_data.x[seed_idx], normalized_data.y[seed_idx]));
 
-        if apex_y <= noise {
+        if apex_y <= local_noise {
             continue;
         }
 
         match (b.from.index, b.from.value, b.to.index, b.to.value) {
             (Some(fi), Some(fx), Some(ti), Some(tx)) if fi < ti => {
                 let (integral, intensity) = xy_integration(&data.x[fi..=ti], &data.y[fi..=ti]);
-                let cand = PeakCandidate {
-                    from: fx,
-                    to: tx,
-                    rt,
-                    integral,
-                    intensity,
-                    number_of_points: ti - fi + 1,
-                    ratio: 0.0,
-                    noise,
+                let snr = local_snr(&data.y, &smoothed, fi, ti, intensity);
+
+                let components = if deconvolve {
+                    let sep = min_sep(&data.x[fi..=ti], ti - fi + 1);
+                    deconvolve_region(&data.x[fi..=ti], &data.y[fi..=ti], integral, 0.03, sep)
+                } else {
+                    None
                 };
-                candidates.push(cand);
+
+                if let Some(components) = components {
+                    for c in components {
+                        let half = 2.0 * c.sigma;
+                        let cfrom = (c.mu - half).max(fx);
+                        let cto = (c.mu + half).min(tx);
+                        let pi = closest_index(&data.x, cfrom);
+                        let pj = closest_index(&data.x, cto);
+                        let fit = peak_model.and_then(|model| {
+                            fit_peak_shape(&data.x[fi..=ti], &data.y[fi..=ti], model)
+                        });
+                        candidates.push(PeakCandidate {
+                            from: cfrom,
+                            to: cto,
+                            rt: c.mu,
+                            integral: c.area,
+                            intensity: c.amplitude,
+                            number_of_points: pj.saturating_sub(pi) + 1,
+                            ratio: 0.0,
+                            noise: local_noise,
+                            fit,
+                            snr,
+                        });
+                    }
+                } else {
+                    let fit = peak_model
+                        .and_then(|model| fit_peak_shape(&data.x[fi..=ti], &data.y[fi..=ti], model));
+                    candidates.push(PeakCandidate {
+                        from: fx,
+                        to: tx,
+                        rt,
+                        integral,
+                        intensity,
+                        number_of_points: ti - fi + 1,
+                        ratio: 0.0,
+                        noise: local_noise,
+                        fit,
+                        snr,
+                    });
+                }
             }
             _ => {}
         }
@@ -175,25 +251,95 @@ pub fn find_peaks(data: &DataXY, options: Option<FindPeaksOptions>) -> Vec<Peak>
     peaks = dedupe_near_identical(peaks);
 
     if !peaks.is_empty() {
-        let mut cutoff = 0.0_f64;
-        if noise > 0.0 {
-            let sn_mult = filter_opts.sn_ratio.unwrap_or(1.0) as f64;
-            cutoff = sn_mult * noise;
-        }
-        if let Some(user_int) = filter_opts.intensity_threshold {
-            cutoff = cutoff.max(user_int);
-        }
-        if cutoff > 0.0 {
-            peaks.retain(|p| p.intensity > cutoff);
-        }
+        let sn_mult = filter_opts.sn_ratio.unwrap_or(1.0) as f64;
+        let user_int = filter_opts.intensity_threshold.unwrap_or(0.0);
+        // Per-peak rather than global: each peak carries the noise level of
+        // its own neighborhood, so the cutoff tracks a heteroscedastic
+        // baseline instead of being set once for the whole trace.
+        peaks.retain(|p| {
+            let cutoff = if p.noise > 0.0 {
+                (sn_mult * p.noise).max(user_int)
+            } else {
+                user_int
+            };
+            cutoff <= 0.0 || p.intensity > cutoff
+        });
     }
 
     if peaks.len() > 1 {
-        peaks = suppress_contained_peaks(data, peaks);
+        peaks = if filter_opts.allow_overlap.unwrap_or(false) {
+            let sn_mult = filter_opts.sn_ratio.unwrap_or(1.0) as f64;
+            resolve_overlapping_peaks(data, peaks, sn_mult)
+        } else {
+            suppress_contained_peaks(data, peaks)
+        };
     }
     peaks
 }
 
+/// Savitzky-Golay smoothed copy of `data.y`, used as the noise-free reference
+/// for the flanking-window SNR estimate. Falls back to the raw signal when
+/// there aren't enough points for a stable window.
+fn smoothed_signal(data: &DataXY) -> Vec<f32> {
+    let n = data.y.len();
+    match odd_in_range(11, n) {
+        Some(ws) => sgg(
+            &data.y,
+            &data.x,
+            SggOptions {
+                window_size: ws,
+                derivative: 0,
+                polynomial: 3,
+            },
+        ),
+        None => data.y.clone(),
+    }
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    let n = values.len();
+    if n % 2 == 1 {
+        values[n / 2]
+    } else {
+        (values[n / 2 - 1] + values[n / 2]) / 2.0
+    }
+}
+
+/// Locally-adaptive SNR for the region `[fi, ti]`: noise sigma is the MAD
+/// (scaled by 1.4826) of the raw-minus-smoothed residual in a flanking window
+/// around the region, excluding the region itself; the local baseline is the
+/// median of the smoothed signal over that same flank.
+fn local_snr(raw: &[f32], smoothed: &[f32], fi: usize, ti: usize, apex_intensity: f64) -> f64 {
+    let width = ti - fi + 1;
+    let flank = width.max(5);
+    let lo = fi.saturating_sub(flank);
+    let hi = (ti + flank).min(raw.len().saturating_sub(1));
+
+    let mut residuals = Vec::new();
+    let mut baseline_vals = Vec::new();
+    for i in lo..=hi {
+        if i >= fi && i <= ti {
+            continue;
+        }
+        residuals.push(raw[i] as f64 - smoothed[i] as f64);
+        baseline_vals.push(smoothed[i] as f64);
+    }
+    if residuals.is_empty() {
+        return f64::INFINITY;
+    }
+
+    let resid_median = median(&mut residuals.clone());
+    let mut abs_dev: Vec<f64> = residuals.iter().map(|r| (r - resid_median).abs()).collect();
+    let sigma = (median(&mut abs_dev) * 1.4826).max(1e-12);
+    let local_baseline = median(&mut baseline_vals);
+
+    (apex_intensity - local_baseline) / sigma
+}
+
 pub fn apex_in_window(data: &DataXY, b: &Boundaries) -> Option<(f64, f64)> {
     let l = b.from.index?;
     let r = b.to.index?;
@@ -226,6 +372,13 @@ fn filter_peak_candidates(peaks: Vec<PeakCandidate>, opt: FilterPeaksOptions) ->
                 }
             }
         }
+        if pass {
+            if let Some(snr_min) = opt.snr_threshold {
+                if p.snr < snr_min as f64 {
+                    pass = false;
+                }
+            }
+        }
         if pass {
             out.push(Peak::from(p));
         }
@@ -323,3 +476,131 @@ fn suppress_contained_peaks(data: &DataXY, peaks: Vec<Peak>) -> Vec<Peak> {
     out.sort_by(|a, b| a.rt.partial_cmp(&b.rt).unwrap_or(std::cmp::Ordering::Equal));
     out
 }
+
+/// Alternative to [`suppress_contained_peaks`] used when `allow_overlap` is
+/// set: instead of deleting the weaker peak of every overlapping pair, each
+/// cluster of peaks with overlapping `[from, to]` ranges is resolved jointly
+/// by non-negative least squares against a design matrix of unit-area
+/// Gaussians, one column per apex.
+fn resolve_overlapping_peaks(data: &DataXY, peaks: Vec<Peak>, sn_ratio: f64) -> Vec<Peak> {
+    if peaks.len() <= 1 {
+        return peaks;
+    }
+    let mut out = Vec::with_capacity(peaks.len());
+    for cluster in cluster_overlapping(peaks) {
+        out.extend(deconvolve_cluster(data, cluster, sn_ratio));
+    }
+    out.sort_by(|a, b| a.rt.partial_cmp(&b.rt).unwrap_or(Ordering::Equal));
+    out
+}
+
+/// Groups peaks whose `[from, to]` ranges overlap, merging chains
+/// transitively (a peak that overlaps the running span of a growing cluster
+/// joins it, even if it doesn't directly overlap the cluster's first member).
+fn cluster_overlapping(mut peaks: Vec<Peak>) -> Vec<Vec<Peak>> {
+    peaks.sort_by(|a, b| a.from.partial_cmp(&b.from).unwrap_or(Ordering::Equal));
+    let mut clusters: Vec<Vec<Peak>> = Vec::new();
+    let mut cur: Vec<Peak> = Vec::new();
+    let mut cur_max_to = f64::NEG_INFINITY;
+    for p in peaks {
+        if cur.is_empty() || p.from < cur_max_to {
+            cur_max_to = cur_max_to.max(p.to);
+            cur.push(p);
+        } else {
+            clusters.push(std::mem::take(&mut cur));
+            cur_max_to = p.to;
+            cur.push(p);
+        }
+    }
+    if !cur.is_empty() {
+        clusters.push(cur);
+    }
+    clusters
+}
+
+/// Resolve one cluster of overlapping peaks by non-negative least squares.
+/// Candidates whose solved amplitude falls below `sn_ratio * noise` (noise
+/// being the average of the cluster's own per-peak estimates) are dropped
+/// and the remaining columns refit, then each surviving peak's integral and
+/// intensity are recomputed from its resolved Gaussian component rather than
+/// from the shared raw interval.
+fn deconvolve_cluster(data: &DataXY, cluster: Vec<Peak>, sn_ratio: f64) -> Vec<Peak> {
+    if cluster.len() < 2 {
+        return cluster;
+    }
+    let lo = cluster.iter().map(|p| p.from).fold(f64::INFINITY, f64::min);
+    let hi = cluster
+        .iter()
+        .map(|p| p.to)
+        .fold(f64::NEG_INFINITY, f64::max);
+    let i0 = closest_index(&data.x, lo);
+    let i1 = closest_index(&data.x, hi);
+    if i1 <= i0 {
+        return cluster;
+    }
+    let xs = &data.x[i0..=i1];
+    let ys: Vec<f64> = data.y[i0..=i1].iter().map(|&v| v as f64).collect();
+
+    let fallback_sigma = (min_sep(xs, xs.len()) / 2.0).max(1e-9);
+    let sigma_of = |p: &Peak| -> f64 {
+        p.fit
+            .map(|f| f.width)
+            .filter(|s| s.is_finite() && *s > 0.0)
+            .unwrap_or(fallback_sigma)
+    };
+
+    let avg_noise = cluster.iter().map(|p| p.noise).sum::<f64>() / cluster.len() as f64;
+    let cutoff = if avg_noise > 0.0 {
+        sn_ratio * avg_noise
+    } else {
+        0.0
+    };
+
+    let mut active: Vec<usize> = (0..cluster.len()).collect();
+    let mut amplitudes: Vec<f64>;
+    loop {
+        let columns: Vec<Vec<f64>> = active
+            .iter()
+            .map(|&k| {
+                let sigma = sigma_of(&cluster[k]);
+                xs.iter()
+                    .map(|&x| gaussian_density(x, cluster[k].rt, sigma))
+                    .collect()
+            })
+            .collect();
+        amplitudes = nnls(&columns, &ys, 100);
+
+        let keep: Vec<usize> = active
+            .iter()
+            .zip(&amplitudes)
+            .filter(|&(_, &a)| a > cutoff)
+            .map(|(&k, _)| k)
+            .collect();
+        if keep.len() == active.len() || keep.len() < 2 {
+            active = keep;
+            break;
+        }
+        active = keep;
+    }
+
+    if active.len() < 2 {
+        return active
+            .first()
+            .map(|&k| vec![cluster[k].clone()])
+            .unwrap_or_default();
+    }
+
+    let mut out = Vec::with_capacity(active.len());
+    for (pos, &k) in active.iter().enumerate() {
+        let area = amplitudes[pos];
+        if area <= 0.0 {
+            continue;
+        }
+        let mut p = cluster[k].clone();
+        let sigma = sigma_of(&p);
+        p.integral = area;
+        p.intensity = area / (sigma * SQRT_2PI);
+        out.push(p);
+    }
+    out
+}
@@ -0,0 +1,280 @@
+use crate::utilities::lm::{LmOptions, lm};
+use crate::utilities::structs::{PeakFit, PeakModel};
+
+const SQRT_2PI: f64 = 2.5066282746310002;
+
+/// Refine a detected peak region against a parametric shape model, seeding
+/// from an analytic log-parabola estimate around the apex (falling back to
+/// the half-width at half-max when the parabola is degenerate), then running
+/// a few Levenberg-Marquardt iterations over `(x, y)` samples within the
+/// region. Returns `None` if the region is too small to fit (fewer than 4
+/// points) or the converged fit is degenerate (non-finite or non-positive
+/// width/amplitude), so callers fall back to the discrete apex/integration.
+pub fn fit_peak_shape(xs: &[f64], ys: &[f32], model: PeakModel) -> Option<PeakFit> {
+    let n = xs.len();
+    if n < 4 || n != ys.len() {
+        return None;
+    }
+
+    let (apex_i, &apex_y) = ys
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.partial_cmp(b.1).unwrap_or(std::cmp::Ordering::Equal))?;
+    let baseline0 = ys.iter().cloned().fold(f32::INFINITY, f32::min) as f64;
+    let a0 = (apex_y as f64 - baseline0).max(1e-9);
+
+    let (mu0, sigma0) = log_parabola_estimate(xs, ys, apex_i).unwrap_or_else(|| {
+        let half = baseline0 + (apex_y as f64 - baseline0) * 0.5;
+        let hwhm = half_width_at(xs, ys, apex_i, half).max((xs[n - 1] - xs[0]) / (2.0 * n as f64));
+        (xs[apex_i], hwhm / 1.1774)
+    });
+    let sigma0 = sigma0.max((xs[n - 1] - xs[0]) / (2.0 * n as f64));
+
+    let params0 = match model {
+        PeakModel::Emg => vec![a0, mu0, sigma0, sigma0.max(1e-6), baseline0],
+        _ => vec![a0, mu0, sigma0, baseline0],
+    };
+
+    let shape = move |p: &[f64], x: f64| -> f64 { eval_shape(model, p, x) };
+
+    let residuals = |p: &[f64]| -> Vec<f64> {
+        xs.iter()
+            .zip(ys)
+            .map(|(&x, &y)| shape(p, x) - y as f64)
+            .collect()
+    };
+
+    let fitted = lm(params0, residuals, LmOptions::default());
+    let (a, mu, w) = (fitted[0], fitted[1], fitted[2].abs());
+    if !a.is_finite() || !mu.is_finite() || !w.is_finite() || w <= 0.0 || a <= 0.0 {
+        return None;
+    }
+    let mut fitted = fitted;
+    fitted[2] = w;
+
+    let mean: f64 = ys.iter().map(|&y| y as f64).sum::<f64>() / n as f64;
+    let sst: f64 = ys.iter().map(|&y| (y as f64 - mean).powi(2)).sum();
+    let sse: f64 = xs
+        .iter()
+        .zip(ys)
+        .map(|(&x, &y)| (eval_shape(model, &fitted, x) - y as f64).powi(2))
+        .sum();
+    let score = if sst > 0.0 { 1.0 - sse / sst } else { 1.0 };
+
+    let (fwhm, area) = fwhm_and_area(model, &fitted);
+    if !fwhm.is_finite() || fwhm <= 0.0 || !area.is_finite() {
+        return None;
+    }
+
+    Some(PeakFit {
+        model,
+        center: mu,
+        amplitude: a,
+        width: w,
+        fwhm,
+        area,
+        score,
+    })
+}
+
+fn eval_shape(model: PeakModel, p: &[f64], x: f64) -> f64 {
+    match model {
+        PeakModel::Gaussian => {
+            let (a, mu, w, c) = (p[0], p[1], p[2].max(1e-12), p[3]);
+            a * (-0.5 * ((x - mu) / w).powi(2)).exp() + c
+        }
+        PeakModel::Lorentzian => {
+            let (a, mu, w, c) = (p[0], p[1], p[2].max(1e-12), p[3]);
+            a / (1.0 + ((x - mu) / w).powi(2)) + c
+        }
+        PeakModel::PseudoVoigt => {
+            const ETA: f64 = 0.5;
+            let (a, mu, w, c) = (p[0], p[1], p[2].max(1e-12), p[3]);
+            let g = a * (-0.5 * ((x - mu) / w).powi(2)).exp();
+            let l = a / (1.0 + ((x - mu) / w).powi(2));
+            ETA * l + (1.0 - ETA) * g + c
+        }
+        PeakModel::Emg => {
+            // Kalambet et al. (2011) parametrization: convolution of a
+            // Gaussian of height `a` with a unit-area exponential of
+            // time-constant `tau`; reduces to the plain Gaussian as tau -> 0.
+            let (a, mu, sigma, tau, c) = (p[0], p[1], p[2].max(1e-12), p[3].max(1e-9), p[4]);
+            let prefactor = a * sigma / tau * (std::f64::consts::FRAC_PI_2).sqrt();
+            let exponent = 0.5 * (sigma / tau).powi(2) - (x - mu) / tau;
+            let z = (sigma / tau - (x - mu) / sigma) / std::f64::consts::SQRT_2;
+            prefactor * exponent.exp() * erfc(z) + c
+        }
+    }
+}
+
+/// Full width at half maximum and area under the fitted curve, per model.
+/// `Emg` has no closed-form FWHM, so it's found by bisecting each flank for
+/// the half-max crossing; its area is still exact, since convolving with a
+/// unit-area exponential preserves the underlying Gaussian's area.
+fn fwhm_and_area(model: PeakModel, p: &[f64]) -> (f64, f64) {
+    let (amplitude, mu, w) = (p[0], p[1], p[2].abs().max(1e-12));
+    match model {
+        PeakModel::Gaussian => (2.3548200450309493 * w, amplitude * w * SQRT_2PI),
+        PeakModel::Lorentzian => (2.0 * w, amplitude * w * std::f64::consts::PI),
+        PeakModel::PseudoVoigt => {
+            const ETA: f64 = 0.5;
+            let gaussian_area = amplitude * w * SQRT_2PI;
+            let lorentzian_area = amplitude * w * std::f64::consts::PI;
+            (2.0 * w, ETA * lorentzian_area + (1.0 - ETA) * gaussian_area)
+        }
+        PeakModel::Emg => {
+            let sigma = w;
+            let span = 10.0 * (sigma + p[3].abs());
+            // The EMG curve peaks below `amplitude` and to the right of `mu`,
+            // so its own apex is located by sampling before splitting flanks.
+            const SAMPLES: usize = 512;
+            let (mut x_peak, mut y_peak) = (mu, eval_shape(model, p, mu) - p[4]);
+            for i in 0..=SAMPLES {
+                let x = mu - span + 2.0 * span * i as f64 / SAMPLES as f64;
+                let y = eval_shape(model, p, x) - p[4];
+                if y > y_peak {
+                    (x_peak, y_peak) = (x, y);
+                }
+            }
+            let half = y_peak / 2.0;
+            let left = bisect_crossing(|x| eval_shape(model, p, x) - p[4] - half, mu - span, x_peak);
+            let right = bisect_crossing(|x| eval_shape(model, p, x) - p[4] - half, x_peak, mu + span);
+            let fwhm = match (left, right) {
+                (Some(l), Some(r)) => r - l,
+                _ => 2.3548200450309493 * sigma,
+            };
+            // EMG is the convolution of a Gaussian (area = amplitude*sigma*sqrt(2*pi))
+            // with a unit-area exponential, so the total area is preserved.
+            (fwhm, amplitude * sigma * SQRT_2PI)
+        }
+    }
+}
+
+/// Bisect `f` for a sign-change root in `[lo, hi]`, scanning a handful of
+/// sub-intervals first since `f` may not change sign exactly once. Returns
+/// `None` if no sign change is found.
+fn bisect_crossing(f: impl Fn(f64) -> f64, lo: f64, hi: f64) -> Option<f64> {
+    const STEPS: usize = 64;
+    let dx = (hi - lo) / STEPS as f64;
+    let mut a = lo;
+    let mut fa = f(a);
+    for i in 1..=STEPS {
+        let b = lo + i as f64 * dx;
+        let fb = f(b);
+        if fa == 0.0 {
+            return Some(a);
+        }
+        if fa.signum() != fb.signum() {
+            let (mut x0, mut x1) = (a, b);
+            let (mut f0, mut f1) = (fa, fb);
+            for _ in 0..40 {
+                let mid = 0.5 * (x0 + x1);
+                let fm = f(mid);
+                if fm.signum() == f0.signum() {
+                    x0 = mid;
+                    f0 = fm;
+                } else {
+                    x1 = mid;
+                    f1 = fm;
+                }
+            }
+            let _ = f1;
+            return Some(0.5 * (x0 + x1));
+        }
+        a = b;
+        fa = fb;
+    }
+    None
+}
+
+/// Complementary error function via the Abramowitz & Stegun 7.1.26
+/// rational approximation (max error ~1.5e-7).
+fn erfc(x: f64) -> f64 {
+    let sign = if x < 0.0 { -1.0 } else { 1.0 };
+    let x = x.abs();
+    const A1: f64 = 0.254829592;
+    const A2: f64 = -0.284496736;
+    const A3: f64 = 1.421413741;
+    const A4: f64 = -1.453152027;
+    const A5: f64 = 1.061405429;
+    const P: f64 = 0.3275911;
+    let t = 1.0 / (1.0 + P * x);
+    let poly = ((((A5 * t + A4) * t + A3) * t + A2) * t + A1) * t;
+    let erf = sign * (1.0 - poly * (-x * x).exp());
+    1.0 - erf
+}
+
+/// Analytic log-parabola estimate of center/sigma from the apex and its two
+/// neighbors: fit `ln(y) = a*x^2 + b*x + c` through the three points, then
+/// `center = -b/(2a)`, `sigma = sqrt(-1/(2a))`. Only valid when `a < 0` and
+/// all three `y` are positive; returns `None` otherwise so the caller falls
+/// back to the half-width-at-half-max seed.
+fn log_parabola_estimate(xs: &[f64], ys: &[f32], apex_i: usize) -> Option<(f64, f64)> {
+    if apex_i == 0 || apex_i + 1 >= xs.len() {
+        return None;
+    }
+    let (x0, x1, x2) = (xs[apex_i - 1], xs[apex_i], xs[apex_i + 1]);
+    let (y0, y1, y2) = (ys[apex_i - 1] as f64, ys[apex_i] as f64, ys[apex_i + 1] as f64);
+    if y0 <= 0.0 || y1 <= 0.0 || y2 <= 0.0 {
+        return None;
+    }
+    let (l0, l1, l2) = (y0.ln(), y1.ln(), y2.ln());
+
+    // Solve the 3x3 Vandermonde system for (a, b, c) directly.
+    let d01 = x0 - x1;
+    let d02 = x0 - x2;
+    let d12 = x1 - x2;
+    let denom = d01 * d02 * d12;
+    if denom.abs() < 1e-12 {
+        return None;
+    }
+    let a = (l0 * (x1 - x2) - l1 * (x0 - x2) + l2 * (x0 - x1)) / denom;
+    let b = (l0 * (x2 * x2 - x1 * x1) - l1 * (x2 * x2 - x0 * x0) + l2 * (x1 * x1 - x0 * x0))
+        / denom;
+    if a >= 0.0 || !a.is_finite() || !b.is_finite() {
+        return None;
+    }
+    let center = -b / (2.0 * a);
+    let sigma = (-1.0 / (2.0 * a)).sqrt();
+    if !center.is_finite() || !sigma.is_finite() || sigma <= 0.0 {
+        return None;
+    }
+    Some((center, sigma))
+}
+
+/// Linear-interpolated half-width at half-max around `apex_i`, used as a
+/// seed for the fitted peak's sigma/gamma.
+fn half_width_at(xs: &[f64], ys: &[f32], apex_i: usize, half: f64) -> f64 {
+    let n = xs.len();
+    let left = {
+        let mut i = apex_i;
+        while i > 0 && ys[i] as f64 > half {
+            i -= 1;
+        }
+        if i == apex_i {
+            xs[apex_i]
+        } else {
+            lerp_crossing(xs[i], ys[i] as f64, xs[i + 1], ys[i + 1] as f64, half)
+        }
+    };
+    let right = {
+        let mut i = apex_i;
+        while i + 1 < n && ys[i] as f64 > half {
+            i += 1;
+        }
+        if i == apex_i {
+            xs[apex_i]
+        } else {
+            lerp_crossing(xs[i - 1], ys[i - 1] as f64, xs[i], ys[i] as f64, half)
+        }
+    };
+    ((right - left) / 2.0).abs().max(1e-9)
+}
+
+#[inline]
+fn lerp_crossing(x0: f64, y0: f64, x1: f64, y1: f64, target: f64) -> f64 {
+    if (y1 - y0).abs() < 1e-12 {
+        return x0;
+    }
+    x0 + (target - y0) * (x1 - x0) / (y1 - y0)
+}
@@ -3,7 +3,7 @@ use rayon::{ThreadPoolBuilder, prelude::*};
 use crate::utilities::{
     find_peaks::FindPeaksOptions,
     get_peak::get_peak,
-    parse::parse_mzml::MzML,
+    parse::{lazy::LazyMzML, parse_mzml::MzML},
     structs::{ChromRoi, DataXY, Roi},
 };
 
@@ -37,12 +37,45 @@ pub fn get_peaks_from_chrom(
     }
 }
 
+/// Like [`get_peaks_from_chrom`], but reads a [`LazyMzML`] view instead of an
+/// already-decoded [`MzML`], so only the chromatograms named in `items` get
+/// their time/intensity arrays materialized — the rest of a large file's
+/// chromatogram table is never decoded.
+pub fn get_peaks_from_chrom_lazy(
+    mzml: &LazyMzML<'_>,
+    items: &[ChromRoi],
+    options: Option<FindPeaksOptions>,
+    cores: usize,
+) -> Option<Vec<(usize, String, f64, f64, f64, f64, f64, f64)>> {
+    let f = |roi: &ChromRoi| {
+        if roi.window <= 0.0 || !roi.rt.is_finite() {
+            return (roi.idx, roi.id.clone(), roi.rt, 0.0, 0.0, 0.0, 0.0, 0.0);
+        }
+        let i = roi.idx;
+        if i >= mzml.chromatogram_count() {
+            return (i, roi.id.clone(), roi.rt, 0.0, 0.0, 0.0, 0.0, 0.0);
+        }
+        let id = mzml.chromatogram_id(i).unwrap_or_default();
+        let (x, y) = match (mzml.chromatogram_time(i), mzml.chromatogram_intensity(i)) {
+            (Ok(t), Ok(ints)) => (t, ints),
+            _ => (Vec::new(), Vec::new()),
+        };
+        compute_one(i, id, x, y, roi, &options)
+    };
+    if cores <= 1 || items.len() < 2 {
+        Some(items.iter().map(f).collect())
+    } else {
+        let pool = ThreadPoolBuilder::new().num_threads(cores).build().ok()?;
+        Some(pool.install(|| items.par_iter().map(f).collect()))
+    }
+}
+
 #[inline]
 fn compute_one(
     ch_index: usize,
     ch_id: &str,
     x: Vec<f64>,
-    y: Vec<f64>,
+    y: Vec<f32>,
     roi: &ChromRoi,
     options: &Option<FindPeaksOptions>,
 ) -> (usize, String, f64, f64, f64, f64, f64, f64) {
@@ -46,6 +46,7 @@ fn compute_one(
         EicOptions {
             ppm_tolerance: 20.0,
             mz_tolerance: 0.005,
+            ..Default::default()
         },
     ) {
         Ok(v) => v,
@@ -1,6 +1,8 @@
+use crate::utilities::matrix::Matrix;
+
 pub type Point = Vec<f64>;
 
-fn dist(a: &Point, b: &Point) -> f64 {
+fn dist_rows(a: &[f64], b: &[f64]) -> f64 {
     let mut s = 0.0;
     for i in 0..a.len() {
         let d = a[i] - b[i];
@@ -9,65 +11,162 @@ fn dist(a: &Point, b: &Point) -> f64 {
     s.sqrt()
 }
 
-fn mean(ps: &[&Point]) -> Point {
-    let d = ps[0].len();
+fn mean_rows(rows: &[&[f64]]) -> Vec<f64> {
+    let d = rows[0].len();
     let mut m = vec![0.0; d];
-    for p in ps {
+    for r in rows {
         for i in 0..d {
-            m[i] += p[i];
+            m[i] += r[i];
         }
     }
-    let n = ps.len() as f64;
-    for i in 0..d {
-        m[i] /= n;
+    let n = rows.len() as f64;
+    for v in &mut m {
+        *v /= n;
     }
     m
 }
 
-pub fn kmeans(points: &[Point], mut centroids: Vec<Point>) -> Vec<Point> {
-    if points.is_empty() || centroids.is_empty() {
-        return Vec::new();
+/// k-means over a dense row-major matrix. Reads rows as slices, so the
+/// per-point `Vec` allocations `kmeans` used to pay in `mean()` are gone.
+pub fn kmeans_matrix(points: &Matrix<f64>, centroids: Matrix<f64>) -> Matrix<f64> {
+    lloyd(points, centroids, 0.0, 300)
+}
+
+/// Lloyd's algorithm: reassign points to the nearest centroid, recompute
+/// centroids as the mean of their assigned points, and stop once the largest
+/// centroid movement drops below `tol` or `max_iter` iterations are spent.
+/// Empty clusters are re-seeded at the point farthest from its centroid
+/// rather than left at their stale position.
+fn lloyd(points: &Matrix<f64>, mut centroids: Matrix<f64>, tol: f64, max_iter: usize) -> Matrix<f64> {
+    if points.rows() == 0 || centroids.rows() == 0 {
+        return Matrix::from_vec(0, points.cols(), Vec::new());
     }
-    let k = centroids.len();
-    let mut converged = false;
-    let mut it = 0usize;
+    let k = centroids.rows();
 
-    while !converged {
+    for _ in 0..max_iter.max(1) {
         let mut groups: Vec<Vec<usize>> = vec![Vec::new(); k];
+        let mut nearest_d: Vec<f64> = vec![0.0; points.rows()];
 
-        for i in 0..points.len() {
-            let p = &points[i];
+        for i in 0..points.rows() {
+            let p = points.row(i);
             let mut idx = 0usize;
-            let mut best = dist(p, &centroids[0]);
+            let mut best = dist_rows(p, centroids.row(0));
             for j in 1..k {
-                let d = dist(p, &centroids[j]);
+                let d = dist_rows(p, centroids.row(j));
                 if d < best {
                     best = d;
                     idx = j;
                 }
             }
             groups[idx].push(i);
+            nearest_d[i] = best;
         }
 
-        let mut next = Vec::with_capacity(k);
+        let mut next = Matrix::<f64>::new(k, points.cols());
         for gi in 0..k {
             if groups[gi].is_empty() {
-                next.push(centroids[gi].clone());
+                let far = (0..points.rows())
+                    .max_by(|&a, &b| nearest_d[a].partial_cmp(&nearest_d[b]).unwrap())
+                    .unwrap_or(0);
+                next.row_mut(gi).copy_from_slice(points.row(far));
             } else {
-                let mut refs: Vec<&Point> = Vec::with_capacity(groups[gi].len());
-                for &ix in &groups[gi] {
-                    refs.push(&points[ix]);
-                }
-                next.push(mean(&refs));
+                let refs: Vec<&[f64]> = groups[gi].iter().map(|&ix| points.row(ix)).collect();
+                next.row_mut(gi).copy_from_slice(&mean_rows(&refs));
             }
         }
 
-        converged = next == centroids;
+        let movement = (0..k)
+            .map(|gi| dist_rows(next.row(gi), centroids.row(gi)))
+            .fold(0.0, f64::max);
         centroids = next;
-        it += 1;
-        if it > 300 {
+        if movement < tol {
             break;
         }
     }
     centroids
 }
+
+pub fn kmeans(points: &[Point], centroids: Vec<Point>) -> Vec<Point> {
+    if points.is_empty() || centroids.is_empty() {
+        return Vec::new();
+    }
+    let pm = Matrix::from_rows(points);
+    let cm = Matrix::from_rows(&centroids);
+    let result = kmeans_matrix(&pm, cm);
+    (0..result.rows()).map(|i| result.row(i).to_vec()).collect()
+}
+
+/// Deterministic LCG (same constants used crate-wide for seeded sampling).
+#[inline]
+fn lcg_next(state: &mut u64) -> u64 {
+    *state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+    *state
+}
+
+#[inline]
+fn lcg_f64(state: &mut u64) -> f64 {
+    (lcg_next(state) >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// k-means++ seeding: the first centroid is picked uniformly at random, then
+/// each subsequent centroid is chosen with probability proportional to its
+/// squared distance to the nearest already-chosen centroid.
+fn kmeans_plus_plus(points: &Matrix<f64>, k: usize, seed: u64) -> Matrix<f64> {
+    let n = points.rows();
+    let mut state = seed;
+    let mut chosen = Vec::with_capacity(k);
+
+    let first = (lcg_next(&mut state) as usize) % n;
+    chosen.push(first);
+
+    let mut nearest_d2 = vec![0.0; n];
+    for i in 0..n {
+        nearest_d2[i] = dist_rows(points.row(i), points.row(first)).powi(2);
+    }
+
+    while chosen.len() < k {
+        let total: f64 = nearest_d2.iter().sum();
+        let next_idx = if total <= 0.0 {
+            ((lcg_next(&mut state) as usize) % n)
+        } else {
+            let u = lcg_f64(&mut state) * total;
+            let mut acc = 0.0;
+            let mut pick = n - 1;
+            for i in 0..n {
+                acc += nearest_d2[i];
+                if acc >= u {
+                    pick = i;
+                    break;
+                }
+            }
+            pick
+        };
+        chosen.push(next_idx);
+        for i in 0..n {
+            let d2 = dist_rows(points.row(i), points.row(next_idx)).powi(2);
+            if d2 < nearest_d2[i] {
+                nearest_d2[i] = d2;
+            }
+        }
+    }
+
+    let mut centroids = Matrix::<f64>::new(k, points.cols());
+    for (gi, &pi) in chosen.iter().enumerate() {
+        centroids.row_mut(gi).copy_from_slice(points.row(pi));
+    }
+    centroids
+}
+
+/// k-means with automatic k-means++ seeding and movement-based convergence,
+/// so callers don't need to hand-pick initial centroids or rely on the
+/// 300-iteration cap being hit on every run.
+pub fn kmeans_auto(points: &[Point], k: usize, seed: u64, tol: f64, max_iter: usize) -> Vec<Point> {
+    if points.is_empty() || k == 0 {
+        return Vec::new();
+    }
+    let pm = Matrix::from_rows(points);
+    let k = k.min(pm.rows());
+    let seeded = kmeans_plus_plus(&pm, k, seed);
+    let result = lloyd(&pm, seeded, tol, max_iter);
+    (0..result.rows()).map(|i| result.row(i).to_vec()).collect()
+}
@@ -0,0 +1,160 @@
+/// Options for the generic Levenberg-Marquardt solver.
+#[derive(Clone, Copy, Debug)]
+pub struct LmOptions {
+    pub max_iterations: usize,
+    pub lambda0: f64,
+    pub tol: f64,
+}
+
+impl Default for LmOptions {
+    fn default() -> Self {
+        Self {
+            max_iterations: 50,
+            lambda0: 1e-3,
+            tol: 1e-10,
+        }
+    }
+}
+
+/// Minimize `sum(residuals(params)^2)` over `params` via a bounded
+/// Levenberg-Marquardt iteration with a central-difference Jacobian. Returns
+/// the refined parameter vector; never panics on a singular step (falls back
+/// to the previous iterate and keeps going until `max_iterations`).
+pub fn lm<F>(mut params: Vec<f64>, residuals: F, opts: LmOptions) -> Vec<f64>
+where
+    F: Fn(&[f64]) -> Vec<f64>,
+{
+    let n = params.len();
+    if n == 0 {
+        return params;
+    }
+    let mut lambda = opts.lambda0;
+    let mut r = residuals(&params);
+    let mut sse = sum_sq(&r);
+
+    for _ in 0..opts.max_iterations {
+        let jac = jacobian(&residuals, &params, &r);
+        let m = r.len();
+
+        // JtJ (n x n) and Jtr (n)
+        let mut jtj = vec![vec![0.0; n]; n];
+        let mut jtr = vec![0.0; n];
+        for i in 0..n {
+            for k in 0..m {
+                jtr[i] += jac[k][i] * r[k];
+            }
+            for j in 0..n {
+                let mut s = 0.0;
+                for k in 0..m {
+                    s += jac[k][i] * jac[k][j];
+                }
+                jtj[i][j] = s;
+            }
+        }
+
+        let mut a = jtj.clone();
+        for i in 0..n {
+            a[i][i] += lambda * jtj[i][i].max(1e-12);
+        }
+        let neg_jtr: Vec<f64> = jtr.iter().map(|&v| -v).collect();
+        let delta = match solve_dense(&a, &neg_jtr) {
+            Some(d) => d,
+            None => {
+                lambda *= 10.0;
+                continue;
+            }
+        };
+
+        let trial: Vec<f64> = params.iter().zip(&delta).map(|(&p, &d)| p + d).collect();
+        let trial_r = residuals(&trial);
+        let trial_sse = sum_sq(&trial_r);
+
+        if trial_sse.is_finite() && trial_sse < sse {
+            let step: f64 = delta.iter().map(|d| d * d).sum::<f64>().sqrt();
+            params = trial;
+            r = trial_r;
+            sse = trial_sse;
+            lambda = (lambda * 0.5).max(1e-12);
+            if step < opts.tol {
+                break;
+            }
+        } else {
+            lambda *= 10.0;
+            if lambda > 1e12 {
+                break;
+            }
+        }
+    }
+    params
+}
+
+fn sum_sq(v: &[f64]) -> f64 {
+    v.iter().map(|x| x * x).sum()
+}
+
+fn jacobian<F>(residuals: &F, params: &[f64], base: &[f64]) -> Vec<Vec<f64>>
+where
+    F: Fn(&[f64]) -> Vec<f64>,
+{
+    let n = params.len();
+    let m = base.len();
+    let mut jac = vec![vec![0.0; n]; m];
+    for j in 0..n {
+        let h = (params[j].abs() * 1e-6).max(1e-8);
+        let mut plus = params.to_vec();
+        plus[j] += h;
+        let mut minus = params.to_vec();
+        minus[j] -= h;
+        let rp = residuals(&plus);
+        let rm = residuals(&minus);
+        for i in 0..m {
+            jac[i][j] = (rp[i] - rm[i]) / (2.0 * h);
+        }
+    }
+    jac
+}
+
+/// Solve `A x = b` for a small dense `A` via Gaussian elimination with
+/// partial pivoting. Returns `None` if `A` is (numerically) singular.
+pub(crate) fn solve_dense(a: &[Vec<f64>], b: &[f64]) -> Option<Vec<f64>> {
+    let n = b.len();
+    let mut m: Vec<Vec<f64>> = a.iter().cloned().collect();
+    let mut rhs = b.to_vec();
+
+    for col in 0..n {
+        let mut piv = col;
+        let mut best = m[col][col].abs();
+        for row in (col + 1)..n {
+            if m[row][col].abs() > best {
+                best = m[row][col].abs();
+                piv = row;
+            }
+        }
+        if best < 1e-14 {
+            return None;
+        }
+        m.swap(col, piv);
+        rhs.swap(col, piv);
+
+        for row in (col + 1)..n {
+            let f = m[row][col] / m[col][col];
+            if f == 0.0 {
+                continue;
+            }
+            for k in col..n {
+                m[row][k] -= f * m[col][k];
+            }
+            rhs[row] -= f * rhs[col];
+        }
+    }
+
+    let mut x = vec![0.0; n];
+    for row in (0..n).rev() {
+        let mut s = rhs[row];
+        for k in (row + 1)..n {
+            s -= m[row][k] * x[k];
+        }
+        x[row] = s / m[row][row];
+    }
+    Some(x)
+}
@@ -0,0 +1,86 @@
+use std::ops::{Index, IndexMut};
+
+/// Dense row-major matrix backed by a single contiguous `Vec<T>`.
+///
+/// Indexing by a single `usize` returns the row as a slice, so `m[i]` reads
+/// like row access on a 2D array while keeping the data cache-friendly for
+/// large 2D datasets (LC-MS/ion-mobility frames, integration grids).
+#[derive(Clone, Debug)]
+pub struct Matrix<T> {
+    data: Vec<T>,
+    cols: usize,
+}
+
+impl<T: Clone + Default> Matrix<T> {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self {
+            data: vec![T::default(); rows * cols],
+            cols,
+        }
+    }
+}
+
+impl<T> Matrix<T> {
+    pub fn from_vec(rows: usize, cols: usize, data: Vec<T>) -> Self {
+        debug_assert_eq!(data.len(), rows * cols);
+        Self { data, cols }
+    }
+
+    pub fn from_rows(rows: &[Vec<T>]) -> Self
+    where
+        T: Clone,
+    {
+        let cols = rows.first().map_or(0, |r| r.len());
+        let mut data = Vec::with_capacity(rows.len() * cols);
+        for r in rows {
+            debug_assert_eq!(r.len(), cols);
+            data.extend_from_slice(r);
+        }
+        Self { data, cols }
+    }
+
+    #[inline]
+    pub fn rows(&self) -> usize {
+        if self.cols == 0 { 0 } else { self.data.len() / self.cols }
+    }
+
+    #[inline]
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    #[inline]
+    pub fn row(&self, i: usize) -> &[T] {
+        let start = i * self.cols;
+        &self.data[start..start + self.cols]
+    }
+
+    #[inline]
+    pub fn row_mut(&mut self, i: usize) -> &mut [T] {
+        let start = i * self.cols;
+        &mut self.data[start..start + self.cols]
+    }
+
+    pub fn iter_rows(&self) -> impl Iterator<Item = &[T]> {
+        self.data.chunks(self.cols)
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+}
+
+impl<T> Index<usize> for Matrix<T> {
+    type Output = [T];
+    #[inline]
+    fn index(&self, i: usize) -> &[T] {
+        self.row(i)
+    }
+}
+
+impl<T> IndexMut<usize> for Matrix<T> {
+    #[inline]
+    fn index_mut(&mut self, i: usize) -> &mut [T] {
+        self.row_mut(i)
+    }
+}
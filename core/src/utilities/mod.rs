@@ -4,8 +4,29 @@ pub use air_pls::air_pls;
 pub mod calculate_eic;
 pub use calculate_eic::{Eic, EicOptions, calculate_eic_from_bin1, calculate_eic_from_mzml};
 
+pub mod deconvolve;
+pub use deconvolve::{GaussianComponent, deconvolve_region, em_gaussian_mixture};
+
+pub mod detect_mass_traces;
+pub use detect_mass_traces::{DetectMassTracesOptions, MassTrace, detect_mass_traces};
+
+pub mod experiment;
+pub use experiment::{AreaIter, Experiment};
+
+pub mod find_features;
+pub use find_features::{
+    Feature, FindFeaturesOptions, IsotopeEnvelope, IsotopeGroupingOptions, find_features,
+    group_isotope_envelopes, optimize_find_features_options,
+};
+
 pub mod find_noise_level;
-pub use find_noise_level::find_noise_level;
+pub use find_noise_level::{
+    NoiseOptions, WindowedNoiseOptions, find_noise_level, find_noise_level_with_cores,
+    find_noise_level_with_options, windowed_noise_level,
+};
+
+pub mod fit_peak_shape;
+pub use fit_peak_shape::fit_peak_shape;
 
 pub mod find_peaks;
 
@@ -14,6 +35,9 @@ pub mod functions;
 pub mod calculate_baseline;
 pub use calculate_baseline::calculate_baseline;
 
+pub mod csv_io;
+pub use csv_io::CsvOptions;
+
 pub mod get_boundaries;
 
 pub mod get_peak;
@@ -23,23 +47,41 @@ pub mod get_peaks_from_chrom;
 pub mod get_peaks_from_eic;
 
 pub mod kmeans;
-pub use kmeans::{Point, kmeans};
+pub use kmeans::{Point, kmeans, kmeans_auto, kmeans_matrix};
+
+pub mod matrix;
+pub use matrix::Matrix;
+
+pub mod peaks_bin;
 
 pub mod lm;
 pub use lm::lm;
 
+pub mod nnls;
+pub use nnls::nnls;
+
 pub mod noise_san_plot;
 pub use noise_san_plot::noise_san_plot;
 
 pub mod parse;
 
+pub mod quantile_summary;
+pub use quantile_summary::QuantileSummary;
+
+pub mod resample_eic;
+pub use resample_eic::resample_eic;
+
 pub mod scan_for_peaks;
 
+pub mod select_spectra;
+pub use select_spectra::{SpectrumSelector, query_xic, select_spectra, select_spectrum_indices};
+
 pub mod sgg;
 
 pub mod structs;
 
 pub mod utilities;
 pub use utilities::{
-    closest_index, mean_step, min_positive_step, min_sep, odd_in_range, quad_peak,
+    asls_baseline, closest_index, mean_step, min_positive_step, min_sep, odd_in_range, quad_peak,
+    solve_pentadiagonal_spd, xy_integration_baseline,
 };
@@ -0,0 +1,104 @@
+use crate::utilities::lm::solve_dense;
+
+/// Solve `min ||A x - b||^2` subject to `x >= 0` via the active-set algorithm
+/// of Lawson & Hanson (1974). `columns[j]` is the `j`-th column of `A`
+/// (length `b.len()`). Each active-set update refits the passive (non-zero)
+/// columns by ordinary least squares, so `max_iter` bounds the number of
+/// passive-set changes rather than a convergence tolerance.
+pub fn nnls(columns: &[Vec<f64>], b: &[f64], max_iter: usize) -> Vec<f64> {
+    let k = columns.len();
+    if k == 0 || b.is_empty() {
+        return vec![0.0; k];
+    }
+    let tol = 1e-10;
+    let mut x = vec![0.0; k];
+    let mut passive = vec![false; k];
+
+    for _ in 0..max_iter.max(1) {
+        let resid = residual(columns, &x, b);
+        let mut best_j = None;
+        let mut best_w = tol;
+        for (j, col) in columns.iter().enumerate() {
+            if passive[j] {
+                continue;
+            }
+            let w = dot(col, &resid);
+            if w > best_w {
+                best_w = w;
+                best_j = Some(j);
+            }
+        }
+        let Some(j) = best_j else {
+            break;
+        };
+        passive[j] = true;
+
+        loop {
+            let idxs: Vec<usize> = (0..k).filter(|&c| passive[c]).collect();
+            let z = solve_passive(columns, &idxs, b);
+            if z.iter().all(|&v| v > 0.0) {
+                for (pos, &c) in idxs.iter().enumerate() {
+                    x[c] = z[pos];
+                }
+                break;
+            }
+
+            let mut alpha = f64::INFINITY;
+            for (pos, &c) in idxs.iter().enumerate() {
+                if z[pos] <= 0.0 {
+                    let denom = x[c] - z[pos];
+                    if denom > 1e-15 {
+                        alpha = alpha.min(x[c] / denom);
+                    }
+                }
+            }
+            if !alpha.is_finite() {
+                alpha = 0.0;
+            }
+            for (pos, &c) in idxs.iter().enumerate() {
+                x[c] += alpha * (z[pos] - x[c]);
+            }
+            for &c in &idxs {
+                if x[c].abs() < tol {
+                    x[c] = 0.0;
+                    passive[c] = false;
+                }
+            }
+        }
+    }
+
+    x
+}
+
+fn solve_passive(columns: &[Vec<f64>], idxs: &[usize], b: &[f64]) -> Vec<f64> {
+    let k = idxs.len();
+    if k == 0 {
+        return Vec::new();
+    }
+    let mut ata = vec![vec![0.0; k]; k];
+    let mut atb = vec![0.0; k];
+    for (a, &ca) in idxs.iter().enumerate() {
+        atb[a] = dot(&columns[ca], b);
+        for (c, &cc) in idxs.iter().enumerate() {
+            ata[a][c] = dot(&columns[ca], &columns[cc]);
+        }
+    }
+    solve_dense(&ata, &atb).unwrap_or_else(|| vec![0.0; k])
+}
+
+fn residual(columns: &[Vec<f64>], x: &[f64], b: &[f64]) -> Vec<f64> {
+    let mut r = b.to_vec();
+    for (j, col) in columns.iter().enumerate() {
+        if x[j] == 0.0 {
+            continue;
+        }
+        for (ri, &cv) in r.iter_mut().zip(col) {
+            *ri -= x[j] * cv;
+        }
+    }
+    r
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
@@ -0,0 +1,40 @@
+// CRC32C (Castagnoli) over a byte slice, used by `decode`/`encode` to check
+// that one array's on-disk bytes weren't silently truncated or flipped -
+// independent of [`super::integrity`]'s whole-buffer BLAKE3 trailer, which
+// only newer writers append and which covers everything at once rather than
+// localizing a bad region to one array.
+
+const POLY: u32 = 0x82F63B78;
+
+fn build_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut j = 0;
+        while j < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ POLY
+            } else {
+                crc >> 1
+            };
+            j += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+/// Compute the CRC32C checksum of `bytes`, matching the reflected
+/// `0x82F63B78` polynomial (`crc = 0xFFFFFFFF`, XOR-out `0xFFFFFFFF`).
+pub fn crc32c(bytes: &[u8]) -> u32 {
+    static TABLE: std::sync::OnceLock<[u32; 256]> = std::sync::OnceLock::new();
+    let table = TABLE.get_or_init(build_table);
+    let mut crc = 0xFFFFFFFFu32;
+    for &b in bytes {
+        let idx = ((crc ^ b as u32) & 0xFF) as usize;
+        crc = table[idx] ^ (crc >> 8);
+    }
+    crc ^ 0xFFFFFFFF
+}
@@ -1,19 +1,70 @@
 use serde_json;
 
 use crate::utilities::parse::{
-    helper::{rd_f64, rd_u32, rd_u64, read_array_as_f64},
+    crc32c::crc32c,
+    helper::{
+        BE_FLAG, BinReader, ByteOrder, rd_u32_order, rd_u64_order, read_array_as_f32,
+        read_array_as_f64, read_fields,
+    },
     parse_mzml::{ChromatogramSummary, MzML, Precursor, Run, SpectrumSummary},
 };
 
+/// Options for [`decode_with_options`].
+#[derive(Clone, Copy, Debug)]
+pub struct DecodeOptions {
+    /// When set, recompute the CRC32C of every raw (`f32`/`f64`, format `1`
+    /// or `2`) spectrum/chromatogram array and compare it against the two
+    /// little-endian `u32` checksums [`crate::utilities::parse::encode::encode`],
+    /// [`crate::utilities::parse::encode::encode_arrays`],
+    /// [`crate::utilities::parse::encode::encode_with_options`] and
+    /// [`crate::utilities::parse::encode::encode_parallel`] store in each
+    /// index record's otherwise-unused bytes `24..32`. Other encoders leave
+    /// those bytes zeroed, and non-raw formats (numpress/quantized/
+    /// predictive) aren't checksummed at all, so this only protects buffers
+    /// produced by the four encoders above.
+    pub verify_checksums: bool,
+}
+
+impl Default for DecodeOptions {
+    fn default() -> Self {
+        Self {
+            verify_checksums: false,
+        }
+    }
+}
+
 pub fn decode(bin: &[u8]) -> Result<MzML, String> {
+    decode_with_options(bin, None)
+}
+
+pub fn decode_with_options(bin: &[u8], options: Option<DecodeOptions>) -> Result<MzML, String> {
+    let opts = options.unwrap_or_default();
+    // The magic is both the format discriminator ("version") and, being the
+    // very first thing read, the cheapest possible truncation check: a
+    // `BinReader` over the whole buffer reports "not enough data at offset
+    // N" instead of a bare length check, so a 2-byte stub gets an error
+    // naming the offset it ran out at.
+    let mut header = BinReader::new(bin);
+    let magic_bytes = header.read_bytes(4).map_err(String::from)?;
+    if magic_bytes != b"BIN1" && magic_bytes != b"BINS" && magic_bytes != b"BINQ" {
+        return Err("bad magic".into());
+    }
     if bin.len() < 64 {
         return Err("short header".into());
     }
     let magic = &bin[0..4];
 
+    let order = if bin[12] & BE_FLAG != 0 {
+        ByteOrder::Big
+    } else {
+        ByteOrder::Little
+    };
+    let rd_u32 = |b: &[u8], p: usize| rd_u32_order(b, p, order);
+    let rd_u64 = |b: &[u8], p: usize| rd_u64_order(b, p, order);
+
     let n_spec = rd_u32(bin, 4)? as usize;
     let n_ch = rd_u32(bin, 8)? as usize;
-    let cx = bin[12];
+    let cx = bin[12] & !BE_FLAG;
     let cy = bin[13];
     let sx = bin[14];
     let sy = bin[15];
@@ -33,6 +84,7 @@ pub fn decode(bin: &[u8]) -> Result<MzML, String> {
         return Err("spec index OOB".into());
     }
     let mut sidx: Vec<(u64, u32, u64, u32)> = Vec::with_capacity(n_spec);
+    let mut scrc: Vec<(u32, u32)> = Vec::with_capacity(n_spec);
     for i in 0..n_spec {
         let b = s_idx_off + i * 32;
         sidx.push((
@@ -41,6 +93,7 @@ pub fn decode(bin: &[u8]) -> Result<MzML, String> {
             rd_u64(bin, b + 12)?,
             rd_u32(bin, b + 20)?,
         ));
+        scrc.push((rd_u32(bin, b + 24)?, rd_u32(bin, b + 28)?));
     }
 
     let c_idx_len = n_ch.checked_mul(32).ok_or("chrom index ovf")?;
@@ -48,6 +101,7 @@ pub fn decode(bin: &[u8]) -> Result<MzML, String> {
         return Err("chrom index OOB".into());
     }
     let mut cidx: Vec<(u64, u32, u64, u32)> = Vec::with_capacity(n_ch);
+    let mut ccrc: Vec<(u32, u32)> = Vec::with_capacity(n_ch);
     for i in 0..n_ch {
         let b = c_idx_off + i * 32;
         cidx.push((
@@ -56,60 +110,83 @@ pub fn decode(bin: &[u8]) -> Result<MzML, String> {
             rd_u64(bin, b + 12)?,
             rd_u32(bin, b + 20)?,
         ));
+        ccrc.push((rd_u32(bin, b + 24)?, rd_u32(bin, b + 28)?));
     }
 
+    // Checked against the spare bytes each index record carries; see
+    // `DecodeOptions::verify_checksums`. Only raw (`fmt` 1 or 2) arrays have
+    // a byte span that's just `off..off + len * elem_size`, so anything else
+    // (numpress/quantized/predictive) is left unverified.
+    let verify_one = |bin: &[u8], off: u64, len: u32, fmt: u8, crc: u32| -> Result<(), String> {
+        if off == 0 || len == 0 || crc == 0 {
+            return Ok(());
+        }
+        let elem = match fmt {
+            2 => 8usize,
+            1 => 4usize,
+            _ => return Ok(()),
+        };
+        let o = off as usize;
+        let need = (len as usize).checked_mul(elem).ok_or("len overflow")?;
+        if o + need > bin.len() {
+            return Err("array OOB".into());
+        }
+        if crc32c(&bin[o..o + need]) != crc {
+            return Err("crc mismatch".into());
+        }
+        Ok(())
+    };
+
     let mut spectra: Vec<SpectrumSummary> = Vec::with_capacity(n_spec);
     let mut chroms: Vec<ChromatogramSummary> = Vec::with_capacity(n_ch);
 
-    if magic == b"BIN1" {
+    if magic == b"BIN1" || magic == b"BINQ" {
         let s_meta_len = n_spec.checked_mul(104).ok_or("spec meta ovf")?;
         if s_meta_off + s_meta_len > bin.len() {
             return Err("spec meta OOB".into());
         }
         for i in 0..n_spec {
             let b = s_meta_off + i * 104;
-            let index = rd_u32(bin, b + 0)? as usize;
-            let array_length = rd_u32(bin, b + 4)? as usize;
-            let ms_level = {
-                let v = bin[b + 8];
-                if v == 255 { None } else { Some(v) }
-            };
-            let polarity = {
-                let v = bin[b + 9];
-                if v == 255 { None } else { Some(v) }
-            };
-            let spectrum_type = {
-                let v = bin[b + 10];
-                if v == 255 { None } else { Some(v) }
-            };
-            let rt = {
-                let v = rd_f64(bin, b + 12)?;
-                if v < 0.0 { None } else { Some(v) }
-            };
-            let swl = {
-                let v = rd_f64(bin, b + 20)?;
-                if v < 0.0 { None } else { Some(v) }
-            };
-            let swu = {
-                let v = rd_f64(bin, b + 28)?;
-                if v < 0.0 { None } else { Some(v) }
-            };
-            let tic = {
-                let v = rd_f64(bin, b + 36)?;
-                if v < 0.0 { None } else { Some(v) }
+            read_fields!(bin, b, order, {
+                index: u32 @ 0,
+                array_length: u32 @ 4,
+                ms_level_raw: u8 @ 8,
+                polarity_raw: u8 @ 9,
+                spectrum_type_raw: u8 @ 10,
+                rt_raw: f64 @ 12,
+                swl_raw: f64 @ 20,
+                swu_raw: f64 @ 28,
+                tic_raw: f64 @ 36,
+                bpi_raw: f64 @ 44,
+                bpm_raw: f64 @ 52,
+                pt: f64 @ 60,
+                pl: f64 @ 68,
+                pu: f64 @ 76,
+                ps: f64 @ 84,
+            });
+            let index = index as usize;
+            let array_length = array_length as usize;
+            let ms_level = if ms_level_raw == 255 {
+                None
+            } else {
+                Some(ms_level_raw)
             };
-            let bpi = {
-                let v = rd_f64(bin, b + 44)?;
-                if v < 0.0 { None } else { Some(v) }
+            let polarity = if polarity_raw == 255 {
+                None
+            } else {
+                Some(polarity_raw)
             };
-            let bpm = {
-                let v = rd_f64(bin, b + 52)?;
-                if v < 0.0 { None } else { Some(v) }
+            let spectrum_type = if spectrum_type_raw == 255 {
+                None
+            } else {
+                Some(spectrum_type_raw)
             };
-            let pt = rd_f64(bin, b + 60)?;
-            let pl = rd_f64(bin, b + 68)?;
-            let pu = rd_f64(bin, b + 76)?;
-            let ps = rd_f64(bin, b + 84)?;
+            let rt = if rt_raw < 0.0 { None } else { Some(rt_raw) };
+            let swl = if swl_raw < 0.0 { None } else { Some(swl_raw) };
+            let swu = if swu_raw < 0.0 { None } else { Some(swu_raw) };
+            let tic = if tic_raw < 0.0 { None } else { Some(tic_raw) };
+            let bpi = if bpi_raw < 0.0 { None } else { Some(bpi_raw) };
+            let bpm = if bpm_raw < 0.0 { None } else { Some(bpm_raw) };
             let prec = {
                 let a = if pt < 0.0 { None } else { Some(pt) };
                 let d = if pl < 0.0 { None } else { Some(pl) };
@@ -151,14 +228,16 @@ pub fn decode(bin: &[u8]) -> Result<MzML, String> {
         let mut ids: Vec<(u64, u32)> = Vec::with_capacity(n_ch);
         for i in 0..n_ch {
             let b = c_meta_off + i * 24;
-            let index = rd_u32(bin, b + 0)? as usize;
-            let array_length = rd_u32(bin, b + 4)? as usize;
-            let off = rd_u64(bin, b + 8)?;
-            let len = rd_u32(bin, b + 16)?;
+            read_fields!(bin, b, order, {
+                index: u32 @ 0,
+                array_length: u32 @ 4,
+                off: u64 @ 8,
+                len: u32 @ 16,
+            });
             ids.push((off, len));
             chroms.push(ChromatogramSummary {
-                index,
-                array_length,
+                index: index as usize,
+                array_length: array_length as usize,
                 time_array: None,
                 intensity_array: None,
                 id: String::new(),
@@ -166,16 +245,32 @@ pub fn decode(bin: &[u8]) -> Result<MzML, String> {
         }
 
         for (i, (x_off, x_len, _, _)) in sidx.iter().enumerate() {
-            spectra[i].mz_array = read_array_as_f64(bin, *x_off, *x_len, sx)?;
+            if opts.verify_checksums {
+                verify_one(bin, *x_off, *x_len, sx, scrc[i].0)
+                    .map_err(|_| format!("crc mismatch @spectrum {}", i))?;
+            }
+            spectra[i].mz_array = read_array_as_f64(bin, *x_off, *x_len, sx, order)?;
         }
         for (i, (_, _, y_off, y_len)) in sidx.iter().enumerate() {
-            spectra[i].intensity_array = read_array_as_f64(bin, *y_off, *y_len, sy)?;
+            if opts.verify_checksums {
+                verify_one(bin, *y_off, *y_len, sy, scrc[i].1)
+                    .map_err(|_| format!("crc mismatch @spectrum {}", i))?;
+            }
+            spectra[i].intensity_array = read_array_as_f32(bin, *y_off, *y_len, sy, order)?;
         }
         for (i, (x_off, x_len, _, _)) in cidx.iter().enumerate() {
-            chroms[i].time_array = read_array_as_f64(bin, *x_off, *x_len, cx)?;
+            if opts.verify_checksums {
+                verify_one(bin, *x_off, *x_len, cx, ccrc[i].0)
+                    .map_err(|_| format!("crc mismatch @chromatogram {}", i))?;
+            }
+            chroms[i].time_array = read_array_as_f64(bin, *x_off, *x_len, cx, order)?;
         }
         for (i, (_, _, y_off, y_len)) in cidx.iter().enumerate() {
-            chroms[i].intensity_array = read_array_as_f64(bin, *y_off, *y_len, cy)?;
+            if opts.verify_checksums {
+                verify_one(bin, *y_off, *y_len, cy, ccrc[i].1)
+                    .map_err(|_| format!("crc mismatch @chromatogram {}", i))?;
+            }
+            chroms[i].intensity_array = read_array_as_f32(bin, *y_off, *y_len, cy, order)?;
         }
 
         for (i, (off, len)) in ids.into_iter().enumerate() {
@@ -220,25 +315,41 @@ pub fn decode(bin: &[u8]) -> Result<MzML, String> {
             });
         }
         for (i, (x_off, x_len, _, _)) in sidx.iter().enumerate() {
-            let a = read_array_as_f64(bin, *x_off, *x_len, sx)?;
+            if opts.verify_checksums {
+                verify_one(bin, *x_off, *x_len, sx, scrc[i].0)
+                    .map_err(|_| format!("crc mismatch @spectrum {}", i))?;
+            }
+            let a = read_array_as_f64(bin, *x_off, *x_len, sx, order)?;
             let n = a.as_ref().map(|v| v.len()).unwrap_or(0);
             spectra[i].mz_array = a;
             spectra[i].array_length = n.max(spectra[i].array_length);
         }
         for (i, (_, _, y_off, y_len)) in sidx.iter().enumerate() {
-            let a = read_array_as_f64(bin, *y_off, *y_len, sy)?;
+            if opts.verify_checksums {
+                verify_one(bin, *y_off, *y_len, sy, scrc[i].1)
+                    .map_err(|_| format!("crc mismatch @spectrum {}", i))?;
+            }
+            let a = read_array_as_f32(bin, *y_off, *y_len, sy, order)?;
             let n = a.as_ref().map(|v| v.len()).unwrap_or(0);
             spectra[i].intensity_array = a;
             spectra[i].array_length = n.max(spectra[i].array_length);
         }
         for (i, (x_off, x_len, _, _)) in cidx.iter().enumerate() {
-            let a = read_array_as_f64(bin, *x_off, *x_len, cx)?;
+            if opts.verify_checksums {
+                verify_one(bin, *x_off, *x_len, cx, ccrc[i].0)
+                    .map_err(|_| format!("crc mismatch @chromatogram {}", i))?;
+            }
+            let a = read_array_as_f64(bin, *x_off, *x_len, cx, order)?;
             let n = a.as_ref().map(|v| v.len()).unwrap_or(0);
             chroms[i].time_array = a;
             chroms[i].array_length = n.max(chroms[i].array_length);
         }
         for (i, (_, _, y_off, y_len)) in cidx.iter().enumerate() {
-            let a = read_array_as_f64(bin, *y_off, *y_len, cy)?;
+            if opts.verify_checksums {
+                verify_one(bin, *y_off, *y_len, cy, ccrc[i].1)
+                    .map_err(|_| format!("crc mismatch @chromatogram {}", i))?;
+            }
+            let a = read_array_as_f32(bin, *y_off, *y_len, cy, order)?;
             let n = a.as_ref().map(|v| v.len()).unwrap_or(0);
             chroms[i].intensity_array = a;
             chroms[i].array_length = n.max(chroms[i].array_length);
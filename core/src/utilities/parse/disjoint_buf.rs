@@ -0,0 +1,39 @@
+// A buffer of known final length, handed out as non-overlapping `&mut [u8]`
+// slices to multiple threads at once. This only ever backs callers that
+// first compute a full write plan (every array's final offset and length,
+// the same table `encode` fills in sequentially) and then request disjoint
+// `[offset, offset+len)` ranges from separate threads - mirroring rav1d's
+// `DisjointMut` pattern for provably non-aliasing parallel writes into one
+// preallocated frame.
+
+use std::cell::UnsafeCell;
+
+pub struct DisjointBuf {
+    buf: UnsafeCell<Vec<u8>>,
+}
+
+unsafe impl Sync for DisjointBuf {}
+
+impl DisjointBuf {
+    pub fn new(buf: Vec<u8>) -> Self {
+        Self {
+            buf: UnsafeCell::new(buf),
+        }
+    }
+
+    /// Hand out a mutable view of `[offset, offset+len)`.
+    ///
+    /// # Safety
+    /// The caller must guarantee that the ranges requested across all
+    /// concurrently-live slices (including ones handed to other threads) are
+    /// disjoint; this only checks that the range fits within the buffer.
+    pub unsafe fn get_mut(&self, offset: usize, len: usize) -> &mut [u8] {
+        let buf = unsafe { &mut *self.buf.get() };
+        assert!(offset + len <= buf.len(), "DisjointBuf: range out of bounds");
+        &mut buf[offset..offset + len]
+    }
+
+    pub fn into_inner(self) -> Vec<u8> {
+        self.buf.into_inner()
+    }
+}
@@ -1,12 +1,102 @@
 use crate::utilities::parse::{
+    crc32c::crc32c,
+    disjoint_buf::DisjointBuf,
     helper::{
-        ensure_cap, set_f64_at, set_u32_at, set_u64_at, write_f32_at, write_f32_le, write_f64_at,
-        write_f64_le,
+        BE_FLAG, ByteOrder, ensure_cap, set_f64_at, set_f64_at_order, set_u32_at, set_u32_at_order,
+        set_u64_at, set_u64_at_order, write_f32_array_ordered, write_f32_at, write_f32_le,
+        write_f64_array_ordered, write_f64_at, write_f64_le,
     },
     parse_mzml::MzML,
+    predictive::encode_pred,
+    quantize::write_quant_le,
 };
 
-pub fn encode(mzml: &MzML) -> Vec<u8> {
+/// CRC32C of the `len` bytes written at `off` in `out`, or `0` for an empty
+/// array (`off == 0`) - matching how `decode`'s `verify_one` already treats a
+/// zero checksum as "nothing to check".
+#[inline]
+fn array_crc(out: &[u8], off: u64, len: usize) -> u32 {
+    if off == 0 || len == 0 {
+        return 0;
+    }
+    let o = off as usize;
+    crc32c(&out[o..o + len])
+}
+use rayon::ThreadPoolBuilder;
+use rayon::prelude::*;
+
+/// Storage width for `intensity_array` in [`encode_with_options`]; `mz_array`/
+/// `time_array` always round-trip through `f64` regardless, matching
+/// [`encode`]/[`encode_arrays`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArrayDtype {
+    F32,
+    F64,
+}
+
+/// Options for [`encode_with_options`].
+#[derive(Clone, Copy, Debug)]
+pub struct EncodeOptions {
+    /// [`encode`]/[`encode_arrays`] always store `intensity_array` as raw
+    /// `f32` (format `1`); set this to [`ArrayDtype::F64`] to store it as raw
+    /// `f64` (format `2`) instead.
+    pub intensity_dtype: ArrayDtype,
+}
+
+impl Default for EncodeOptions {
+    fn default() -> Self {
+        Self {
+            intensity_dtype: ArrayDtype::F32,
+        }
+    }
+}
+
+/// Picks [`encode`] (`BIN1`, full per-spectrum/chromatogram metadata) when
+/// `mzml.run` carries metadata beyond bare arrays, or [`encode_arrays`]
+/// (`BINS`, arrays only) otherwise, so callers don't have to choose between
+/// the two by hand; `options` then selects `intensity_array`'s storage
+/// width. Per-array compression codes (numpress/quantized/predictive)
+/// already have dedicated encoders ([`encode_quantized`],
+/// [`encode_predictive`]) and aren't selectable here.
+pub fn encode_with_options(mzml: &MzML, options: Option<EncodeOptions>) -> Vec<u8> {
+    let opts = options.unwrap_or_default();
+    let has_full_metadata = mzml.run.as_ref().is_some_and(|r| {
+        r.spectra.iter().any(|s| {
+            s.ms_level.is_some()
+                || s.polarity.is_some()
+                || s.spectrum_type.is_some()
+                || s.retention_time.is_some()
+                || s.precursor.is_some()
+        }) || r.chromatograms.iter().any(|c| !c.id.is_empty())
+    });
+    match (has_full_metadata, opts.intensity_dtype) {
+        (true, ArrayDtype::F32) => encode(mzml),
+        (false, ArrayDtype::F32) => encode_arrays(mzml),
+        (true, ArrayDtype::F64) => encode_bin1_f64_intensity(mzml),
+        (false, ArrayDtype::F64) => encode_arrays_f64_intensity(mzml),
+    }
+}
+
+/// Writes `vals` at the current cursor with the on-disk width/encoding
+/// `dtype` selects - raw `f32` for [`ArrayDtype::F32`], or widened to raw
+/// `f64` for [`ArrayDtype::F64`] - returning the same `(offset, count)` pair
+/// [`helper::write_f32_le`]/[`helper::write_f64_le`] do.
+#[inline]
+fn write_intensity(out: &mut Vec<u8>, cur: &mut usize, vals: &[f32], dtype: ArrayDtype) -> (u64, u32) {
+    match dtype {
+        ArrayDtype::F32 => unsafe { write_f32_le(out, cur, vals) },
+        ArrayDtype::F64 => {
+            let v64: Vec<f64> = vals.iter().map(|&x| x as f64).collect();
+            unsafe { write_f64_le(out, cur, &v64) }
+        }
+    }
+}
+
+/// Shared `BIN1` encoder backing both [`encode`] (raw `f32` intensity, format
+/// `1`) and the `f64`-intensity path [`encode_with_options`] dispatches to
+/// (format `2`); `mz_array`/`time_array` always round-trip through `f64`
+/// regardless of `intensity_dtype`.
+fn encode_bin1(mzml: &MzML, intensity_dtype: ArrayDtype) -> Vec<u8> {
     const H: usize = 64;
     const SI: usize = 32;
     const CI: usize = 32;
@@ -18,6 +108,11 @@ pub fn encode(mzml: &MzML) -> Vec<u8> {
         (x + 7) & !7
     }
 
+    let (intensity_fmt, intensity_width): (u8, usize) = match intensity_dtype {
+        ArrayDtype::F32 => (1, 4),
+        ArrayDtype::F64 => (2, 8),
+    };
+
     let run = match mzml.run.as_ref() {
         Some(r) => r,
         None => {
@@ -26,9 +121,9 @@ pub fn encode(mzml: &MzML) -> Vec<u8> {
             set_u32_at(&mut out, 4, 0);
             set_u32_at(&mut out, 8, 0);
             out[12] = 2;
-            out[13] = 1;
+            out[13] = intensity_fmt;
             out[14] = 2;
-            out[15] = 1;
+            out[15] = intensity_fmt;
             set_u64_at(&mut out, 56, H as u64);
             return out;
         }
@@ -53,7 +148,7 @@ pub fn encode(mzml: &MzML) -> Vec<u8> {
     for s in &run.spectra {
         if let Some(v) = &s.intensity_array {
             plan = a8(plan);
-            plan += v.len() * 4;
+            plan += v.len() * intensity_width;
         }
     }
     for c in &run.chromatograms {
@@ -65,7 +160,7 @@ pub fn encode(mzml: &MzML) -> Vec<u8> {
     for c in &run.chromatograms {
         if let Some(v) = &c.intensity_array {
             plan = a8(plan);
-            plan += v.len() * 4;
+            plan += v.len() * intensity_width;
         }
     }
     for c in &run.chromatograms {
@@ -86,9 +181,9 @@ pub fn encode(mzml: &MzML) -> Vec<u8> {
     set_u32_at(&mut out, 4, n_spec);
     set_u32_at(&mut out, 8, n_ch);
     out[12] = 2;
-    out[13] = 1;
+    out[13] = intensity_fmt;
     out[14] = 2;
-    out[15] = 1;
+    out[15] = intensity_fmt;
 
     let spec_index_off = cur as u64;
     cur += sb;
@@ -111,7 +206,7 @@ pub fn encode(mzml: &MzML) -> Vec<u8> {
     let mut sy: Vec<(u64, u32)> = Vec::with_capacity(n_spec as usize);
     for s in &run.spectra {
         let p = match &s.intensity_array {
-            Some(v) if !v.is_empty() => unsafe { write_f32_le(&mut out, &mut cur, v) },
+            Some(v) if !v.is_empty() => write_intensity(&mut out, &mut cur, v, intensity_dtype),
             _ => (0, 0),
         };
         sy.push(p);
@@ -127,7 +222,7 @@ pub fn encode(mzml: &MzML) -> Vec<u8> {
     let mut cy: Vec<(u64, u32)> = Vec::with_capacity(n_ch as usize);
     for c in &run.chromatograms {
         let p = match &c.intensity_array {
-            Some(v) if !v.is_empty() => unsafe { write_f32_le(&mut out, &mut cur, v) },
+            Some(v) if !v.is_empty() => write_intensity(&mut out, &mut cur, v, intensity_dtype),
             _ => (0, 0),
         };
         cy.push(p);
@@ -154,21 +249,27 @@ pub fn encode(mzml: &MzML) -> Vec<u8> {
         let b = spec_index_off as usize + i * SI;
         let (x_off, x_len) = sx[i];
         let (y_off, y_len) = sy[i];
+        let x_crc = array_crc(&out, x_off, x_len as usize * 8);
+        let y_crc = array_crc(&out, y_off, y_len as usize * intensity_width);
         set_u64_at(&mut out, b + 0, x_off);
         set_u32_at(&mut out, b + 8, x_len);
         set_u64_at(&mut out, b + 12, y_off);
         set_u32_at(&mut out, b + 20, y_len);
-        set_u64_at(&mut out, b + 24, 0);
+        set_u32_at(&mut out, b + 24, x_crc);
+        set_u32_at(&mut out, b + 28, y_crc);
     }
     for i in 0..(n_ch as usize) {
         let b = chrom_index_off as usize + i * CI;
         let (x_off, x_len) = cx[i];
         let (y_off, y_len) = cy[i];
+        let x_crc = array_crc(&out, x_off, x_len as usize * 8);
+        let y_crc = array_crc(&out, y_off, y_len as usize * intensity_width);
         set_u64_at(&mut out, b + 0, x_off);
         set_u32_at(&mut out, b + 8, x_len);
         set_u64_at(&mut out, b + 12, y_off);
         set_u32_at(&mut out, b + 20, y_len);
-        set_u64_at(&mut out, b + 24, 0);
+        set_u32_at(&mut out, b + 24, x_crc);
+        set_u32_at(&mut out, b + 28, y_crc);
     }
 
     for (i, s) in run.spectra.iter().enumerate() {
@@ -222,7 +323,31 @@ pub fn encode(mzml: &MzML) -> Vec<u8> {
     out
 }
 
-pub fn encode_arrays(mzml: &MzML) -> Vec<u8> {
+/// [`encode`] with `intensity_array` stored as raw `f64` (format `2`)
+/// instead of `f32`; see [`encode_with_options`].
+fn encode_bin1_f64_intensity(mzml: &MzML) -> Vec<u8> {
+    encode_bin1(mzml, ArrayDtype::F64)
+}
+
+/// Writes `vals` directly at `off` in `buf` with the on-disk width `dtype`
+/// selects, mirroring [`write_intensity`] for the fixed-offset `BINS` layout
+/// instead of a streaming cursor.
+#[inline]
+fn write_intensity_at(buf: &mut [u8], off: usize, vals: &[f32], dtype: ArrayDtype) {
+    match dtype {
+        ArrayDtype::F32 => unsafe { write_f32_at(buf, off, vals) },
+        ArrayDtype::F64 => {
+            let v64: Vec<f64> = vals.iter().map(|&x| x as f64).collect();
+            unsafe { write_f64_at(buf, off, &v64) }
+        }
+    }
+}
+
+/// Shared `BINS` encoder backing both [`encode_arrays`] (raw `f32` intensity,
+/// format `1`) and the `f64`-intensity path [`encode_with_options`] dispatches
+/// to (format `2`); `mz_array`/`time_array` always round-trip through `f64`
+/// regardless of `intensity_dtype`.
+fn encode_arrays_impl(mzml: &MzML, intensity_dtype: ArrayDtype) -> Vec<u8> {
     const H: usize = 64;
     const I: usize = 32;
 
@@ -231,6 +356,11 @@ pub fn encode_arrays(mzml: &MzML) -> Vec<u8> {
         (x + 7) & !7
     }
 
+    let (intensity_fmt, intensity_width): (u8, usize) = match intensity_dtype {
+        ArrayDtype::F32 => (1, 4),
+        ArrayDtype::F64 => (2, 8),
+    };
+
     let run = match mzml.run.as_ref() {
         Some(r) => r,
         None => {
@@ -238,9 +368,9 @@ pub fn encode_arrays(mzml: &MzML) -> Vec<u8> {
             out[0..4].copy_from_slice(b"BINS");
             set_u32_at(&mut out, 4, 1);
             out[12] = 2;
-            out[13] = 1;
+            out[13] = intensity_fmt;
             out[14] = 2;
-            out[15] = 1;
+            out[15] = intensity_fmt;
             set_u64_at(&mut out, 48, H as u64);
             set_u64_at(&mut out, 56, H as u64);
             return out;
@@ -270,7 +400,7 @@ pub fn encode_arrays(mzml: &MzML) -> Vec<u8> {
         if let Some(v) = &s.intensity_array {
             cur = a8(cur);
             sin.push((cur as u64, v.len() as u32));
-            cur += v.len() * 4;
+            cur += v.len() * intensity_width;
         } else {
             sin.push((0, 0));
         }
@@ -290,7 +420,7 @@ pub fn encode_arrays(mzml: &MzML) -> Vec<u8> {
         if let Some(v) = &c.intensity_array {
             cur = a8(cur);
             cin.push((cur as u64, v.len() as u32));
-            cur += v.len() * 4;
+            cur += v.len() * intensity_width;
         } else {
             cin.push((0, 0));
         }
@@ -303,33 +433,14 @@ pub fn encode_arrays(mzml: &MzML) -> Vec<u8> {
     set_u32_at(&mut out, 4, n_spec);
     set_u32_at(&mut out, 8, n_ch);
     out[12] = 2;
-    out[13] = 1;
+    out[13] = intensity_fmt;
     out[14] = 2;
-    out[15] = 1;
+    out[15] = intensity_fmt;
 
     let s_idx_off = H as u64;
     let c_idx_off = (H + sb) as u64;
     let data_off = (H + sb + cb) as u64;
 
-    for i in 0..(n_spec as usize) {
-        let b = s_idx_off as usize + i * I;
-        let (x_off, x_len) = smz[i];
-        let (y_off, y_len) = sin[i];
-        set_u64_at(&mut out, b + 0, x_off);
-        set_u32_at(&mut out, b + 8, x_len);
-        set_u64_at(&mut out, b + 12, y_off);
-        set_u32_at(&mut out, b + 20, y_len);
-    }
-    for i in 0..(n_ch as usize) {
-        let b = c_idx_off as usize + i * I;
-        let (x_off, x_len) = ctm[i];
-        let (y_off, y_len) = cin[i];
-        set_u64_at(&mut out, b + 0, x_off);
-        set_u32_at(&mut out, b + 8, x_len);
-        set_u64_at(&mut out, b + 12, y_off);
-        set_u32_at(&mut out, b + 20, y_len);
-    }
-
     for (i, s) in run.spectra.iter().enumerate() {
         if let (Some(v), (off, len)) = (&s.mz_array, smz[i]) {
             if off != 0 && len != 0 {
@@ -340,7 +451,7 @@ pub fn encode_arrays(mzml: &MzML) -> Vec<u8> {
     for (i, s) in run.spectra.iter().enumerate() {
         if let (Some(v), (off, len)) = (&s.intensity_array, sin[i]) {
             if off != 0 && len != 0 {
-                unsafe { write_f32_at(&mut out, off as usize, v) }
+                write_intensity_at(&mut out, off as usize, v, intensity_dtype);
             }
         }
     }
@@ -354,11 +465,38 @@ pub fn encode_arrays(mzml: &MzML) -> Vec<u8> {
     for (i, c) in run.chromatograms.iter().enumerate() {
         if let (Some(v), (off, len)) = (&c.intensity_array, cin[i]) {
             if off != 0 && len != 0 {
-                unsafe { write_f32_at(&mut out, off as usize, v) }
+                write_intensity_at(&mut out, off as usize, v, intensity_dtype);
             }
         }
     }
 
+    for i in 0..(n_spec as usize) {
+        let b = s_idx_off as usize + i * I;
+        let (x_off, x_len) = smz[i];
+        let (y_off, y_len) = sin[i];
+        let x_crc = array_crc(&out, x_off, x_len as usize * 8);
+        let y_crc = array_crc(&out, y_off, y_len as usize * intensity_width);
+        set_u64_at(&mut out, b + 0, x_off);
+        set_u32_at(&mut out, b + 8, x_len);
+        set_u64_at(&mut out, b + 12, y_off);
+        set_u32_at(&mut out, b + 20, y_len);
+        set_u32_at(&mut out, b + 24, x_crc);
+        set_u32_at(&mut out, b + 28, y_crc);
+    }
+    for i in 0..(n_ch as usize) {
+        let b = c_idx_off as usize + i * I;
+        let (x_off, x_len) = ctm[i];
+        let (y_off, y_len) = cin[i];
+        let x_crc = array_crc(&out, x_off, x_len as usize * 8);
+        let y_crc = array_crc(&out, y_off, y_len as usize * intensity_width);
+        set_u64_at(&mut out, b + 0, x_off);
+        set_u32_at(&mut out, b + 8, x_len);
+        set_u64_at(&mut out, b + 12, y_off);
+        set_u32_at(&mut out, b + 20, y_len);
+        set_u32_at(&mut out, b + 24, x_crc);
+        set_u32_at(&mut out, b + 28, y_crc);
+    }
+
     set_u64_at(&mut out, 16, s_idx_off);
     set_u64_at(&mut out, 24, c_idx_off);
     set_u64_at(&mut out, 32, 0);
@@ -368,3 +506,1023 @@ pub fn encode_arrays(mzml: &MzML) -> Vec<u8> {
 
     out
 }
+
+/// [`encode_arrays`] with `intensity_array` stored as raw `f64` (format `2`)
+/// instead of `f32`; see [`encode_with_options`].
+fn encode_arrays_f64_intensity(mzml: &MzML) -> Vec<u8> {
+    encode_arrays_impl(mzml, ArrayDtype::F64)
+}
+
+/// Encodes `mzml` as `BIN1` with full per-spectrum/chromatogram metadata and
+/// `intensity_array` stored as raw `f32` (format `1`); see
+/// [`encode_with_options`] to pick `f64` instead.
+pub fn encode(mzml: &MzML) -> Vec<u8> {
+    encode_bin1(mzml, ArrayDtype::F32)
+}
+
+/// Encodes `mzml` as `BINS` (arrays only, no per-spectrum/chromatogram
+/// metadata) with `intensity_array` stored as raw `f32` (format `1`); see
+/// [`encode_with_options`] to pick `f64` instead.
+pub fn encode_arrays(mzml: &MzML) -> Vec<u8> {
+    encode_arrays_impl(mzml, ArrayDtype::F32)
+}
+
+/// Like [`encode`], but stores `intensity_array` (and, when `quantize_time`
+/// is set, `time_array`) with the block-quantized codec from
+/// [`crate::utilities::parse::quantize`] instead of raw `f32`/`f64`, trading
+/// exactness for a bounded, controllable size reduction. `bits` selects
+/// `int8` or `int4` codes (anything else is treated as `8`); `block` is the
+/// number of values sharing one scale factor. `mz_array` is always stored
+/// raw, since quantizing it would distort peak-picking downstream.
+pub fn encode_quantized(mzml: &MzML, bits: u8, block: usize, quantize_time: bool) -> Vec<u8> {
+    const H: usize = 64;
+    const SI: usize = 32;
+    const CI: usize = 32;
+    const SM: usize = 104;
+    const CM: usize = 24;
+
+    #[inline]
+    fn a8(x: usize) -> usize {
+        (x + 7) & !7
+    }
+
+    let bits = if bits == 4 { 4 } else { 8 };
+
+    let run = match mzml.run.as_ref() {
+        Some(r) => r,
+        None => {
+            let mut out = vec![0u8; H];
+            out[0..4].copy_from_slice(b"BINQ");
+            set_u32_at(&mut out, 4, 0);
+            set_u32_at(&mut out, 8, 0);
+            out[12] = if quantize_time { 5 } else { 2 };
+            out[13] = 5;
+            out[14] = 2;
+            out[15] = 5;
+            set_u64_at(&mut out, 56, H as u64);
+            return out;
+        }
+    };
+
+    let n_spec = run.spectra.len() as u32;
+    let n_ch = run.chromatograms.len() as u32;
+
+    let sb = (n_spec as usize) * SI;
+    let cb = (n_ch as usize) * CI;
+    let smb = (n_spec as usize) * SM;
+    let cmb = (n_ch as usize) * CM;
+
+    // Quantized block sizes are data-dependent (all-zero blocks are elided),
+    // so unlike `encode` this only precomputes the fixed-size header/index/
+    // meta region; the data region grows on demand via `ensure_cap` the same
+    // way the numpress-backed writers already do.
+    let plan = H + sb + cb + smb + cmb;
+
+    let mut out: Vec<u8> = Vec::with_capacity(plan);
+    unsafe {
+        out.set_len(plan);
+    }
+    let mut cur = H;
+
+    out[0..4].copy_from_slice(b"BINQ");
+    set_u32_at(&mut out, 4, n_spec);
+    set_u32_at(&mut out, 8, n_ch);
+    out[12] = if quantize_time { 5 } else { 2 };
+    out[13] = 5;
+    out[14] = 2;
+    out[15] = 5;
+
+    let spec_index_off = cur as u64;
+    cur += sb;
+    let chrom_index_off = cur as u64;
+    cur += cb;
+    let spec_meta_off = cur as u64;
+    cur += smb;
+    let chrom_meta_off = cur as u64;
+    cur += cmb;
+    let data_off = cur as u64;
+
+    let mut sx: Vec<(u64, u32)> = Vec::with_capacity(n_spec as usize);
+    for s in &run.spectra {
+        let p = match &s.mz_array {
+            Some(v) if !v.is_empty() => unsafe { write_f64_le(&mut out, &mut cur, v) },
+            _ => (0, 0),
+        };
+        sx.push(p);
+    }
+    let mut sy: Vec<(u64, u32)> = Vec::with_capacity(n_spec as usize);
+    for s in &run.spectra {
+        let p = match &s.intensity_array {
+            Some(v) if !v.is_empty() => {
+                let v64: Vec<f64> = v.iter().map(|&x| x as f64).collect();
+                write_quant_le(&mut out, &mut cur, &v64, bits, block)
+            }
+            _ => (0, 0),
+        };
+        sy.push(p);
+    }
+    let mut cx: Vec<(u64, u32)> = Vec::with_capacity(n_ch as usize);
+    for c in &run.chromatograms {
+        let p = match &c.time_array {
+            Some(v) if !v.is_empty() => {
+                if quantize_time {
+                    write_quant_le(&mut out, &mut cur, v, bits, block)
+                } else {
+                    unsafe { write_f64_le(&mut out, &mut cur, v) }
+                }
+            }
+            _ => (0, 0),
+        };
+        cx.push(p);
+    }
+    let mut cy: Vec<(u64, u32)> = Vec::with_capacity(n_ch as usize);
+    for c in &run.chromatograms {
+        let p = match &c.intensity_array {
+            Some(v) if !v.is_empty() => {
+                let v64: Vec<f64> = v.iter().map(|&x| x as f64).collect();
+                write_quant_le(&mut out, &mut cur, &v64, bits, block)
+            }
+            _ => (0, 0),
+        };
+        cy.push(p);
+    }
+    let mut cid: Vec<(u64, u32)> = Vec::with_capacity(n_ch as usize);
+    for c in &run.chromatograms {
+        let s = c.id.as_bytes();
+        if s.is_empty() {
+            cid.push((0, 0));
+        } else {
+            cur = a8(cur);
+            let off = cur as u64;
+            let len = s.len() as u32;
+            ensure_cap(&mut out, cur + s.len());
+            unsafe {
+                std::ptr::copy_nonoverlapping(s.as_ptr(), out.as_mut_ptr().add(cur), s.len());
+            }
+            cur += s.len();
+            cid.push((off, len));
+        }
+    }
+
+    for i in 0..(n_spec as usize) {
+        let b = spec_index_off as usize + i * SI;
+        let (x_off, x_len) = sx[i];
+        let (y_off, y_len) = sy[i];
+        set_u64_at(&mut out, b + 0, x_off);
+        set_u32_at(&mut out, b + 8, x_len);
+        set_u64_at(&mut out, b + 12, y_off);
+        set_u32_at(&mut out, b + 20, y_len);
+        set_u64_at(&mut out, b + 24, 0);
+    }
+    for i in 0..(n_ch as usize) {
+        let b = chrom_index_off as usize + i * CI;
+        let (x_off, x_len) = cx[i];
+        let (y_off, y_len) = cy[i];
+        set_u64_at(&mut out, b + 0, x_off);
+        set_u32_at(&mut out, b + 8, x_len);
+        set_u64_at(&mut out, b + 12, y_off);
+        set_u32_at(&mut out, b + 20, y_len);
+        set_u64_at(&mut out, b + 24, 0);
+    }
+
+    for (i, s) in run.spectra.iter().enumerate() {
+        let b = spec_meta_off as usize + i * SM;
+        set_u32_at(&mut out, b + 0, s.index as u32);
+        set_u32_at(&mut out, b + 4, s.array_length as u32);
+        out[b + 8] = s.ms_level.unwrap_or(255);
+        out[b + 9] = s.polarity.unwrap_or(255);
+        out[b + 10] = s.spectrum_type.unwrap_or(255);
+        out[b + 11] = 0;
+        set_f64_at(&mut out, b + 12, s.retention_time.unwrap_or(-1.0));
+        set_f64_at(&mut out, b + 20, s.scan_window_lower_limit.unwrap_or(-1.0));
+        set_f64_at(&mut out, b + 28, s.scan_window_upper_limit.unwrap_or(-1.0));
+        set_f64_at(&mut out, b + 36, s.total_ion_current.unwrap_or(-1.0));
+        set_f64_at(&mut out, b + 44, s.base_peak_intensity.unwrap_or(-1.0));
+        set_f64_at(&mut out, b + 52, s.base_peak_mz.unwrap_or(-1.0));
+        let (tgt, low, up, sel) = match &s.precursor {
+            Some(p) => (
+                p.isolation_window_target_mz.unwrap_or(-1.0),
+                p.isolation_window_lower_offset.unwrap_or(-1.0),
+                p.isolation_window_upper_offset.unwrap_or(-1.0),
+                p.selected_ion_mz.unwrap_or(-1.0),
+            ),
+            None => (-1.0, -1.0, -1.0, -1.0),
+        };
+        set_f64_at(&mut out, b + 60, tgt);
+        set_f64_at(&mut out, b + 68, low);
+        set_f64_at(&mut out, b + 76, up);
+        set_f64_at(&mut out, b + 84, sel);
+    }
+
+    for i in 0..(n_ch as usize) {
+        let b = chrom_meta_off as usize + i * CM;
+        set_u32_at(&mut out, b + 0, run.chromatograms[i].index as u32);
+        set_u32_at(&mut out, b + 4, run.chromatograms[i].array_length as u32);
+        let (off, len) = cid[i];
+        set_u64_at(&mut out, b + 8, off);
+        set_u32_at(&mut out, b + 16, len);
+        set_u32_at(&mut out, b + 20, 0);
+    }
+
+    let total = cur as u64;
+    set_u64_at(&mut out, 16, if n_spec > 0 { spec_index_off } else { 0 });
+    set_u64_at(&mut out, 24, if n_ch > 0 { chrom_index_off } else { 0 });
+    set_u64_at(&mut out, 32, if n_spec > 0 { spec_meta_off } else { 0 });
+    set_u64_at(&mut out, 40, if n_ch > 0 { chrom_meta_off } else { 0 });
+    set_u64_at(&mut out, 48, data_off);
+    set_u64_at(&mut out, 56, total);
+
+    out.truncate(cur);
+    out
+}
+
+/// Below this many total array elements, the thread-pool setup in
+/// [`encode_parallel`] costs more than the sequential [`encode`] path it
+/// would replace.
+const PARALLEL_THRESHOLD: usize = 1_000_000;
+
+/// Parallel variant of [`encode`], producing byte-identical `BIN1` output.
+/// `encode` already runs a planning pass that fixes every array's final
+/// offset and length before a single byte is written, so the per-array
+/// copies into the `data_off` region are provably non-overlapping; here they
+/// are dispatched across a rayon thread pool of `cores` threads through
+/// [`DisjointBuf`] instead of running one after another on the main thread.
+/// The index- and metadata-table fills stay on the main thread. Falls back
+/// to [`encode`] below [`PARALLEL_THRESHOLD`] total array elements.
+pub fn encode_parallel(mzml: &MzML, cores: usize) -> Vec<u8> {
+    const H: usize = 64;
+    const SI: usize = 32;
+    const CI: usize = 32;
+    const SM: usize = 104;
+    const CM: usize = 24;
+
+    #[inline]
+    fn a8(x: usize) -> usize {
+        (x + 7) & !7
+    }
+
+    let run = match mzml.run.as_ref() {
+        Some(r) => r,
+        None => return encode(mzml),
+    };
+
+    let total_elems: usize = run
+        .spectra
+        .iter()
+        .map(|s| {
+            s.mz_array.as_ref().map_or(0, |v| v.len())
+                + s.intensity_array.as_ref().map_or(0, |v| v.len())
+        })
+        .sum::<usize>()
+        + run
+            .chromatograms
+            .iter()
+            .map(|c| {
+                c.time_array.as_ref().map_or(0, |v| v.len())
+                    + c.intensity_array.as_ref().map_or(0, |v| v.len())
+            })
+            .sum::<usize>();
+
+    if total_elems < PARALLEL_THRESHOLD {
+        return encode(mzml);
+    }
+
+    let n_spec = run.spectra.len() as u32;
+    let n_ch = run.chromatograms.len() as u32;
+
+    let sb = (n_spec as usize) * SI;
+    let cb = (n_ch as usize) * CI;
+    let smb = (n_spec as usize) * SM;
+    let cmb = (n_ch as usize) * CM;
+
+    // Planning pass: fix every array's final (offset, byte len) up front,
+    // the same arithmetic `encode` performs, except offsets are captured
+    // here instead of falling out of `write_f64_le`/`write_f32_le` as they
+    // run.
+    let mut cur = H + sb + cb + smb + cmb;
+
+    let mut sx: Vec<(usize, usize)> = Vec::with_capacity(n_spec as usize);
+    for s in &run.spectra {
+        match &s.mz_array {
+            Some(v) if !v.is_empty() => {
+                cur = a8(cur);
+                sx.push((cur, v.len() * 8));
+                cur += v.len() * 8;
+            }
+            _ => sx.push((0, 0)),
+        }
+    }
+    let mut sy: Vec<(usize, usize)> = Vec::with_capacity(n_spec as usize);
+    for s in &run.spectra {
+        match &s.intensity_array {
+            Some(v) if !v.is_empty() => {
+                cur = a8(cur);
+                sy.push((cur, v.len() * 4));
+                cur += v.len() * 4;
+            }
+            _ => sy.push((0, 0)),
+        }
+    }
+    let mut cx: Vec<(usize, usize)> = Vec::with_capacity(n_ch as usize);
+    for c in &run.chromatograms {
+        match &c.time_array {
+            Some(v) if !v.is_empty() => {
+                cur = a8(cur);
+                cx.push((cur, v.len() * 8));
+                cur += v.len() * 8;
+            }
+            _ => cx.push((0, 0)),
+        }
+    }
+    let mut cy: Vec<(usize, usize)> = Vec::with_capacity(n_ch as usize);
+    for c in &run.chromatograms {
+        match &c.intensity_array {
+            Some(v) if !v.is_empty() => {
+                cur = a8(cur);
+                cy.push((cur, v.len() * 4));
+                cur += v.len() * 4;
+            }
+            _ => cy.push((0, 0)),
+        }
+    }
+    let mut cid: Vec<(usize, usize)> = Vec::with_capacity(n_ch as usize);
+    for c in &run.chromatograms {
+        let b = c.id.as_bytes();
+        if b.is_empty() {
+            cid.push((0, 0));
+        } else {
+            cur = a8(cur);
+            cid.push((cur, b.len()));
+            cur += b.len();
+        }
+    }
+
+    let total = cur;
+    let disjoint = DisjointBuf::new(vec![0u8; total]);
+
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(cores.max(1))
+        .thread_name(|i| format!("encode-{}", i))
+        .build()
+        .expect("failed to build rayon pool");
+
+    pool.install(|| {
+        run.spectra
+            .par_iter()
+            .zip(sx.par_iter())
+            .for_each(|(s, &(off, len))| {
+                if len == 0 {
+                    return;
+                }
+                if let Some(v) = &s.mz_array {
+                    let dst = unsafe { disjoint.get_mut(off, len) };
+                    unsafe {
+                        std::ptr::copy_nonoverlapping(v.as_ptr() as *const u8, dst.as_mut_ptr(), len);
+                    }
+                }
+            });
+        run.spectra
+            .par_iter()
+            .zip(sy.par_iter())
+            .for_each(|(s, &(off, len))| {
+                if len == 0 {
+                    return;
+                }
+                if let Some(v) = &s.intensity_array {
+                    let dst = unsafe { disjoint.get_mut(off, len) };
+                    unsafe {
+                        std::ptr::copy_nonoverlapping(v.as_ptr() as *const u8, dst.as_mut_ptr(), len);
+                    }
+                }
+            });
+        run.chromatograms
+            .par_iter()
+            .zip(cx.par_iter())
+            .for_each(|(c, &(off, len))| {
+                if len == 0 {
+                    return;
+                }
+                if let Some(v) = &c.time_array {
+                    let dst = unsafe { disjoint.get_mut(off, len) };
+                    unsafe {
+                        std::ptr::copy_nonoverlapping(v.as_ptr() as *const u8, dst.as_mut_ptr(), len);
+                    }
+                }
+            });
+        run.chromatograms
+            .par_iter()
+            .zip(cy.par_iter())
+            .for_each(|(c, &(off, len))| {
+                if len == 0 {
+                    return;
+                }
+                if let Some(v) = &c.intensity_array {
+                    let dst = unsafe { disjoint.get_mut(off, len) };
+                    unsafe {
+                        std::ptr::copy_nonoverlapping(v.as_ptr() as *const u8, dst.as_mut_ptr(), len);
+                    }
+                }
+            });
+        run.chromatograms
+            .par_iter()
+            .zip(cid.par_iter())
+            .for_each(|(c, &(off, len))| {
+                if len == 0 {
+                    return;
+                }
+                let b = c.id.as_bytes();
+                let dst = unsafe { disjoint.get_mut(off, len) };
+                unsafe {
+                    std::ptr::copy_nonoverlapping(b.as_ptr(), dst.as_mut_ptr(), len);
+                }
+            });
+    });
+
+    let mut out = disjoint.into_inner();
+
+    out[0..4].copy_from_slice(b"BIN1");
+    set_u32_at(&mut out, 4, n_spec);
+    set_u32_at(&mut out, 8, n_ch);
+    out[12] = 2;
+    out[13] = 1;
+    out[14] = 2;
+    out[15] = 1;
+
+    let spec_index_off = H;
+    let chrom_index_off = spec_index_off + sb;
+    let spec_meta_off = chrom_index_off + cb;
+    let chrom_meta_off = spec_meta_off + smb;
+    let data_off = chrom_meta_off + cmb;
+
+    for i in 0..(n_spec as usize) {
+        let b = spec_index_off + i * SI;
+        let (x_off, x_len) = sx[i];
+        let (y_off, y_len) = sy[i];
+        set_u64_at(&mut out, b + 0, if x_len > 0 { x_off as u64 } else { 0 });
+        set_u32_at(&mut out, b + 8, (x_len / 8) as u32);
+        set_u64_at(&mut out, b + 12, if y_len > 0 { y_off as u64 } else { 0 });
+        set_u32_at(&mut out, b + 20, (y_len / 4) as u32);
+        let x_crc = array_crc(&out, x_off as u64, x_len);
+        let y_crc = array_crc(&out, y_off as u64, y_len);
+        set_u32_at(&mut out, b + 24, x_crc);
+        set_u32_at(&mut out, b + 28, y_crc);
+    }
+    for i in 0..(n_ch as usize) {
+        let b = chrom_index_off + i * CI;
+        let (x_off, x_len) = cx[i];
+        let (y_off, y_len) = cy[i];
+        set_u64_at(&mut out, b + 0, if x_len > 0 { x_off as u64 } else { 0 });
+        set_u32_at(&mut out, b + 8, (x_len / 8) as u32);
+        set_u64_at(&mut out, b + 12, if y_len > 0 { y_off as u64 } else { 0 });
+        set_u32_at(&mut out, b + 20, (y_len / 4) as u32);
+        let x_crc = array_crc(&out, x_off as u64, x_len);
+        let y_crc = array_crc(&out, y_off as u64, y_len);
+        set_u32_at(&mut out, b + 24, x_crc);
+        set_u32_at(&mut out, b + 28, y_crc);
+    }
+
+    for (i, s) in run.spectra.iter().enumerate() {
+        let b = spec_meta_off + i * SM;
+        set_u32_at(&mut out, b + 0, s.index as u32);
+        set_u32_at(&mut out, b + 4, s.array_length as u32);
+        out[b + 8] = s.ms_level.unwrap_or(255);
+        out[b + 9] = s.polarity.unwrap_or(255);
+        out[b + 10] = s.spectrum_type.unwrap_or(255);
+        out[b + 11] = 0;
+        set_f64_at(&mut out, b + 12, s.retention_time.unwrap_or(-1.0));
+        set_f64_at(&mut out, b + 20, s.scan_window_lower_limit.unwrap_or(-1.0));
+        set_f64_at(&mut out, b + 28, s.scan_window_upper_limit.unwrap_or(-1.0));
+        set_f64_at(&mut out, b + 36, s.total_ion_current.unwrap_or(-1.0));
+        set_f64_at(&mut out, b + 44, s.base_peak_intensity.unwrap_or(-1.0));
+        set_f64_at(&mut out, b + 52, s.base_peak_mz.unwrap_or(-1.0));
+        let (tgt, low, up, sel) = match &s.precursor {
+            Some(p) => (
+                p.isolation_window_target_mz.unwrap_or(-1.0),
+                p.isolation_window_lower_offset.unwrap_or(-1.0),
+                p.isolation_window_upper_offset.unwrap_or(-1.0),
+                p.selected_ion_mz.unwrap_or(-1.0),
+            ),
+            None => (-1.0, -1.0, -1.0, -1.0),
+        };
+        set_f64_at(&mut out, b + 60, tgt);
+        set_f64_at(&mut out, b + 68, low);
+        set_f64_at(&mut out, b + 76, up);
+        set_f64_at(&mut out, b + 84, sel);
+    }
+
+    for i in 0..(n_ch as usize) {
+        let b = chrom_meta_off + i * CM;
+        set_u32_at(&mut out, b + 0, run.chromatograms[i].index as u32);
+        set_u32_at(&mut out, b + 4, run.chromatograms[i].array_length as u32);
+        let (off, len) = cid[i];
+        set_u64_at(&mut out, b + 8, if len > 0 { off as u64 } else { 0 });
+        set_u32_at(&mut out, b + 16, len as u32);
+        set_u32_at(&mut out, b + 20, 0);
+    }
+
+    set_u64_at(&mut out, 16, if n_spec > 0 { spec_index_off as u64 } else { 0 });
+    set_u64_at(&mut out, 24, if n_ch > 0 { chrom_index_off as u64 } else { 0 });
+    set_u64_at(&mut out, 32, if n_spec > 0 { spec_meta_off as u64 } else { 0 });
+    set_u64_at(&mut out, 40, if n_ch > 0 { chrom_meta_off as u64 } else { 0 });
+    set_u64_at(&mut out, 48, data_off as u64);
+    set_u64_at(&mut out, 56, total as u64);
+
+    out
+}
+
+/// Like [`encode`], but writes every multi-byte header/index/meta numeric
+/// field and the raw `mz`/`time`/`intensity` arrays (formats `1`/`2`) in
+/// `order` instead of always little-endian, and sets [`helper::BE_FLAG`] on
+/// the chrom-time format byte so [`crate::utilities::parse::decode::decode`]
+/// picks the same order back up. See the [`ByteOrder`] doc comment for why
+/// that bit is free and why the numpress/quantization codecs are excluded.
+pub fn encode_with_order(mzml: &MzML, order: ByteOrder) -> Vec<u8> {
+    const H: usize = 64;
+    const SI: usize = 32;
+    const CI: usize = 32;
+    const SM: usize = 104;
+    const CM: usize = 24;
+
+    #[inline]
+    fn a8(x: usize) -> usize {
+        (x + 7) & !7
+    }
+
+    let run = match mzml.run.as_ref() {
+        Some(r) => r,
+        None => {
+            let mut out = vec![0u8; H];
+            out[0..4].copy_from_slice(b"BIN1");
+            set_u32_at_order(&mut out, 4, 0, order);
+            set_u32_at_order(&mut out, 8, 0, order);
+            out[12] = if order == ByteOrder::Big { 2 | BE_FLAG } else { 2 };
+            out[13] = 1;
+            out[14] = 2;
+            out[15] = 1;
+            set_u64_at_order(&mut out, 56, H as u64, order);
+            return out;
+        }
+    };
+
+    let n_spec = run.spectra.len() as u32;
+    let n_ch = run.chromatograms.len() as u32;
+
+    let sb = (n_spec as usize) * SI;
+    let cb = (n_ch as usize) * CI;
+    let smb = (n_spec as usize) * SM;
+    let cmb = (n_ch as usize) * CM;
+
+    let mut plan = H + sb + cb + smb + cmb;
+
+    for s in &run.spectra {
+        if let Some(v) = &s.mz_array {
+            plan = a8(plan);
+            plan += v.len() * 8;
+        }
+    }
+    for s in &run.spectra {
+        if let Some(v) = &s.intensity_array {
+            plan = a8(plan);
+            plan += v.len() * 4;
+        }
+    }
+    for c in &run.chromatograms {
+        if let Some(v) = &c.time_array {
+            plan = a8(plan);
+            plan += v.len() * 8;
+        }
+    }
+    for c in &run.chromatograms {
+        if let Some(v) = &c.intensity_array {
+            plan = a8(plan);
+            plan += v.len() * 4;
+        }
+    }
+    for c in &run.chromatograms {
+        let b = c.id.as_bytes();
+        if !b.is_empty() {
+            plan = a8(plan);
+            plan += b.len();
+        }
+    }
+
+    let mut out: Vec<u8> = Vec::with_capacity(plan);
+    unsafe {
+        out.set_len(plan);
+    }
+    let mut cur = H;
+
+    out[0..4].copy_from_slice(b"BIN1");
+    set_u32_at_order(&mut out, 4, n_spec, order);
+    set_u32_at_order(&mut out, 8, n_ch, order);
+    out[12] = if order == ByteOrder::Big { 2 | BE_FLAG } else { 2 };
+    out[13] = 1;
+    out[14] = 2;
+    out[15] = 1;
+
+    let spec_index_off = cur as u64;
+    cur += sb;
+    let chrom_index_off = cur as u64;
+    cur += cb;
+    let spec_meta_off = cur as u64;
+    cur += smb;
+    let chrom_meta_off = cur as u64;
+    cur += cmb;
+    let data_off = cur as u64;
+
+    let mut sx: Vec<(u64, u32)> = Vec::with_capacity(n_spec as usize);
+    for s in &run.spectra {
+        let p = match &s.mz_array {
+            Some(v) if !v.is_empty() => write_f64_array_ordered(&mut out, &mut cur, v, order),
+            _ => (0, 0),
+        };
+        sx.push(p);
+    }
+    let mut sy: Vec<(u64, u32)> = Vec::with_capacity(n_spec as usize);
+    for s in &run.spectra {
+        let p = match &s.intensity_array {
+            Some(v) if !v.is_empty() => write_f32_array_ordered(&mut out, &mut cur, v, order),
+            _ => (0, 0),
+        };
+        sy.push(p);
+    }
+    let mut cx: Vec<(u64, u32)> = Vec::with_capacity(n_ch as usize);
+    for c in &run.chromatograms {
+        let p = match &c.time_array {
+            Some(v) if !v.is_empty() => write_f64_array_ordered(&mut out, &mut cur, v, order),
+            _ => (0, 0),
+        };
+        cx.push(p);
+    }
+    let mut cy: Vec<(u64, u32)> = Vec::with_capacity(n_ch as usize);
+    for c in &run.chromatograms {
+        let p = match &c.intensity_array {
+            Some(v) if !v.is_empty() => write_f32_array_ordered(&mut out, &mut cur, v, order),
+            _ => (0, 0),
+        };
+        cy.push(p);
+    }
+    let mut cid: Vec<(u64, u32)> = Vec::with_capacity(n_ch as usize);
+    for c in &run.chromatograms {
+        let s = c.id.as_bytes();
+        if s.is_empty() {
+            cid.push((0, 0));
+        } else {
+            cur = a8(cur);
+            let off = cur as u64;
+            let len = s.len() as u32;
+            ensure_cap(&mut out, cur + s.len());
+            unsafe {
+                std::ptr::copy_nonoverlapping(s.as_ptr(), out.as_mut_ptr().add(cur), s.len());
+            }
+            cur += s.len();
+            cid.push((off, len));
+        }
+    }
+
+    for i in 0..(n_spec as usize) {
+        let b = spec_index_off as usize + i * SI;
+        let (x_off, x_len) = sx[i];
+        let (y_off, y_len) = sy[i];
+        set_u64_at_order(&mut out, b + 0, x_off, order);
+        set_u32_at_order(&mut out, b + 8, x_len, order);
+        set_u64_at_order(&mut out, b + 12, y_off, order);
+        set_u32_at_order(&mut out, b + 20, y_len, order);
+        set_u64_at_order(&mut out, b + 24, 0, order);
+    }
+    for i in 0..(n_ch as usize) {
+        let b = chrom_index_off as usize + i * CI;
+        let (x_off, x_len) = cx[i];
+        let (y_off, y_len) = cy[i];
+        set_u64_at_order(&mut out, b + 0, x_off, order);
+        set_u32_at_order(&mut out, b + 8, x_len, order);
+        set_u64_at_order(&mut out, b + 12, y_off, order);
+        set_u32_at_order(&mut out, b + 20, y_len, order);
+        set_u64_at_order(&mut out, b + 24, 0, order);
+    }
+
+    for (i, s) in run.spectra.iter().enumerate() {
+        let b = spec_meta_off as usize + i * SM;
+        set_u32_at_order(&mut out, b + 0, s.index as u32, order);
+        set_u32_at_order(&mut out, b + 4, s.array_length as u32, order);
+        out[b + 8] = s.ms_level.unwrap_or(255);
+        out[b + 9] = s.polarity.unwrap_or(255);
+        out[b + 10] = s.spectrum_type.unwrap_or(255);
+        out[b + 11] = 0;
+        set_f64_at_order(&mut out, b + 12, s.retention_time.unwrap_or(-1.0), order);
+        set_f64_at_order(
+            &mut out,
+            b + 20,
+            s.scan_window_lower_limit.unwrap_or(-1.0),
+            order,
+        );
+        set_f64_at_order(
+            &mut out,
+            b + 28,
+            s.scan_window_upper_limit.unwrap_or(-1.0),
+            order,
+        );
+        set_f64_at_order(&mut out, b + 36, s.total_ion_current.unwrap_or(-1.0), order);
+        set_f64_at_order(
+            &mut out,
+            b + 44,
+            s.base_peak_intensity.unwrap_or(-1.0),
+            order,
+        );
+        set_f64_at_order(&mut out, b + 52, s.base_peak_mz.unwrap_or(-1.0), order);
+        let (tgt, low, up, sel) = match &s.precursor {
+            Some(p) => (
+                p.isolation_window_target_mz.unwrap_or(-1.0),
+                p.isolation_window_lower_offset.unwrap_or(-1.0),
+                p.isolation_window_upper_offset.unwrap_or(-1.0),
+                p.selected_ion_mz.unwrap_or(-1.0),
+            ),
+            None => (-1.0, -1.0, -1.0, -1.0),
+        };
+        set_f64_at_order(&mut out, b + 60, tgt, order);
+        set_f64_at_order(&mut out, b + 68, low, order);
+        set_f64_at_order(&mut out, b + 76, up, order);
+        set_f64_at_order(&mut out, b + 84, sel, order);
+    }
+
+    for i in 0..(n_ch as usize) {
+        let b = chrom_meta_off as usize + i * CM;
+        set_u32_at_order(&mut out, b + 0, run.chromatograms[i].index as u32, order);
+        set_u32_at_order(
+            &mut out,
+            b + 4,
+            run.chromatograms[i].array_length as u32,
+            order,
+        );
+        let (off, len) = cid[i];
+        set_u64_at_order(&mut out, b + 8, off, order);
+        set_u32_at_order(&mut out, b + 16, len, order);
+        set_u32_at_order(&mut out, b + 20, 0, order);
+    }
+
+    let total = cur as u64;
+    set_u64_at_order(
+        &mut out,
+        16,
+        if n_spec > 0 { spec_index_off } else { 0 },
+        order,
+    );
+    set_u64_at_order(
+        &mut out,
+        24,
+        if n_ch > 0 { chrom_index_off } else { 0 },
+        order,
+    );
+    set_u64_at_order(
+        &mut out,
+        32,
+        if n_spec > 0 { spec_meta_off } else { 0 },
+        order,
+    );
+    set_u64_at_order(
+        &mut out,
+        40,
+        if n_ch > 0 { chrom_meta_off } else { 0 },
+        order,
+    );
+    set_u64_at_order(&mut out, 48, data_off, order);
+    set_u64_at_order(&mut out, 56, total, order);
+
+    out.truncate(cur);
+    out
+}
+
+/// Like [`encode`], but writes `mz_array` through the lossless predictive
+/// codec in [`crate::utilities::parse::predictive`] (format id `6`) instead
+/// of raw `f64`, trading the ascending-m/z axis's tiny second differences
+/// for a much smaller index/data footprint while staying bit-exact.
+pub fn encode_predictive(mzml: &MzML) -> Vec<u8> {
+    const H: usize = 64;
+    const SI: usize = 32;
+    const CI: usize = 32;
+    const SM: usize = 104;
+    const CM: usize = 24;
+
+    #[inline]
+    fn a8(x: usize) -> usize {
+        (x + 7) & !7
+    }
+
+    let run = match mzml.run.as_ref() {
+        Some(r) => r,
+        None => {
+            let mut out = vec![0u8; H];
+            out[0..4].copy_from_slice(b"BIN1");
+            set_u32_at(&mut out, 4, 0);
+            set_u32_at(&mut out, 8, 0);
+            out[12] = 2;
+            out[13] = 1;
+            out[14] = 6;
+            out[15] = 1;
+            set_u64_at(&mut out, 56, H as u64);
+            return out;
+        }
+    };
+
+    let n_spec = run.spectra.len() as u32;
+    let n_ch = run.chromatograms.len() as u32;
+
+    let sb = (n_spec as usize) * SI;
+    let cb = (n_ch as usize) * CI;
+    let smb = (n_spec as usize) * SM;
+    let cmb = (n_ch as usize) * CM;
+
+    let mut plan = H + sb + cb + smb + cmb;
+
+    let mz_bytes: Vec<Option<Vec<u8>>> = run
+        .spectra
+        .iter()
+        .map(|s| {
+            s.mz_array
+                .as_ref()
+                .filter(|v| !v.is_empty())
+                .map(|v| encode_pred(v))
+        })
+        .collect();
+    for b in mz_bytes.iter().flatten() {
+        plan = a8(plan);
+        plan += b.len();
+    }
+    for s in &run.spectra {
+        if let Some(v) = &s.intensity_array {
+            plan = a8(plan);
+            plan += v.len() * 4;
+        }
+    }
+    for c in &run.chromatograms {
+        if let Some(v) = &c.time_array {
+            plan = a8(plan);
+            plan += v.len() * 8;
+        }
+    }
+    for c in &run.chromatograms {
+        if let Some(v) = &c.intensity_array {
+            plan = a8(plan);
+            plan += v.len() * 4;
+        }
+    }
+    for c in &run.chromatograms {
+        let b = c.id.as_bytes();
+        if !b.is_empty() {
+            plan = a8(plan);
+            plan += b.len();
+        }
+    }
+
+    let mut out: Vec<u8> = Vec::with_capacity(plan);
+    unsafe {
+        out.set_len(plan);
+    }
+    let mut cur = H;
+
+    out[0..4].copy_from_slice(b"BIN1");
+    set_u32_at(&mut out, 4, n_spec);
+    set_u32_at(&mut out, 8, n_ch);
+    out[12] = 2;
+    out[13] = 1;
+    out[14] = 6;
+    out[15] = 1;
+
+    let spec_index_off = cur as u64;
+    cur += sb;
+    let chrom_index_off = cur as u64;
+    cur += cb;
+    let spec_meta_off = cur as u64;
+    cur += smb;
+    let chrom_meta_off = cur as u64;
+    cur += cmb;
+    let data_off = cur as u64;
+
+    let mut sx: Vec<(u64, u32)> = Vec::with_capacity(n_spec as usize);
+    for (i, s) in run.spectra.iter().enumerate() {
+        let p = match (&s.mz_array, &mz_bytes[i]) {
+            (Some(v), Some(bytes)) if !v.is_empty() => {
+                cur = a8(cur);
+                let off = cur as u64;
+                ensure_cap(&mut out, cur + bytes.len());
+                out[cur..cur + bytes.len()].copy_from_slice(bytes);
+                cur += bytes.len();
+                (off, v.len() as u32)
+            }
+            _ => (0, 0),
+        };
+        sx.push(p);
+    }
+    let mut sy: Vec<(u64, u32)> = Vec::with_capacity(n_spec as usize);
+    for s in &run.spectra {
+        let p = match &s.intensity_array {
+            Some(v) if !v.is_empty() => unsafe { write_f32_le(&mut out, &mut cur, v) },
+            _ => (0, 0),
+        };
+        sy.push(p);
+    }
+    let mut cx: Vec<(u64, u32)> = Vec::with_capacity(n_ch as usize);
+    for c in &run.chromatograms {
+        let p = match &c.time_array {
+            Some(v) if !v.is_empty() => unsafe { write_f64_le(&mut out, &mut cur, v) },
+            _ => (0, 0),
+        };
+        cx.push(p);
+    }
+    let mut cy: Vec<(u64, u32)> = Vec::with_capacity(n_ch as usize);
+    for c in &run.chromatograms {
+        let p = match &c.intensity_array {
+            Some(v) if !v.is_empty() => unsafe { write_f32_le(&mut out, &mut cur, v) },
+            _ => (0, 0),
+        };
+        cy.push(p);
+    }
+    let mut cid: Vec<(u64, u32)> = Vec::with_capacity(n_ch as usize);
+    for c in &run.chromatograms {
+        let s = c.id.as_bytes();
+        if s.is_empty() {
+            cid.push((0, 0));
+        } else {
+            cur = a8(cur);
+            let off = cur as u64;
+            let len = s.len() as u32;
+            ensure_cap(&mut out, cur + s.len());
+            unsafe {
+                std::ptr::copy_nonoverlapping(s.as_ptr(), out.as_mut_ptr().add(cur), s.len());
+            }
+            cur += s.len();
+            cid.push((off, len));
+        }
+    }
+
+    for i in 0..(n_spec as usize) {
+        let b = spec_index_off as usize + i * SI;
+        let (x_off, x_len) = sx[i];
+        let (y_off, y_len) = sy[i];
+        set_u64_at(&mut out, b + 0, x_off);
+        set_u32_at(&mut out, b + 8, x_len);
+        set_u64_at(&mut out, b + 12, y_off);
+        set_u32_at(&mut out, b + 20, y_len);
+        set_u64_at(&mut out, b + 24, 0);
+    }
+    for i in 0..(n_ch as usize) {
+        let b = chrom_index_off as usize + i * CI;
+        let (x_off, x_len) = cx[i];
+        let (y_off, y_len) = cy[i];
+        set_u64_at(&mut out, b + 0, x_off);
+        set_u32_at(&mut out, b + 8, x_len);
+        set_u64_at(&mut out, b + 12, y_off);
+        set_u32_at(&mut out, b + 20, y_len);
+        set_u64_at(&mut out, b + 24, 0);
+    }
+
+    for (i, s) in run.spectra.iter().enumerate() {
+        let b = spec_meta_off as usize + i * SM;
+        set_u32_at(&mut out, b + 0, s.index as u32);
+        set_u32_at(&mut out, b + 4, s.array_length as u32);
+        out[b + 8] = s.ms_level.unwrap_or(255);
+        out[b + 9] = s.polarity.unwrap_or(255);
+        out[b + 10] = s.spectrum_type.unwrap_or(255);
+        out[b + 11] = 0;
+        set_f64_at(&mut out, b + 12, s.retention_time.unwrap_or(-1.0));
+        set_f64_at(&mut out, b + 20, s.scan_window_lower_limit.unwrap_or(-1.0));
+        set_f64_at(&mut out, b + 28, s.scan_window_upper_limit.unwrap_or(-1.0));
+        set_f64_at(&mut out, b + 36, s.total_ion_current.unwrap_or(-1.0));
+        set_f64_at(&mut out, b + 44, s.base_peak_intensity.unwrap_or(-1.0));
+        set_f64_at(&mut out, b + 52, s.base_peak_mz.unwrap_or(-1.0));
+        let (tgt, low, up, sel) = match &s.precursor {
+            Some(p) => (
+                p.isolation_window_target_mz.unwrap_or(-1.0),
+                p.isolation_window_lower_offset.unwrap_or(-1.0),
+                p.isolation_window_upper_offset.unwrap_or(-1.0),
+                p.selected_ion_mz.unwrap_or(-1.0),
+            ),
+            None => (-1.0, -1.0, -1.0, -1.0),
+        };
+        set_f64_at(&mut out, b + 60, tgt);
+        set_f64_at(&mut out, b + 68, low);
+        set_f64_at(&mut out, b + 76, up);
+        set_f64_at(&mut out, b + 84, sel);
+    }
+
+    for i in 0..(n_ch as usize) {
+        let b = chrom_meta_off as usize + i * CM;
+        set_u32_at(&mut out, b + 0, run.chromatograms[i].index as u32);
+        set_u32_at(&mut out, b + 4, run.chromatograms[i].array_length as u32);
+        let (off, len) = cid[i];
+        set_u64_at(&mut out, b + 8, off);
+        set_u32_at(&mut out, b + 16, len);
+        set_u32_at(&mut out, b + 20, 0);
+    }
+
+    let total = cur as u64;
+    set_u64_at(&mut out, 16, if n_spec > 0 { spec_index_off } else { 0 });
+    set_u64_at(&mut out, 24, if n_ch > 0 { chrom_index_off } else { 0 });
+    set_u64_at(&mut out, 32, if n_spec > 0 { spec_meta_off } else { 0 });
+    set_u64_at(&mut out, 40, if n_ch > 0 { chrom_meta_off } else { 0 });
+    set_u64_at(&mut out, 48, data_off);
+    set_u64_at(&mut out, 56, total);
+
+    out.truncate(cur);
+    out
+}
@@ -20,19 +20,26 @@ pub fn ensure_cap(out: &mut Vec<u8>, need: usize) {
         out.resize(cap, 0);
     }
 }
+// These four used to `ptr::copy_nonoverlapping` the float slices' raw bytes
+// directly into the output buffer, which only produces little-endian BIN1
+// output because every target this crate actually builds for (x86, ARM,
+// wasm32) happens to be little-endian host-side - on a big-endian host it
+// would silently write native-endian bytes into a format every reader
+// assumes is little-endian. Writing each element through `to_le_bytes`
+// instead costs nothing on the hosts that matter and removes that
+// correctness trap entirely. Kept `unsafe fn` so existing call sites don't
+// need to change.
 #[inline]
 pub unsafe fn write_f64_le(out: &mut Vec<u8>, pos: &mut usize, vals: &[f64]) -> (u64, u32) {
     *pos = (*pos + 7) & !7;
     let off = *pos as u64;
     let nbytes = vals.len() * 8;
     ensure_cap(out, *pos + nbytes);
-    unsafe {
-        std::ptr::copy_nonoverlapping(
-            vals.as_ptr() as *const u8,
-            out.as_mut_ptr().add(*pos),
-            nbytes,
-        )
-    };
+    let mut p = *pos;
+    for &v in vals {
+        out[p..p + 8].copy_from_slice(&v.to_le_bytes());
+        p += 8;
+    }
     *pos += nbytes;
     (off, vals.len() as u32)
 }
@@ -42,36 +49,170 @@ pub unsafe fn write_f32_le(out: &mut Vec<u8>, pos: &mut usize, vals: &[f32]) ->
     let off = *pos as u64;
     let nbytes = vals.len() * 4;
     ensure_cap(out, *pos + nbytes);
-    unsafe {
-        std::ptr::copy_nonoverlapping(
-            vals.as_ptr() as *const u8,
-            out.as_mut_ptr().add(*pos),
-            nbytes,
-        )
-    };
+    let mut p = *pos;
+    for &v in vals {
+        out[p..p + 4].copy_from_slice(&v.to_le_bytes());
+        p += 4;
+    }
     *pos += nbytes;
     (off, vals.len() as u32)
 }
 #[inline]
 pub unsafe fn write_f64_at(buf: &mut [u8], off: usize, vals: &[f64]) {
-    unsafe {
-        std::ptr::copy_nonoverlapping(
-            vals.as_ptr() as *const u8,
-            buf.as_mut_ptr().add(off),
-            vals.len() * 8,
-        )
-    };
+    let mut p = off;
+    for &v in vals {
+        buf[p..p + 8].copy_from_slice(&v.to_le_bytes());
+        p += 8;
+    }
 }
 #[inline]
 pub unsafe fn write_f32_at(buf: &mut [u8], off: usize, vals: &[f32]) {
-    unsafe {
-        std::ptr::copy_nonoverlapping(
-            vals.as_ptr() as *const u8,
-            buf.as_mut_ptr().add(off),
-            vals.len() * 4,
-        )
+    let mut p = off;
+    for &v in vals {
+        buf[p..p + 4].copy_from_slice(&v.to_le_bytes());
+        p += 4;
+    }
+}
+/// Byte order for a whole BIN1/BINS/BINQ buffer. Recorded as the top bit of
+/// the chrom-time format byte at header offset 12 (`BE_FLAG`): every format
+/// id used there stays well under `0x80`, so the bit is free, and reading it
+/// needs no endian awareness since it's a single byte. Only the raw `f32`/
+/// `f64` array formats (`1`/`2`) and the header/index/meta numeric fields
+/// honor this - the numpress and block-quantization codecs (`3`/`4`/`5`)
+/// keep their self-describing blocks little-endian regardless, since they
+/// were never meant to be read byte-for-byte by another host anyway.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ByteOrder {
+    Little,
+    Big,
+}
+
+pub const BE_FLAG: u8 = 0x80;
+
+macro_rules! endian_rw {
+    ($ty:ty, $len:expr, $set_le:ident, $set_be:ident, $rd_le:ident, $rd_be:ident) => {
+        #[inline]
+        pub fn $set_le(buf: &mut [u8], pos: usize, val: $ty) {
+            buf[pos..pos + $len].copy_from_slice(&val.to_le_bytes());
+        }
+        #[inline]
+        pub fn $set_be(buf: &mut [u8], pos: usize, val: $ty) {
+            buf[pos..pos + $len].copy_from_slice(&val.to_be_bytes());
+        }
+        #[inline]
+        pub fn $rd_le(b: &[u8], p: usize) -> Result<$ty, String> {
+            if p + $len > b.len() {
+                return Err(concat!(stringify!($ty), " OOB").into());
+            }
+            Ok(<$ty>::from_le_bytes(b[p..p + $len].try_into().unwrap()))
+        }
+        #[inline]
+        pub fn $rd_be(b: &[u8], p: usize) -> Result<$ty, String> {
+            if p + $len > b.len() {
+                return Err(concat!(stringify!($ty), " OOB").into());
+            }
+            Ok(<$ty>::from_be_bytes(b[p..p + $len].try_into().unwrap()))
+        }
     };
 }
+
+endian_rw!(u32, 4, set_u32_le_at, set_u32_be_at, rd_u32_le, rd_u32_be);
+endian_rw!(u64, 8, set_u64_le_at, set_u64_be_at, rd_u64_le, rd_u64_be);
+endian_rw!(f64, 8, set_f64_le_at, set_f64_be_at, rd_f64_le, rd_f64_be);
+
+#[inline]
+pub fn set_u32_at_order(buf: &mut [u8], pos: usize, val: u32, order: ByteOrder) {
+    match order {
+        ByteOrder::Little => set_u32_le_at(buf, pos, val),
+        ByteOrder::Big => set_u32_be_at(buf, pos, val),
+    }
+}
+#[inline]
+pub fn set_u64_at_order(buf: &mut [u8], pos: usize, val: u64, order: ByteOrder) {
+    match order {
+        ByteOrder::Little => set_u64_le_at(buf, pos, val),
+        ByteOrder::Big => set_u64_be_at(buf, pos, val),
+    }
+}
+#[inline]
+pub fn set_f64_at_order(buf: &mut [u8], pos: usize, val: f64, order: ByteOrder) {
+    match order {
+        ByteOrder::Little => set_f64_le_at(buf, pos, val),
+        ByteOrder::Big => set_f64_be_at(buf, pos, val),
+    }
+}
+#[inline]
+pub fn rd_u32_order(b: &[u8], p: usize, order: ByteOrder) -> Result<u32, String> {
+    match order {
+        ByteOrder::Little => rd_u32_le(b, p),
+        ByteOrder::Big => rd_u32_be(b, p),
+    }
+}
+#[inline]
+pub fn rd_u64_order(b: &[u8], p: usize, order: ByteOrder) -> Result<u64, String> {
+    match order {
+        ByteOrder::Little => rd_u64_le(b, p),
+        ByteOrder::Big => rd_u64_be(b, p),
+    }
+}
+#[inline]
+pub fn rd_f64_order(b: &[u8], p: usize, order: ByteOrder) -> Result<f64, String> {
+    match order {
+        ByteOrder::Little => rd_f64_le(b, p),
+        ByteOrder::Big => rd_f64_be(b, p),
+    }
+}
+
+/// Append an array of `f64`/`f32` samples honoring `order`, 8-byte aligned,
+/// returning `(offset, element count)`. [`ByteOrder::Little`] goes through
+/// [`write_f64_le`]/[`write_f32_le`].
+#[inline]
+pub fn write_f64_array_ordered(
+    out: &mut Vec<u8>,
+    pos: &mut usize,
+    vals: &[f64],
+    order: ByteOrder,
+) -> (u64, u32) {
+    match order {
+        ByteOrder::Little => unsafe { write_f64_le(out, pos, vals) },
+        ByteOrder::Big => {
+            *pos = (*pos + 7) & !7;
+            let off = *pos as u64;
+            ensure_cap(out, *pos + vals.len() * 8);
+            let mut p = *pos;
+            for &v in vals {
+                out[p..p + 8].copy_from_slice(&v.to_be_bytes());
+                p += 8;
+            }
+            *pos = p;
+            (off, vals.len() as u32)
+        }
+    }
+}
+#[inline]
+pub fn write_f32_array_ordered(
+    out: &mut Vec<u8>,
+    pos: &mut usize,
+    vals: &[f32],
+    order: ByteOrder,
+) -> (u64, u32) {
+    match order {
+        ByteOrder::Little => unsafe { write_f32_le(out, pos, vals) },
+        ByteOrder::Big => {
+            *pos = (*pos + 7) & !7;
+            let off = *pos as u64;
+            ensure_cap(out, *pos + vals.len() * 4);
+            let mut p = *pos;
+            for &v in vals {
+                out[p..p + 4].copy_from_slice(&v.to_be_bytes());
+                p += 4;
+            }
+            *pos = p;
+            (off, vals.len() as u32)
+        }
+    }
+}
+
 #[inline]
 pub fn rd_u32(b: &[u8], p: usize) -> Result<u32, String> {
     if p + 4 > b.len() {
@@ -99,6 +240,7 @@ pub fn read_array_as_f64(
     off: u64,
     len: u32,
     fmt: u8,
+    order: ByteOrder,
 ) -> Result<Option<Vec<f64>>, String> {
     if off == 0 || len == 0 {
         return Ok(None);
@@ -114,7 +256,7 @@ pub fn read_array_as_f64(
             let mut out = Vec::with_capacity(n);
             let mut p = off;
             for _ in 0..n {
-                out.push(f64::from_le_bytes(buf[p..p + 8].try_into().unwrap()));
+                out.push(rd_f64_order(buf, p, order)?);
                 p += 8;
             }
             Ok(Some(out))
@@ -127,12 +269,22 @@ pub fn read_array_as_f64(
             let mut out = Vec::with_capacity(n);
             let mut p = off;
             for _ in 0..n {
-                let v = f32::from_le_bytes(buf[p..p + 4].try_into().unwrap());
+                let bytes: [u8; 4] = buf[p..p + 4].try_into().unwrap();
+                let v = match order {
+                    ByteOrder::Little => f32::from_le_bytes(bytes),
+                    ByteOrder::Big => f32::from_be_bytes(bytes),
+                };
                 out.push(v as f64);
                 p += 4;
             }
             Ok(Some(out))
         }
+        // The numpress and block-quantization codecs embed their own
+        // little-endian header/payload and are unaffected by `order`; see
+        // the `ByteOrder` doc comment above.
+        3 => crate::utilities::parse::numpress::decode_linear(buf, off).map(Some),
+        5 => crate::utilities::parse::quantize::decode_quant(buf, off).map(Some),
+        6 => crate::utilities::parse::predictive::decode_pred(buf, off).map(Some),
         _ => Err("unknown fmt".into()),
     }
 }
@@ -142,6 +294,7 @@ pub fn read_array_as_f32(
     off: u64,
     len: u32,
     fmt: u8,
+    order: ByteOrder,
 ) -> Result<Option<Vec<f32>>, String> {
     if off == 0 || len == 0 {
         return Ok(None);
@@ -168,26 +321,30 @@ pub fn read_array_as_f32(
                 return Err("f32 array OOB".into());
             }
             #[cfg(target_endian = "little")]
-            unsafe {
-                let mut out: Vec<f32> = Vec::with_capacity(n);
-                out.set_len(n);
-                std::ptr::copy_nonoverlapping(
-                    buf.as_ptr().add(off),
-                    out.as_mut_ptr() as *mut u8,
-                    need,
-                );
-                return Ok(Some(out));
-            }
-            #[cfg(not(target_endian = "little"))]
-            {
-                let mut out = Vec::with_capacity(n);
-                let mut p = off;
-                for _ in 0..n {
-                    out.push(f32::from_le_bytes(buf[p..p + 4].try_into().unwrap()));
-                    p += 4;
+            if order == ByteOrder::Little {
+                unsafe {
+                    let mut out: Vec<f32> = Vec::with_capacity(n);
+                    out.set_len(n);
+                    std::ptr::copy_nonoverlapping(
+                        buf.as_ptr().add(off),
+                        out.as_mut_ptr() as *mut u8,
+                        need,
+                    );
+                    return Ok(Some(out));
                 }
-                Ok(Some(out))
             }
+            let mut out = Vec::with_capacity(n);
+            let mut p = off;
+            for _ in 0..n {
+                let bytes: [u8; 4] = buf[p..p + 4].try_into().unwrap();
+                let v = match order {
+                    ByteOrder::Little => f32::from_le_bytes(bytes),
+                    ByteOrder::Big => f32::from_be_bytes(bytes),
+                };
+                out.push(v);
+                p += 4;
+            }
+            Ok(Some(out))
         }
         2 => {
             let need = n.checked_mul(8).ok_or("len overflow")?;
@@ -197,12 +354,148 @@ pub fn read_array_as_f32(
             let mut out = Vec::with_capacity(n);
             let mut p = off;
             for _ in 0..n {
-                let v = f64::from_le_bytes(buf[p..p + 8].try_into().unwrap());
+                let v = rd_f64_order(buf, p, order)?;
                 out.push(f64_to_f32_lossy(v));
                 p += 8;
             }
             Ok(Some(out))
         }
+        4 => crate::utilities::parse::numpress::decode_slof(buf, off).map(Some),
+        5 => crate::utilities::parse::quantize::decode_quant(buf, off)
+            .map(|v| Some(v.into_iter().map(|x| x as f32).collect())),
         _ => Err("unknown fmt".into()),
     }
 }
+
+/// Why a [`BinReader`] read failed: always a truncated/undersized buffer,
+/// never a malformed value, since every primitive it reads is a fixed-width
+/// little-endian field.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodeError {
+    pub offset: usize,
+    pub message: String,
+}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at offset {}", self.message, self.offset)
+    }
+}
+
+impl From<DecodeError> for String {
+    fn from(e: DecodeError) -> String {
+        e.to_string()
+    }
+}
+
+/// Bounds-checked little-endian cursor over a BIN1/BINS/BINQ buffer. Unlike
+/// the bare `rd_u32`/`rd_u64`/`rd_f64` functions above, it tracks its own
+/// position so sequential header fields don't each need their own offset
+/// arithmetic, and every read reports the offset it failed at instead of a
+/// bare "OOB" string - useful at the point a caller first touches a buffer
+/// of unknown provenance (a truncated upload, a mismatched build), before
+/// any offset pulled from it can be trusted.
+pub struct BinReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BinReader<'a> {
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], DecodeError> {
+        if self.pos + n > self.buf.len() {
+            return Err(DecodeError {
+                offset: self.pos,
+                message: "not enough data".to_string(),
+            });
+        }
+        let s = &self.buf[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(s)
+    }
+
+    pub fn read_bytes(&mut self, n: usize) -> Result<&'a [u8], DecodeError> {
+        self.take(n)
+    }
+
+    pub fn read_u32_le(&mut self) -> Result<u32, DecodeError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub fn read_u64_le(&mut self) -> Result<u64, DecodeError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    pub fn read_f32_le(&mut self) -> Result<f32, DecodeError> {
+        Ok(f32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub fn read_f64_le(&mut self) -> Result<f64, DecodeError> {
+        Ok(f64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+}
+
+/// Lets [`read_fields!`] read any of the handful of numeric types a BIN1/BINS
+/// meta record is made of through one call, dispatching on `order` the same
+/// way [`rd_u32_order`]/[`rd_u64_order`]/[`rd_f64_order`] already do.
+pub trait ReadField: Sized {
+    fn read_field(buf: &[u8], pos: usize, order: ByteOrder) -> Result<Self, String>;
+}
+
+impl ReadField for u32 {
+    #[inline]
+    fn read_field(buf: &[u8], pos: usize, order: ByteOrder) -> Result<Self, String> {
+        rd_u32_order(buf, pos, order)
+    }
+}
+impl ReadField for u64 {
+    #[inline]
+    fn read_field(buf: &[u8], pos: usize, order: ByteOrder) -> Result<Self, String> {
+        rd_u64_order(buf, pos, order)
+    }
+}
+impl ReadField for f64 {
+    #[inline]
+    fn read_field(buf: &[u8], pos: usize, order: ByteOrder) -> Result<Self, String> {
+        rd_f64_order(buf, pos, order)
+    }
+}
+impl ReadField for u8 {
+    #[inline]
+    fn read_field(buf: &[u8], pos: usize, _order: ByteOrder) -> Result<Self, String> {
+        buf.get(pos).copied().ok_or_else(|| "u8 OOB".to_string())
+    }
+}
+
+/// Declares a fixed-layout record's fields as `name: ty @ offset` once and
+/// expands each into a bounds-checked, `order`-aware read (`$buf[$off..]`
+/// via [`ReadField`]) bound to a `let $name`, instead of a hand-written chain
+/// of `rd_u32(bin, b + 8)`/`rd_f64(bin, b + 12)` calls that can drift out of
+/// sync with the writer one offset at a time. Must be invoked where `?` can
+/// propagate a `Result<_, String>`.
+///
+/// ```ignore
+/// read_fields!(bin, b, order, {
+///     index: u32 @ 0,
+///     array_length: u32 @ 4,
+/// });
+/// ```
+macro_rules! read_fields {
+    ($buf:expr, $base:expr, $order:expr, { $($name:ident : $ty:ty @ $off:expr),* $(,)? }) => {
+        $(
+            let $name: $ty = $crate::utilities::parse::helper::ReadField::read_field(
+                $buf,
+                $base + $off,
+                $order,
+            )?;
+        )*
+    };
+}
+pub(crate) use read_fields;
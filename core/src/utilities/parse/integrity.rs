@@ -0,0 +1,66 @@
+// Content-integrity trailer for BIN1/BINS/BINQ buffers: a 32-byte BLAKE3-256
+// digest over `[0, total)` (the header, index tables, meta tables, and array
+// data written by `encode`/`encode_arrays`/`encode_quantized`), appended
+// after a 4-byte magic. BLAKE3's portable implementation falls back cleanly
+// where SIMD isn't available and otherwise hashes at memory-bandwidth speed,
+// so sealing even a large run costs little beyond the write itself.
+//
+// The 64-byte header has no field set aside to flag a trailer's presence,
+// and adding one would change the fixed layout every existing reader already
+// assumes (bytes 0-11 are the magic/counts, 12-15 are the per-column format
+// bytes, 16-63 are table offsets and `total`). Presence is instead detected
+// the same way `total` already delimits the data region: a sealed buffer is
+// exactly `total + TRAILER_LEN` bytes with the trailer magic sitting right
+// at `total`. Anything shorter is simply unverified, so every BIN1/BINS/BINQ
+// blob produced before this trailer existed - and every reader of one -
+// stays valid.
+
+use crate::utilities::parse::helper::rd_u64;
+
+const TRAILER_MAGIC: &[u8; 4] = b"B3H1";
+const DIGEST_LEN: usize = 32;
+const TRAILER_LEN: usize = 4 + DIGEST_LEN;
+
+/// Why [`verify`] refused to trust a buffer's offsets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IntegrityError {
+    /// No trailer is present (short header, size mismatch, or bad magic) -
+    /// the buffer was never sealed with [`seal`], not necessarily corrupt.
+    Unverified,
+    /// A trailer is present but its digest does not match the data.
+    Mismatch,
+}
+
+/// Append a BLAKE3 integrity trailer covering `[0, total)` to a buffer
+/// produced by `encode`, `encode_arrays`, or `encode_quantized`.
+pub fn seal(mut bin: Vec<u8>) -> Vec<u8> {
+    let total = (rd_u64(&bin, 56).unwrap_or(bin.len() as u64) as usize).min(bin.len());
+    let digest = blake3::hash(&bin[..total]);
+    bin.truncate(total);
+    bin.extend_from_slice(TRAILER_MAGIC);
+    bin.extend_from_slice(digest.as_bytes());
+    bin
+}
+
+/// Recompute the BLAKE3 digest over `[0, total)` and compare it against the
+/// trailer appended by [`seal`]. Call this - and require `Ok(())` - before
+/// trusting any offset read from the index tables.
+pub fn verify(bin: &[u8]) -> Result<(), IntegrityError> {
+    if bin.len() < 64 {
+        return Err(IntegrityError::Unverified);
+    }
+    let total = rd_u64(bin, 56).map_err(|_| IntegrityError::Unverified)? as usize;
+    if total > bin.len() || bin.len() != total + TRAILER_LEN {
+        return Err(IntegrityError::Unverified);
+    }
+    if &bin[total..total + 4] != TRAILER_MAGIC {
+        return Err(IntegrityError::Unverified);
+    }
+    let expected = &bin[total + 4..total + TRAILER_LEN];
+    let actual = blake3::hash(&bin[..total]);
+    if actual.as_bytes().as_slice() == expected {
+        Ok(())
+    } else {
+        Err(IntegrityError::Mismatch)
+    }
+}
@@ -0,0 +1,167 @@
+use crate::utilities::parse::helper::{
+    BE_FLAG, BinReader, ByteOrder, rd_u32_order, rd_u64_order, read_array_as_f32,
+    read_array_as_f64,
+};
+use crate::utilities::structs::DataXY;
+
+/// A parsed but unmaterialized view over a BIN1/BINS/BINQ buffer.
+///
+/// [`super::decode::decode`] reads the 64-byte header and `sidx`/`cidx`
+/// index tables, then eagerly calls `read_array_as_f64`/`read_array_as_f32`
+/// for every spectrum and chromatogram, which is wasted work when a caller
+/// only needs a handful of arrays out of a multi-gigabyte file. `LazyMzML`
+/// borrows the buffer, parses the same header and index tables up front
+/// (a few dozen bytes per entry), and leaves every `mz_array`/
+/// `intensity_array`/`time_array` decode to the accessor below, run only
+/// when called.
+pub struct LazyMzML<'a> {
+    bin: &'a [u8],
+    order: ByteOrder,
+    sx: u8,
+    sy: u8,
+    cx: u8,
+    cy: u8,
+    sidx: Vec<(u64, u32, u64, u32)>,
+    cidx: Vec<(u64, u32, u64, u32)>,
+    chrom_ids: Vec<String>,
+}
+
+impl<'a> LazyMzML<'a> {
+    pub fn open(bin: &'a [u8]) -> Result<Self, String> {
+        let mut header = BinReader::new(bin);
+        let magic_bytes = header.read_bytes(4).map_err(String::from)?;
+        if magic_bytes != b"BIN1" && magic_bytes != b"BINS" && magic_bytes != b"BINQ" {
+            return Err("bad magic".into());
+        }
+        if bin.len() < 64 {
+            return Err("short header".into());
+        }
+        let magic = &bin[0..4];
+
+        let order = if bin[12] & BE_FLAG != 0 {
+            ByteOrder::Big
+        } else {
+            ByteOrder::Little
+        };
+        let rd_u32 = |b: &[u8], p: usize| rd_u32_order(b, p, order);
+        let rd_u64 = |b: &[u8], p: usize| rd_u64_order(b, p, order);
+
+        let n_spec = rd_u32(bin, 4)? as usize;
+        let n_ch = rd_u32(bin, 8)? as usize;
+        let cx = bin[12] & !BE_FLAG;
+        let cy = bin[13];
+        let sx = bin[14];
+        let sy = bin[15];
+
+        let s_idx_off = rd_u64(bin, 16)? as usize;
+        let c_idx_off = rd_u64(bin, 24)? as usize;
+        let c_meta_off = rd_u64(bin, 40)? as usize;
+
+        let s_idx_len = n_spec.checked_mul(32).ok_or("spec index ovf")?;
+        if s_idx_off + s_idx_len > bin.len() {
+            return Err("spec index OOB".into());
+        }
+        let mut sidx: Vec<(u64, u32, u64, u32)> = Vec::with_capacity(n_spec);
+        for i in 0..n_spec {
+            let b = s_idx_off + i * 32;
+            sidx.push((
+                rd_u64(bin, b)?,
+                rd_u32(bin, b + 8)?,
+                rd_u64(bin, b + 12)?,
+                rd_u32(bin, b + 20)?,
+            ));
+        }
+
+        let c_idx_len = n_ch.checked_mul(32).ok_or("chrom index ovf")?;
+        if c_idx_off + c_idx_len > bin.len() {
+            return Err("chrom index OOB".into());
+        }
+        let mut cidx: Vec<(u64, u32, u64, u32)> = Vec::with_capacity(n_ch);
+        for i in 0..n_ch {
+            let b = c_idx_off + i * 32;
+            cidx.push((
+                rd_u64(bin, b)?,
+                rd_u32(bin, b + 8)?,
+                rd_u64(bin, b + 12)?,
+                rd_u32(bin, b + 20)?,
+            ));
+        }
+
+        // Chromatogram ids live in the BIN1/BINQ-only 24-byte meta block; the
+        // string pool offset/len sit at the same bytes 8/16 `decode` reads.
+        // BINS carries no metadata at all, so ids stay empty, matching
+        // `decode`'s `ChromatogramSummary::id = String::new()` for BINS.
+        let mut chrom_ids: Vec<String> = vec![String::new(); n_ch];
+        if magic == b"BIN1" || magic == b"BINQ" {
+            let c_meta_len = n_ch.checked_mul(24).ok_or("chrom meta ovf")?;
+            if c_meta_off + c_meta_len > bin.len() {
+                return Err("chrom meta OOB".into());
+            }
+            for (i, slot) in chrom_ids.iter_mut().enumerate() {
+                let b = c_meta_off + i * 24;
+                let off = rd_u64(bin, b + 8)? as usize;
+                let len = rd_u32(bin, b + 16)? as usize;
+                if off == 0 || len == 0 {
+                    continue;
+                }
+                if off + len > bin.len() {
+                    return Err("chrom id OOB".into());
+                }
+                *slot = std::str::from_utf8(&bin[off..off + len])
+                    .unwrap_or_default()
+                    .to_owned();
+            }
+        }
+
+        Ok(Self {
+            bin,
+            order,
+            sx,
+            sy,
+            cx,
+            cy,
+            sidx,
+            cidx,
+            chrom_ids,
+        })
+    }
+
+    pub fn spectrum_count(&self) -> usize {
+        self.sidx.len()
+    }
+
+    pub fn chromatogram_count(&self) -> usize {
+        self.cidx.len()
+    }
+
+    pub fn chromatogram_id(&self, i: usize) -> Option<&str> {
+        self.chrom_ids.get(i).map(|s| s.as_str())
+    }
+
+    pub fn spectrum_mz(&self, i: usize) -> Result<Vec<f64>, String> {
+        let (x_off, x_len, _, _) = *self.sidx.get(i).ok_or("spectrum index OOB")?;
+        Ok(read_array_as_f64(self.bin, x_off, x_len, self.sx, self.order)?.unwrap_or_default())
+    }
+
+    pub fn spectrum_intensity(&self, i: usize) -> Result<Vec<f32>, String> {
+        let (_, _, y_off, y_len) = *self.sidx.get(i).ok_or("spectrum index OOB")?;
+        Ok(read_array_as_f32(self.bin, y_off, y_len, self.sy, self.order)?.unwrap_or_default())
+    }
+
+    pub fn chromatogram_time(&self, i: usize) -> Result<Vec<f64>, String> {
+        let (x_off, x_len, _, _) = *self.cidx.get(i).ok_or("chromatogram index OOB")?;
+        Ok(read_array_as_f64(self.bin, x_off, x_len, self.cx, self.order)?.unwrap_or_default())
+    }
+
+    pub fn chromatogram_intensity(&self, i: usize) -> Result<Vec<f32>, String> {
+        let (_, _, y_off, y_len) = *self.cidx.get(i).ok_or("chromatogram index OOB")?;
+        Ok(read_array_as_f32(self.bin, y_off, y_len, self.cy, self.order)?.unwrap_or_default())
+    }
+
+    pub fn chromatogram_xy(&self, i: usize) -> Result<DataXY, String> {
+        Ok(DataXY {
+            x: self.chromatogram_time(i)?,
+            y: self.chromatogram_intensity(i)?,
+        })
+    }
+}
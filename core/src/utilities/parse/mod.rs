@@ -0,0 +1,39 @@
+pub mod bin_to_json;
+
+pub mod crc32c;
+
+pub mod cv_terms;
+pub use cv_terms::CvTerm;
+
+pub mod decode;
+pub use decode::{DecodeOptions, decode, decode_with_options};
+
+pub mod disjoint_buf;
+
+pub mod encode;
+pub use encode::{ArrayDtype, EncodeOptions, encode, encode_with_options};
+
+pub mod helper;
+
+pub mod integrity;
+pub use integrity::{IntegrityError, seal, verify};
+
+pub mod lazy;
+pub use lazy::LazyMzML;
+
+pub mod ms_numpress;
+
+pub mod mzml_to_json;
+
+pub mod numpress;
+
+pub mod parse_mzml;
+
+pub mod predictive;
+
+pub mod quantize;
+
+pub mod sha1;
+
+pub mod write_mzml;
+pub use write_mzml::write_mzml;
@@ -0,0 +1,252 @@
+// MS-Numpress-style codecs for binary data arrays: "linear" (lossy, fixed-point
+// linear-prediction) for monotonic-ish f64 arrays such as m/z, and "slof"
+// (short-logged-float) for non-negative f32 arrays such as intensity.
+//
+// Block layout (both codecs), little-endian:
+//   [0..8)   scale factor, f64
+//   [8..12)  element count, u32
+//   [12..)   payload (codec-specific)
+
+use crate::utilities::parse::helper::ensure_cap;
+
+const HEADER_LEN: usize = 12;
+
+#[inline]
+fn auto_linear_scale(vals: &[f64]) -> f64 {
+    let max_abs = vals.iter().fold(0.0f64, |m, &v| m.max(v.abs()));
+    if max_abs <= 0.0 || !max_abs.is_finite() {
+        return 1.0;
+    }
+    // Keep round(x * s) within ~magnitude 2^31.
+    (2_000_000_000.0 / max_abs).max(1.0)
+}
+
+#[inline]
+fn nibble_encode(e: i32, out: &mut Vec<u8>, nibble_buf: &mut u8, half_filled: &mut bool) {
+    let bytes = e.to_le_bytes();
+    let mut nibbles = [0u8; 8];
+    for i in 0..4 {
+        nibbles[i * 2] = bytes[i] & 0x0f;
+        nibbles[i * 2 + 1] = (bytes[i] >> 4) & 0x0f;
+    }
+    let mut keep = 8usize;
+    while keep > 1 && nibbles[keep - 1] == 0 {
+        keep -= 1;
+    }
+    let head = (8 - keep) as u8;
+    push_nibble(head, out, nibble_buf, half_filled);
+    for &n in &nibbles[0..keep] {
+        push_nibble(n, out, nibble_buf, half_filled);
+    }
+}
+
+#[inline]
+fn push_nibble(n: u8, out: &mut Vec<u8>, nibble_buf: &mut u8, half_filled: &mut bool) {
+    if *half_filled {
+        out.push(*nibble_buf | (n << 4));
+        *half_filled = false;
+    } else {
+        *nibble_buf = n;
+        *half_filled = true;
+    }
+}
+
+struct NibbleReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+    high: bool,
+}
+
+impl<'a> NibbleReader<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Self {
+            buf,
+            pos: 0,
+            high: false,
+        }
+    }
+
+    fn next(&mut self) -> Result<u8, String> {
+        if self.pos >= self.buf.len() {
+            return Err("numpress: nibble stream underrun".into());
+        }
+        let byte = self.buf[self.pos];
+        let n = if self.high {
+            self.pos += 1;
+            byte >> 4
+        } else {
+            byte & 0x0f
+        };
+        self.high = !self.high;
+        Ok(n)
+    }
+
+    fn next_residual(&mut self) -> Result<i32, String> {
+        let dropped = self.next()? as usize;
+        if dropped > 7 {
+            return Err("numpress: corrupt nibble header".into());
+        }
+        let keep = 8 - dropped;
+        let mut nibbles = [0u8; 8];
+        for slot in nibbles.iter_mut().take(keep) {
+            *slot = self.next()?;
+        }
+        let mut bytes = [0u8; 4];
+        for i in 0..4 {
+            bytes[i] = nibbles[i * 2] | (nibbles[i * 2 + 1] << 4);
+        }
+        Ok(i32::from_le_bytes(bytes))
+    }
+}
+
+/// Encode `vals` with numpress-style linear prediction. `scale` picks the
+/// fixed-point factor; `None` auto-derives one from the data range.
+pub fn encode_linear(vals: &[f64], scale: Option<f64>) -> Vec<u8> {
+    let s = scale.unwrap_or_else(|| auto_linear_scale(vals));
+    let mut out = Vec::with_capacity(HEADER_LEN + vals.len() * 2);
+    out.extend_from_slice(&s.to_le_bytes());
+    out.extend_from_slice(&(vals.len() as u32).to_le_bytes());
+    if vals.is_empty() {
+        return out;
+    }
+
+    let scaled = |x: f64| (x * s).round() as i32;
+    let x0 = scaled(vals[0]);
+    out.extend_from_slice(&x0.to_le_bytes());
+    if vals.len() == 1 {
+        return out;
+    }
+    let x1 = scaled(vals[1]);
+    out.extend_from_slice(&x1.to_le_bytes());
+
+    let mut prev2 = x0 as i64;
+    let mut prev1 = x1 as i64;
+    let mut nibble_buf = 0u8;
+    let mut half_filled = false;
+    for &v in &vals[2..] {
+        let x = scaled(v) as i64;
+        let pred = 2 * prev1 - prev2;
+        let e = (x - pred) as i32;
+        nibble_encode(e, &mut out, &mut nibble_buf, &mut half_filled);
+        prev2 = prev1;
+        prev1 = x;
+    }
+    if half_filled {
+        out.push(nibble_buf);
+    }
+    out
+}
+
+/// Decode a block produced by [`encode_linear`] at `off` within `buf`.
+pub fn decode_linear(buf: &[u8], off: usize) -> Result<Vec<f64>, String> {
+    if off + HEADER_LEN > buf.len() {
+        return Err("numpress linear: short header".into());
+    }
+    let s = f64::from_le_bytes(buf[off..off + 8].try_into().unwrap());
+    let n = u32::from_le_bytes(buf[off + 8..off + 12].try_into().unwrap()) as usize;
+    if n == 0 || s == 0.0 {
+        return Ok(Vec::new());
+    }
+    let mut p = off + HEADER_LEN;
+    if p + 4 > buf.len() {
+        return Err("numpress linear: truncated".into());
+    }
+    let x0 = i32::from_le_bytes(buf[p..p + 4].try_into().unwrap());
+    p += 4;
+    let mut out = Vec::with_capacity(n);
+    out.push(x0 as f64 / s);
+    if n == 1 {
+        return Ok(out);
+    }
+    if p + 4 > buf.len() {
+        return Err("numpress linear: truncated".into());
+    }
+    let x1 = i32::from_le_bytes(buf[p..p + 4].try_into().unwrap());
+    p += 4;
+    out.push(x1 as f64 / s);
+
+    let mut reader = NibbleReader::new(&buf[p..]);
+    let mut prev2 = x0 as i64;
+    let mut prev1 = x1 as i64;
+    for _ in 2..n {
+        let e = reader.next_residual()?;
+        let pred = 2 * prev1 - prev2;
+        let x = pred + e as i64;
+        out.push(x as f64 / s);
+        prev2 = prev1;
+        prev1 = x;
+    }
+    Ok(out)
+}
+
+/// Encode non-negative `vals` with the short-logged-float codec: `round(log(1+x) * scale)`
+/// clamped into a `u16`. `scale` defaults to a value that spans the observed range.
+pub fn encode_slof(vals: &[f32], scale: Option<f32>) -> Vec<u8> {
+    let max = vals.iter().fold(0.0f32, |m, &v| m.max(v.max(0.0)));
+    let s = scale.unwrap_or_else(|| {
+        let span = (1.0 + max as f64).ln();
+        if span > 0.0 {
+            (u16::MAX as f64 / span) as f32
+        } else {
+            1.0
+        }
+    });
+    let mut out = Vec::with_capacity(HEADER_LEN + vals.len() * 2);
+    out.extend_from_slice(&(s as f64).to_le_bytes());
+    out.extend_from_slice(&(vals.len() as u32).to_le_bytes());
+    for &v in vals {
+        let x = v.max(0.0);
+        let logged = ((1.0 + x as f64).ln() * s as f64).round();
+        let clamped = logged.clamp(0.0, u16::MAX as f64) as u16;
+        out.extend_from_slice(&clamped.to_le_bytes());
+    }
+    out
+}
+
+/// Decode a block produced by [`encode_slof`] at `off` within `buf`.
+pub fn decode_slof(buf: &[u8], off: usize) -> Result<Vec<f32>, String> {
+    if off + HEADER_LEN > buf.len() {
+        return Err("numpress slof: short header".into());
+    }
+    let s = f64::from_le_bytes(buf[off..off + 8].try_into().unwrap());
+    let n = u32::from_le_bytes(buf[off + 8..off + 12].try_into().unwrap()) as usize;
+    let need = n.checked_mul(2).ok_or("numpress slof: len overflow")?;
+    let p = off + HEADER_LEN;
+    if p + need > buf.len() {
+        return Err("numpress slof: truncated".into());
+    }
+    if s == 0.0 {
+        return Ok(vec![0.0; n]);
+    }
+    let mut out = Vec::with_capacity(n);
+    let mut q = p;
+    for _ in 0..n {
+        let code = u16::from_le_bytes(buf[q..q + 2].try_into().unwrap());
+        let x = (code as f64 / s).exp() - 1.0;
+        out.push(x as f32);
+        q += 2;
+    }
+    Ok(out)
+}
+
+/// Append an [`encode_linear`] block to `out`, 8-byte aligned, returning `(offset, element count)`.
+pub fn write_linear_le(out: &mut Vec<u8>, pos: &mut usize, vals: &[f64], scale: Option<f64>) -> (u64, u32) {
+    *pos = (*pos + 7) & !7;
+    let off = *pos as u64;
+    let block = encode_linear(vals, scale);
+    ensure_cap(out, *pos + block.len());
+    out[*pos..*pos + block.len()].copy_from_slice(&block);
+    *pos += block.len();
+    (off, vals.len() as u32)
+}
+
+/// Append an [`encode_slof`] block to `out`, 8-byte aligned, returning `(offset, element count)`.
+pub fn write_slof_le(out: &mut Vec<u8>, pos: &mut usize, vals: &[f32], scale: Option<f32>) -> (u64, u32) {
+    *pos = (*pos + 7) & !7;
+    let off = *pos as u64;
+    let block = encode_slof(vals, scale);
+    ensure_cap(out, *pos + block.len());
+    out[*pos..*pos + block.len()].copy_from_slice(&block);
+    *pos += block.len();
+    (off, vals.len() as u32)
+}
@@ -6,6 +6,8 @@ use serde::{Deserialize, Serialize};
 use std::io::{Cursor, Read, Seek, SeekFrom};
 use std::str;
 
+use crate::utilities::parse::ms_numpress;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SpectrumSummary {
     pub index: usize,
@@ -230,6 +232,289 @@ pub fn parse_mzml(bytes: &[u8], slim: bool) -> Result<MzML, String> {
     })
 }
 
+const MZML_READER_HEAD_PROBE: usize = 64 * 1024;
+
+/// Streaming reader for indexed mzML files too large to parse into memory
+/// up front. `open` reads only the run header and, when present, the
+/// `<indexList>`; `spectrum_by_index`/`spectrum_by_id` (and their
+/// `chromatogram_*` counterparts) then seek straight to the stored byte
+/// offset and parse a single `<spectrum>`/`<chromatogram>` element on
+/// demand, so callers pay the cost of array decoding only for the scans
+/// they actually touch. When the index is missing, or its offsets don't
+/// land on the expected opening tag (the reader does not check the SHA-1
+/// `fileChecksum`; this structural check is the only test of the index), this falls back
+/// to a one-time linear scan that records element start offsets without
+/// materializing every `SpectrumSummary`/`ChromatogramSummary`.
+pub struct MzMLReader<R: Read + Seek> {
+    reader: R,
+    pub run: Run,
+    pub index_list: Option<IndexList>,
+    spectrum_offsets: Vec<u64>,
+    chromatogram_offsets: Vec<u64>,
+    scratch: Scratch,
+}
+
+impl<R: Read + Seek> MzMLReader<R> {
+    pub fn open(mut reader: R) -> Result<Self, String> {
+        reader
+            .seek(SeekFrom::Start(0))
+            .map_err(|e| format!("seek start: {e}"))?;
+        let mut head = vec![0u8; MZML_READER_HEAD_PROBE];
+        let n = reader
+            .read(&mut head)
+            .map_err(|e| format!("read head: {e}"))?;
+        head.truncate(n);
+        let run = parse_run_header(&head).unwrap_or(Run {
+            id: String::new(),
+            start_time_stamp: None,
+            default_instrument_configuration_ref: None,
+            spectrum_list_count: None,
+            chromatogram_list_count: None,
+            spectra: Vec::new(),
+            chromatograms: Vec::new(),
+        });
+
+        let index_list = read_index_list(&mut reader)?;
+        let mut spectrum_offsets: Vec<u64> = index_list
+            .as_ref()
+            .map(|l| l.spectrum.iter().map(|o| o.offset).collect())
+            .unwrap_or_default();
+        if spectrum_offsets.is_empty() || !offsets_look_valid(&mut reader, &spectrum_offsets)? {
+            spectrum_offsets = scan_spectrum_offsets(&mut reader)?;
+        }
+
+        let mut chromatogram_offsets: Vec<u64> = index_list
+            .as_ref()
+            .map(|l| l.chromatogram.iter().map(|o| o.offset).collect())
+            .unwrap_or_default();
+        if chromatogram_offsets.is_empty()
+            || !offsets_look_valid_for(&mut reader, &chromatogram_offsets, b"<chromatogram")?
+        {
+            chromatogram_offsets = scan_tag_offsets(&mut reader, b"<chromatogram ")?;
+        }
+
+        reader
+            .seek(SeekFrom::Start(0))
+            .map_err(|e| format!("seek start: {e}"))?;
+        Ok(Self {
+            reader,
+            run,
+            index_list,
+            spectrum_offsets,
+            chromatogram_offsets,
+            scratch: Scratch {
+                b64_buf: Vec::with_capacity(256),
+                zlib_buf: Vec::with_capacity(256),
+            },
+        })
+    }
+
+    /// Number of spectra reachable through this reader.
+    pub fn len(&self) -> usize {
+        self.spectrum_offsets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spectrum_offsets.is_empty()
+    }
+
+    /// Number of chromatograms reachable through this reader.
+    pub fn chromatogram_len(&self) -> usize {
+        self.chromatogram_offsets.len()
+    }
+
+    /// Seek to and parse a single spectrum by its position in scan order.
+    pub fn spectrum_by_index(&mut self, index: usize) -> Result<Option<SpectrumSummary>, String> {
+        let Some(&start) = self.spectrum_offsets.get(index) else {
+            return Ok(None);
+        };
+        let next = self.spectrum_offsets.get(index + 1).copied();
+        read_one_spectrum_span(&mut self.reader, start, next, &mut self.scratch)
+    }
+
+    /// Seek to and parse a single spectrum by its `id` attribute, using the
+    /// index's `idRef`s when available and otherwise checking each
+    /// spectrum's own `id` attribute in offset order.
+    pub fn spectrum_by_id(&mut self, id: &str) -> Result<Option<SpectrumSummary>, String> {
+        if let Some(list) = &self.index_list {
+            if let Some(pos) = list
+                .spectrum
+                .iter()
+                .position(|o| o.id_ref.as_deref() == Some(id))
+            {
+                return self.spectrum_by_index(pos);
+            }
+        }
+        for index in 0..self.spectrum_offsets.len() {
+            let start = self.spectrum_offsets[index];
+            let next = self.spectrum_offsets.get(index + 1).copied();
+            let block = read_span_bytes(&mut self.reader, start, next, b"</spectrum>")?;
+            if find_attr_string(&block, b"spectrum", b"id").as_deref() == Some(id) {
+                return Ok(parse_spectrum_block(&block, &mut self.scratch));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Iterate every spectrum in offset order, reading each one lazily.
+    pub fn iter(&mut self) -> MzMLReaderIter<'_, R> {
+        MzMLReaderIter {
+            reader: self,
+            next: 0,
+        }
+    }
+
+    /// Seek to and parse a single chromatogram by its position in scan order.
+    pub fn chromatogram_by_index(
+        &mut self,
+        index: usize,
+    ) -> Result<Option<ChromatogramSummary>, String> {
+        let Some(&start) = self.chromatogram_offsets.get(index) else {
+            return Ok(None);
+        };
+        let next = self.chromatogram_offsets.get(index + 1).copied();
+        let block = read_span_bytes(&mut self.reader, start, next, b"</chromatogram>")?;
+        Ok(parse_chromatogram_block(&block, &mut self.scratch))
+    }
+
+    /// Seek to and parse a single chromatogram by its `id` attribute, using
+    /// the index's `idRef`s when available and otherwise checking each
+    /// chromatogram's own `id` attribute in offset order.
+    pub fn chromatogram_by_id(&mut self, id: &str) -> Result<Option<ChromatogramSummary>, String> {
+        if let Some(list) = &self.index_list {
+            if let Some(pos) = list
+                .chromatogram
+                .iter()
+                .position(|o| o.id_ref.as_deref() == Some(id))
+            {
+                return self.chromatogram_by_index(pos);
+            }
+        }
+        for index in 0..self.chromatogram_offsets.len() {
+            let start = self.chromatogram_offsets[index];
+            let next = self.chromatogram_offsets.get(index + 1).copied();
+            let block = read_span_bytes(&mut self.reader, start, next, b"</chromatogram>")?;
+            if find_attr_string(&block, b"chromatogram", b"id").as_deref() == Some(id) {
+                return Ok(parse_chromatogram_block(&block, &mut self.scratch));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Lazy iterator over a [`MzMLReader`]'s spectra, produced by [`MzMLReader::iter`].
+pub struct MzMLReaderIter<'a, R: Read + Seek> {
+    reader: &'a mut MzMLReader<R>,
+    next: usize,
+}
+
+impl<R: Read + Seek> Iterator for MzMLReaderIter<'_, R> {
+    type Item = Result<SpectrumSummary, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.next < self.reader.spectrum_offsets.len() {
+            let index = self.next;
+            self.next += 1;
+            match self.reader.spectrum_by_index(index) {
+                Ok(Some(sum)) => return Some(Ok(sum)),
+                Ok(None) => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        None
+    }
+}
+
+fn read_index_list<R: Read + Seek>(r: &mut R) -> Result<Option<IndexList>, String> {
+    const TAIL: u64 = 64 * 1024;
+    let end = r.seek(SeekFrom::End(0)).map_err(|e| format!("seek end: {e}"))?;
+    let start = end.saturating_sub(TAIL);
+    r.seek(SeekFrom::Start(start))
+        .map_err(|e| format!("seek tail: {e}"))?;
+    let mut tail = Vec::with_capacity((end - start) as usize);
+    r.take(end - start)
+        .read_to_end(&mut tail)
+        .map_err(|e| format!("read tail: {e}"))?;
+
+    let index_list_offset = match extract_index_list_offset(&tail) {
+        Some(off) => off,
+        None => return Ok(None),
+    };
+    r.seek(SeekFrom::Start(index_list_offset))
+        .map_err(|e| format!("seek indexList: {e}"))?;
+    let mut buf = vec![0u8; (end - index_list_offset).min(4_000_000).max(4096) as usize];
+    let n = r
+        .read(&mut buf)
+        .map_err(|e| format!("read indexList: {e}"))?;
+    buf.truncate(n);
+
+    let spectrum = parse_offsets_from_index(&buf, b"spectrum");
+    let chromatogram = parse_offsets_from_index(&buf, b"chromatogram");
+    if spectrum.is_empty() && chromatogram.is_empty() {
+        return Ok(None);
+    }
+    let file_checksum = if let Some((s, e)) = tag_body(&tail, b"<fileChecksum>", b"</fileChecksum>") {
+        b2s(Some(strip_ws(&tail[s..e])))
+    } else {
+        None
+    };
+    Ok(Some(IndexList {
+        spectrum,
+        chromatogram,
+        index_list_offset: Some(index_list_offset),
+        file_checksum,
+    }))
+}
+
+/// Sanity-check an index's spectrum offsets by confirming the first one
+/// actually lands on a `<spectrum` tag.
+fn offsets_look_valid<R: Read + Seek>(r: &mut R, offsets: &[u64]) -> Result<bool, String> {
+    offsets_look_valid_for(r, offsets, b"<spectrum")
+}
+
+/// Structural check of an index: confirms that the first stored offset
+/// actually lands on the expected opening tag. The SHA-1 `fileChecksum` is
+/// not checked, and offsets after the first are not probed.
+fn offsets_look_valid_for<R: Read + Seek>(
+    r: &mut R,
+    offsets: &[u64],
+    open_tag: &[u8],
+) -> Result<bool, String> {
+    let Some(&first) = offsets.first() else {
+        return Ok(false);
+    };
+    r.seek(SeekFrom::Start(first))
+        .map_err(|e| format!("seek probe: {e}"))?;
+    let mut probe = vec![0u8; open_tag.len()];
+    let n = r.read(&mut probe).map_err(|e| format!("read probe: {e}"))?;
+    Ok(probe[..n].starts_with(open_tag))
+}
+
+/// Fallback for files with no (or an invalid) `<indexList>`: a single
+/// linear scan recording each `<spectrum ` tag's start offset, without
+/// parsing the element itself.
+fn scan_spectrum_offsets<R: Read + Seek>(r: &mut R) -> Result<Vec<u64>, String> {
+    scan_tag_offsets(r, b"<spectrum ")
+}
+
+/// Like [`scan_spectrum_offsets`] but for an arbitrary tag, reused for the
+/// `<chromatogram>` fallback scan.
+fn scan_tag_offsets<R: Read + Seek>(r: &mut R, open_tag: &[u8]) -> Result<Vec<u64>, String> {
+    r.seek(SeekFrom::Start(0))
+        .map_err(|e| format!("seek: {e}"))?;
+    let mut file = Vec::new();
+    r.read_to_end(&mut file).map_err(|e| format!("read all: {e}"))?;
+    let mut out = Vec::new();
+    let mut cur = 0usize;
+    let of = memmem::Finder::new(open_tag);
+    while let Some(p) = of.find(&file[cur..]) {
+        let start = cur + p;
+        out.push(start as u64);
+        cur = start + 1;
+    }
+    Ok(out)
+}
+
 fn parse_cv_list(xml: &[u8]) -> Vec<CvEntry> {
     let mut out = Vec::new();
     if let Some(block) = find_section(xml, b"cvList") {
@@ -742,12 +1027,16 @@ fn parse_spectrum_offsets_from_index(buf: &[u8]) -> Vec<u64> {
     out
 }
 
-fn read_one_spectrum_span<R: Read + Seek>(
+/// Read the raw bytes of one `<TAG ...>...</TAG>` element starting at
+/// `start`. When `next` (the next element's start offset) is known, this is
+/// a single bounded read; otherwise it streams forward in chunks until
+/// `close_tag` is found, which only happens for the last element in a file.
+fn read_span_bytes<R: Read + Seek>(
     r: &mut R,
     start: u64,
     next: Option<u64>,
-    scratch: &mut Scratch,
-) -> Result<Option<SpectrumSummary>, String> {
+    close_tag: &[u8],
+) -> Result<Vec<u8>, String> {
     r.seek(SeekFrom::Start(start))
         .map_err(|e| format!("seek: {e}"))?;
     if let Some(end) = next {
@@ -755,39 +1044,48 @@ fn read_one_spectrum_span<R: Read + Seek>(
         let mut buf = vec![0u8; len];
         r.read_exact(&mut buf)
             .map_err(|e| format!("read span: {e}"))?;
-        if let Some(pos) = memmem::find(&buf, b"</spectrum>") {
-            buf.truncate(pos + b"</spectrum>".len());
+        if let Some(pos) = memmem::find(&buf, close_tag) {
+            buf.truncate(pos + close_tag.len());
         }
-        Ok(parse_spectrum_block(&buf, scratch))
+        Ok(buf)
     } else {
         let mut buf = Vec::with_capacity(128 * 1024);
         let mut tmp = [0u8; 128 * 1024];
-        let close = b"</spectrum>";
-        let close_f = memmem::Finder::new(close);
+        let close_f = memmem::Finder::new(close_tag);
         let mut search_from = 0usize;
         loop {
             let n = r
                 .read(&mut tmp)
-                .map_err(|e| format!("read tail spectrum: {e}"))?;
+                .map_err(|e| format!("read tail span: {e}"))?;
             if n == 0 {
                 break;
             }
             buf.extend_from_slice(&tmp[..n]);
-            let window_start = search_from.saturating_sub(close.len().saturating_sub(1));
+            let window_start = search_from.saturating_sub(close_tag.len().saturating_sub(1));
             if let Some(rel) = close_f.find(&buf[window_start..]) {
-                let end = window_start + rel + close.len();
+                let end = window_start + rel + close_tag.len();
                 buf.truncate(end);
                 break;
             }
             search_from = buf.len();
             if buf.len() > 32 * 1024 * 1024 {
-                return Err("spectrum block too large?".into());
+                return Err("span too large?".into());
             }
         }
-        Ok(parse_spectrum_block(&buf, scratch))
+        Ok(buf)
     }
 }
 
+fn read_one_spectrum_span<R: Read + Seek>(
+    r: &mut R,
+    start: u64,
+    next: Option<u64>,
+    scratch: &mut Scratch,
+) -> Result<Option<SpectrumSummary>, String> {
+    let buf = read_span_bytes(r, start, next, b"</spectrum>")?;
+    Ok(parse_spectrum_block(&buf, scratch))
+}
+
 fn find_spectrum_end_in(hay: &[u8], start: usize) -> Option<usize> {
     let rel = memmem::find(&hay[start..], b"</spectrum>")?;
     Some(start + rel + b"</spectrum>".len())
@@ -1009,15 +1307,93 @@ fn find_scan_start_time_min(buf: &[u8]) -> Option<f64> {
     None
 }
 
-fn bda_flags(b: &[u8]) -> (bool, bool, bool, bool, bool, bool) {
+/// Which MS-Numpress codec (if any) a `<binaryDataArray>` was compressed
+/// with, keyed off the accession rather than the (non-normative) cvParam
+/// name text.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum NumpressKind {
+    Linear,
+    Pic,
+    Slof,
+}
+
+fn numpress_kind_from_accession(accession: &[u8]) -> Option<NumpressKind> {
+    match accession {
+        b"MS:1002312" => Some(NumpressKind::Linear),
+        b"MS:1002313" => Some(NumpressKind::Pic),
+        b"MS:1002314" => Some(NumpressKind::Slof),
+        _ => None,
+    }
+}
+
+/// The sample width/representation of a `<binaryDataArray>`, keyed off its
+/// CV accession (falling back to the cvParam name, since some writers omit
+/// the accession on the width param). Covers both float widths (`MS:1000521`
+/// 32-bit, `MS:1000523` 64-bit) and integer widths (`MS:1000519` 32-bit,
+/// `MS:1000522` 64-bit) — ion-mobility index arrays and integer scan arrays
+/// decode through the same `read_samples` dispatch as m/z and intensity.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum DType {
+    F32,
+    F64,
+    I32,
+    I64,
+}
+
+fn dtype_from_accession_or_name(accession: Option<&[u8]>, name: &[u8]) -> Option<DType> {
+    if let Some(acc) = accession {
+        match acc {
+            b"MS:1000523" => return Some(DType::F64),
+            b"MS:1000521" => return Some(DType::F32),
+            b"MS:1000522" => return Some(DType::I64),
+            b"MS:1000519" => return Some(DType::I32),
+            _ => {}
+        }
+    }
+    match name {
+        b"64-bit float" => Some(DType::F64),
+        b"32-bit float" => Some(DType::F32),
+        b"64-bit integer" => Some(DType::I64),
+        b"32-bit integer" => Some(DType::I32),
+        _ => None,
+    }
+}
+
+/// Read `want` samples of `dtype` out of `bytes` in `little`/big endian
+/// order, converting uniformly to `f64`.
+fn read_samples(bytes: &[u8], dtype: DType, little: bool, want: usize) -> Vec<f64> {
+    match dtype {
+        DType::F64 => bytes_to_f64_exact_into(bytes, little, want),
+        DType::F32 => bytes_to_f32_as_f64_exact_into(bytes, little, want),
+        DType::I64 => bytes_to_i64_as_f64_exact_into(bytes, little, want),
+        DType::I32 => bytes_to_i32_as_f64_exact_into(bytes, little, want),
+    }
+}
+
+// Scans a `<binaryDataArray>` block's cvParams for the dtype/endianness/
+// compression flags `decode_binary_arrays` needs; `MS:1000574` ("zlib
+// compression") is already handled transparently there, so compressed and
+// uncompressed `<binary>` blobs are indistinguishable to the float
+// converters once this returns.
+#[allow(clippy::type_complexity)]
+fn bda_flags(
+    b: &[u8],
+) -> (
+    bool,
+    bool,
+    bool,
+    Option<DType>,
+    bool,
+    Option<NumpressKind>,
+) {
     let stop = memmem::find(b, b"<binary>").unwrap_or(b.len());
     let head = &b[..stop];
     let mut kind_mz = false;
     let mut kind_int = false;
     let mut is_zlib = false;
-    let mut is_f64 = false;
-    let mut is_f32 = false;
+    let mut dtype = None;
     let mut little = true;
+    let mut numpress = None;
     let mut cur = 0usize;
     let f = memmem::Finder::new(b"<cvParam");
     while let Some(p) = f.find(&head[cur..]) {
@@ -1025,24 +1401,25 @@ fn bda_flags(b: &[u8]) -> (bool, bool, bool, bool, bool, bool) {
         if let Some(gt_rel) = mc_memchr(b'>', &head[from..]) {
             let gt = from + gt_rel;
             let tag_head = &head[from..gt];
+            let accession = find_attr_value_in_tag(tag_head, b"accession");
             if let Some(nm) = find_attr_value_in_tag(tag_head, b"name") {
                 match nm {
                     b"m/z array" => kind_mz = true,
                     b"intensity array" => kind_int = true,
                     b"zlib compression" => is_zlib = true,
-                    b"64-bit float" => is_f64 = true,
-                    b"32-bit float" => is_f32 = true,
                     b"little endian" => little = true,
                     b"big endian" => little = false,
                     _ => {}
                 }
+                dtype = dtype.or(dtype_from_accession_or_name(accession, nm));
             }
+            numpress = numpress.or(accession.and_then(numpress_kind_from_accession));
             cur = gt + 1;
         } else {
             break;
         }
     }
-    (kind_mz, kind_int, is_zlib, is_f64, is_f32, little)
+    (kind_mz, kind_int, is_zlib, dtype, little, numpress)
 }
 
 fn decode_binary_arrays(
@@ -1066,7 +1443,7 @@ fn decode_binary_arrays(
         };
         let b = &block[start..start + end_rel];
 
-        let (kind_mz, kind_int, is_zlib, is_f64, is_f32, little) = bda_flags(b);
+        let (kind_mz, kind_int, is_zlib, dtype, little, numpress) = bda_flags(b);
 
         if let Some((bs, be)) = tag_body(b, b"<binary>", b"</binary>") {
             strip_b64_ws_into(&b[bs..be], &mut scratch.zlib_buf);
@@ -1097,32 +1474,53 @@ fn decode_binary_arrays(
                 &scratch.b64_buf
             };
 
+            if let Some(kind) = numpress {
+                if kind_mz {
+                    mz = Some(match kind {
+                        NumpressKind::Linear => ms_numpress::decode_linear(bytes, expected_len),
+                        NumpressKind::Pic => ms_numpress::decode_pic(bytes, expected_len),
+                        NumpressKind::Slof => ms_numpress::decode_slof(bytes, expected_len)
+                            .into_iter()
+                            .map(|v| v as f64)
+                            .collect(),
+                    });
+                } else if kind_int {
+                    inten = Some(match kind {
+                        NumpressKind::Slof => ms_numpress::decode_slof(bytes, expected_len),
+                        NumpressKind::Linear => ms_numpress::decode_linear(bytes, expected_len)
+                            .into_iter()
+                            .map(|v| v as f32)
+                            .collect(),
+                        NumpressKind::Pic => ms_numpress::decode_pic(bytes, expected_len)
+                            .into_iter()
+                            .map(|v| v as f32)
+                            .collect(),
+                    });
+                }
+                cur = start + end_rel + bda_close.len();
+                continue;
+            }
+
             let want = if expected_len > 0 {
                 expected_len
-            } else if is_f64 {
-                bytes.len() / 8
             } else {
-                bytes.len() / 4
+                match dtype {
+                    Some(DType::F64) | Some(DType::I64) => bytes.len() / 8,
+                    _ => bytes.len() / 4,
+                }
             };
 
-            if kind_mz {
-                let vals = if is_f64 {
-                    bytes_to_f64_exact_into(bytes, little, want)
-                } else if is_f32 {
-                    bytes_to_f32_as_f64_exact_into(bytes, little, want)
-                } else {
-                    Vec::new()
-                };
-                mz = Some(vals);
-            } else if kind_int {
-                let vals = if is_f32 {
-                    bytes_to_f32_exact_into(bytes, little, want)
-                } else if is_f64 {
-                    bytes_to_f64_as_f32_exact_into(bytes, little, want)
-                } else {
-                    Vec::new()
-                };
-                inten = Some(vals);
+            if let Some(dtype) = dtype {
+                if kind_mz {
+                    mz = Some(read_samples(bytes, dtype, little, want));
+                } else if kind_int {
+                    inten = Some(
+                        read_samples(bytes, dtype, little, want)
+                            .into_iter()
+                            .map(|v| v as f32)
+                            .collect(),
+                    );
+                }
             }
         }
 
@@ -1132,15 +1530,25 @@ fn decode_binary_arrays(
     (mz, inten)
 }
 
-fn bda_flags_chrom(b: &[u8]) -> (bool, bool, bool, bool, bool, bool) {
+#[allow(clippy::type_complexity)]
+fn bda_flags_chrom(
+    b: &[u8],
+) -> (
+    bool,
+    bool,
+    bool,
+    Option<DType>,
+    bool,
+    Option<NumpressKind>,
+) {
     let stop = memmem::find(b, b"<binary>").unwrap_or(b.len());
     let head = &b[..stop];
     let mut kind_time = false;
     let mut kind_int = false;
     let mut is_zlib = false;
-    let mut is_f64 = false;
-    let mut is_f32 = false;
+    let mut dtype = None;
     let mut little = true;
+    let mut numpress = None;
     let mut cur = 0usize;
     let f = memmem::Finder::new(b"<cvParam");
     while let Some(p) = f.find(&head[cur..]) {
@@ -1148,24 +1556,25 @@ fn bda_flags_chrom(b: &[u8]) -> (bool, bool, bool, bool, bool, bool) {
         if let Some(gt_rel) = mc_memchr(b'>', &head[from..]) {
             let gt = from + gt_rel;
             let tag_head = &head[from..gt];
+            let accession = find_attr_value_in_tag(tag_head, b"accession");
             if let Some(nm) = find_attr_value_in_tag(tag_head, b"name") {
                 match nm {
                     b"time array" => kind_time = true,
                     b"intensity array" => kind_int = true,
                     b"zlib compression" => is_zlib = true,
-                    b"64-bit float" => is_f64 = true,
-                    b"32-bit float" => is_f32 = true,
                     b"little endian" => little = true,
                     b"big endian" => little = false,
                     _ => {}
                 }
+                dtype = dtype.or(dtype_from_accession_or_name(accession, nm));
             }
+            numpress = numpress.or(accession.and_then(numpress_kind_from_accession));
             cur = gt + 1;
         } else {
             break;
         }
     }
-    (kind_time, kind_int, is_zlib, is_f64, is_f32, little)
+    (kind_time, kind_int, is_zlib, dtype, little, numpress)
 }
 
 fn decode_chrom_binary_arrays(
@@ -1189,7 +1598,7 @@ fn decode_chrom_binary_arrays(
         };
         let b = &block[start..start + end_rel];
 
-        let (kind_time, kind_int, is_zlib, is_f64, is_f32, little) = bda_flags_chrom(b);
+        let (kind_time, kind_int, is_zlib, dtype, little, numpress) = bda_flags_chrom(b);
 
         if let Some((bs, be)) = tag_body(b, b"<binary>", b"</binary>") {
             strip_b64_ws_into(&b[bs..be], &mut scratch.zlib_buf);
@@ -1220,32 +1629,53 @@ fn decode_chrom_binary_arrays(
                 &scratch.b64_buf
             };
 
+            if let Some(kind) = numpress {
+                if kind_time {
+                    time_arr = Some(match kind {
+                        NumpressKind::Linear => ms_numpress::decode_linear(bytes, expected_len),
+                        NumpressKind::Pic => ms_numpress::decode_pic(bytes, expected_len),
+                        NumpressKind::Slof => ms_numpress::decode_slof(bytes, expected_len)
+                            .into_iter()
+                            .map(|v| v as f64)
+                            .collect(),
+                    });
+                } else if kind_int {
+                    intensity_arr = Some(match kind {
+                        NumpressKind::Slof => ms_numpress::decode_slof(bytes, expected_len),
+                        NumpressKind::Linear => ms_numpress::decode_linear(bytes, expected_len)
+                            .into_iter()
+                            .map(|v| v as f32)
+                            .collect(),
+                        NumpressKind::Pic => ms_numpress::decode_pic(bytes, expected_len)
+                            .into_iter()
+                            .map(|v| v as f32)
+                            .collect(),
+                    });
+                }
+                cur = start + end_rel + bda_close.len();
+                continue;
+            }
+
             let want = if expected_len > 0 {
                 expected_len
-            } else if is_f64 {
-                bytes.len() / 8
             } else {
-                bytes.len() / 4
+                match dtype {
+                    Some(DType::F64) | Some(DType::I64) => bytes.len() / 8,
+                    _ => bytes.len() / 4,
+                }
             };
 
-            if kind_time {
-                let vals = if is_f64 {
-                    bytes_to_f64_exact_into(bytes, little, want)
-                } else if is_f32 {
-                    bytes_to_f32_as_f64_exact_into(bytes, little, want)
-                } else {
-                    Vec::new()
-                };
-                time_arr = Some(vals);
-            } else if kind_int {
-                let vals = if is_f32 {
-                    bytes_to_f32_exact_into(bytes, little, want)
-                } else if is_f64 {
-                    bytes_to_f64_as_f32_exact_into(bytes, little, want)
-                } else {
-                    Vec::new()
-                };
-                intensity_arr = Some(vals);
+            if let Some(dtype) = dtype {
+                if kind_time {
+                    time_arr = Some(read_samples(bytes, dtype, little, want));
+                } else if kind_int {
+                    intensity_arr = Some(
+                        read_samples(bytes, dtype, little, want)
+                            .into_iter()
+                            .map(|v| v as f32)
+                            .collect(),
+                    );
+                }
             }
         }
 
@@ -1376,36 +1806,6 @@ fn is_ws(b: u8) -> bool {
     matches!(b, b' ' | b'\n' | b'\r' | b'\t')
 }
 
-fn bytes_to_f32_exact_into(b: &[u8], little: bool, want: usize) -> Vec<f32> {
-    let len = want.min(b.len() / 4);
-    let mut out = Vec::with_capacity(len);
-    let words = &b[..len * 4];
-    for c in words.chunks_exact(4) {
-        let bits = if little {
-            u32::from_le_bytes([c[0], c[1], c[2], c[3]])
-        } else {
-            u32::from_be_bytes([c[0], c[1], c[2], c[3]])
-        };
-        out.push(f32::from_bits(bits));
-    }
-    out
-}
-
-fn bytes_to_f64_as_f32_exact_into(b: &[u8], little: bool, want: usize) -> Vec<f32> {
-    let len = want.min(b.len() / 8);
-    let mut out = Vec::with_capacity(len);
-    let bytes = &b[..len * 8];
-    for c in bytes.chunks_exact(8) {
-        let bits = if little {
-            u64::from_le_bytes([c[0], c[1], c[2], c[3], c[4], c[5], c[6], c[7]])
-        } else {
-            u64::from_be_bytes([c[0], c[1], c[2], c[3], c[4], c[5], c[6], c[7]])
-        };
-        out.push(f64::from_bits(bits) as f32);
-    }
-    out
-}
-
 fn bytes_to_f64_exact_into(b: &[u8], little: bool, want: usize) -> Vec<f64> {
     let len = want.min(b.len() / 8);
     let mut out = Vec::with_capacity(len);
@@ -1439,6 +1839,36 @@ fn bytes_to_f32_as_f64_exact_into(b: &[u8], little: bool, want: usize) -> Vec<f6
     out
 }
 
+fn bytes_to_i32_as_f64_exact_into(b: &[u8], little: bool, want: usize) -> Vec<f64> {
+    let len = want.min(b.len() / 4);
+    let mut out = Vec::with_capacity(len);
+    let words = &b[..len * 4];
+    for c in words.chunks_exact(4) {
+        let v = if little {
+            i32::from_le_bytes([c[0], c[1], c[2], c[3]])
+        } else {
+            i32::from_be_bytes([c[0], c[1], c[2], c[3]])
+        };
+        out.push(v as f64);
+    }
+    out
+}
+
+fn bytes_to_i64_as_f64_exact_into(b: &[u8], little: bool, want: usize) -> Vec<f64> {
+    let len = want.min(b.len() / 8);
+    let mut out = Vec::with_capacity(len);
+    let bytes = &b[..len * 8];
+    for c in bytes.chunks_exact(8) {
+        let v = if little {
+            i64::from_le_bytes([c[0], c[1], c[2], c[3], c[4], c[5], c[6], c[7]])
+        } else {
+            i64::from_be_bytes([c[0], c[1], c[2], c[3], c[4], c[5], c[6], c[7]])
+        };
+        out.push(v as f64);
+    }
+    out
+}
+
 fn strip_ws(s: &[u8]) -> &[u8] {
     let mut a = 0;
     let mut b = s.len();
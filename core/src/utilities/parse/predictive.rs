@@ -0,0 +1,191 @@
+// Lossless predictive codec for strictly-ascending arrays (m/z values): a
+// fixed-point scale S (a power of ten, chosen so `round(v*S)/S == v` for
+// every value) turns the array into integers, second-order linear
+// prediction (`pred[i] = 2*q[i-1] - q[i-2]`) collapses the near-constant
+// second differences typical of an ascending m/z axis down to small
+// residuals, and those residuals are ZigZag+LEB128-varint encoded. The
+// first two integers are stored verbatim so the recurrence has a seed.
+// Self-describing like the numpress/quantize codecs above, so the file
+// header and index-table entries need no extra fields.
+//
+// Header layout, little-endian:
+//   [0]      mode, u8 (0 = predictive, 1 = raw f64 fallback)
+//   mode 1:  [1..5) count, u32; then `count` raw f64 (bit-exact fallback
+//            for non-monotonic or otherwise non-representable data)
+//   mode 0:  [1..9)   scale S, f64
+//            [9..13)  count, u32
+//            [13..21) q[0], i64
+//            [21..29) q[1], i64
+//            [29..)   ZigZag+LEB128 varint residuals for i in 2..count
+
+use crate::utilities::parse::helper::ensure_cap;
+
+const MAX_SCALE_EXP: i32 = 15;
+
+#[inline]
+fn zigzag_encode(v: i64) -> u64 {
+    ((v << 1) ^ (v >> 63)) as u64
+}
+
+#[inline]
+fn zigzag_decode(v: u64) -> i64 {
+    ((v >> 1) as i64) ^ -((v & 1) as i64)
+}
+
+fn write_varint(out: &mut Vec<u8>, mut v: u64) {
+    loop {
+        let byte = (v & 0x7f) as u8;
+        v >>= 7;
+        if v == 0 {
+            out.push(byte);
+            break;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn read_varint(buf: &[u8], pos: &mut usize) -> Result<u64, String> {
+    let mut v: u64 = 0;
+    let mut shift = 0u32;
+    loop {
+        if *pos >= buf.len() {
+            return Err("varint OOB".into());
+        }
+        let byte = buf[*pos];
+        *pos += 1;
+        v |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            break;
+        }
+        shift += 7;
+    }
+    Ok(v)
+}
+
+/// Find the smallest power-of-ten scale that reproduces every value in
+/// `vals` bit-exactly through `round(v*S)/S`, and whose fixed-point
+/// integers fit in `i64`. Returns `None` if no such scale exists within
+/// `MAX_SCALE_EXP`.
+fn pick_scale(vals: &[f64]) -> Option<f64> {
+    'exp: for exp in 0..=MAX_SCALE_EXP {
+        let s = 10f64.powi(exp);
+        for &v in vals {
+            if !v.is_finite() {
+                continue 'exp;
+            }
+            let q = (v * s).round();
+            if !q.is_finite() || q.abs() >= i64::MAX as f64 {
+                continue 'exp;
+            }
+            if q / s != v {
+                continue 'exp;
+            }
+        }
+        return Some(s);
+    }
+    None
+}
+
+fn encode_raw_fallback(vals: &[f64]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(5 + vals.len() * 8);
+    out.push(1);
+    out.extend_from_slice(&(vals.len() as u32).to_le_bytes());
+    for &v in vals {
+        out.extend_from_slice(&v.to_le_bytes());
+    }
+    out
+}
+
+pub fn encode_pred(vals: &[f64]) -> Vec<u8> {
+    if vals.len() < 2 {
+        return encode_raw_fallback(vals);
+    }
+    let Some(scale) = pick_scale(vals) else {
+        return encode_raw_fallback(vals);
+    };
+    let q: Vec<i64> = vals.iter().map(|&v| (v * scale).round() as i64).collect();
+
+    let mut body = Vec::with_capacity(9 + vals.len() * 2);
+    body.extend_from_slice(&q[0].to_le_bytes());
+    body.extend_from_slice(&q[1].to_le_bytes());
+    for i in 2..q.len() {
+        let pred = 2 * q[i - 1] - q[i - 2];
+        let residual = q[i] - pred;
+        write_varint(&mut body, zigzag_encode(residual));
+    }
+
+    if body.len() + 13 >= vals.len() * 8 {
+        return encode_raw_fallback(vals);
+    }
+
+    let mut out = Vec::with_capacity(13 + body.len());
+    out.push(0);
+    out.extend_from_slice(&scale.to_le_bytes());
+    out.extend_from_slice(&(vals.len() as u32).to_le_bytes());
+    out.extend_from_slice(&body);
+    out
+}
+
+pub fn decode_pred(buf: &[u8], off: usize) -> Result<Vec<f64>, String> {
+    if off >= buf.len() {
+        return Err("predictive array OOB".into());
+    }
+    let mode = buf[off];
+    if mode == 1 {
+        if off + 5 > buf.len() {
+            return Err("predictive: short header".into());
+        }
+        let count = u32::from_le_bytes(buf[off + 1..off + 5].try_into().unwrap()) as usize;
+        let mut out = Vec::with_capacity(count);
+        let mut p = off + 5;
+        for _ in 0..count {
+            if p + 8 > buf.len() {
+                return Err("predictive: truncated value".into());
+            }
+            out.push(f64::from_le_bytes(buf[p..p + 8].try_into().unwrap()));
+            p += 8;
+        }
+        return Ok(out);
+    }
+
+    if off + 13 > buf.len() {
+        return Err("predictive: short header".into());
+    }
+    let scale = f64::from_le_bytes(buf[off + 1..off + 9].try_into().unwrap());
+    let count = u32::from_le_bytes(buf[off + 9..off + 13].try_into().unwrap()) as usize;
+    if count == 0 {
+        return Ok(Vec::new());
+    }
+    let seed_len = 8 * count.min(2);
+    if off + 13 + seed_len > buf.len() {
+        return Err("predictive: truncated seed".into());
+    }
+    let mut q: Vec<i64> = Vec::with_capacity(count);
+    if count >= 1 {
+        q.push(i64::from_le_bytes(
+            buf[off + 13..off + 21].try_into().unwrap(),
+        ));
+    }
+    if count >= 2 {
+        q.push(i64::from_le_bytes(
+            buf[off + 21..off + 29].try_into().unwrap(),
+        ));
+    }
+    let mut pos = off + 13 + seed_len;
+    for i in 2..count {
+        let residual = zigzag_decode(read_varint(buf, &mut pos)?);
+        let pred = 2 * q[i - 1] - q[i - 2];
+        q.push(pred + residual);
+    }
+    Ok(q.into_iter().map(|v| v as f64 / scale).collect())
+}
+
+pub fn write_pred_le(out: &mut Vec<u8>, pos: &mut usize, vals: &[f64]) -> (u64, u32) {
+    *pos = (*pos + 7) & !7;
+    let off = *pos as u64;
+    let bytes = encode_pred(vals);
+    ensure_cap(out, *pos + bytes.len());
+    out[*pos..*pos + bytes.len()].copy_from_slice(&bytes);
+    *pos += bytes.len();
+    (off, vals.len() as u32)
+}
@@ -0,0 +1,130 @@
+// Block-quantized fixed-width codec ("k-quant"-style): values are grouped
+// into fixed-size blocks, each block storing one f32 scale plus its values
+// quantized to signed int8 (or int4, packed two per byte). An all-zero block
+// stores scale 0.0 and emits no codes. Self-describing like the numpress
+// codecs above: the stream carries its own bits/block/count header, so the
+// file header and index-table entries need no extra fields.
+//
+// Block layout, little-endian:
+//   [0]      bits, u8 (4 or 8)
+//   [1..3)   block size, u16
+//   [3]      reserved, u8
+//   [4..8)   element count, u32
+//   [8..)    one entry per block of up to `block` elements:
+//              [0..4)  scale, f32 (0.0 => block is all-zero, no codes follow)
+//              [4..)   signed codes, `bits` wide (int8, or int4 packed
+//                      two-per-byte low-nibble-first)
+
+use crate::utilities::parse::helper::ensure_cap;
+
+const HEADER_LEN: usize = 8;
+
+#[inline]
+fn code_max(bits: u8) -> i32 {
+    (1i32 << (bits - 1)) - 1
+}
+
+/// Encode `vals` as fixed-width per-block quantized codes. `bits` is clamped
+/// to `4` or `8`; NaN/Inf values are treated as `0.0` rather than poisoning a
+/// block's scale.
+pub fn encode_quant(vals: &[f64], bits: u8, block: usize) -> Vec<u8> {
+    let bits = if bits == 4 { 4 } else { 8 };
+    let block = block.max(1);
+    let cmax = code_max(bits) as f64;
+
+    let mut out = Vec::with_capacity(HEADER_LEN + vals.len());
+    out.push(bits);
+    out.extend_from_slice(&(block.min(u16::MAX as usize) as u16).to_le_bytes());
+    out.push(0);
+    out.extend_from_slice(&(vals.len() as u32).to_le_bytes());
+
+    for chunk in vals.chunks(block) {
+        let max_abs = chunk
+            .iter()
+            .fold(0.0f64, |m, &v| if v.is_finite() { m.max(v.abs()) } else { m });
+        if max_abs <= 0.0 {
+            out.extend_from_slice(&0.0f32.to_le_bytes());
+            continue;
+        }
+        let scale = (max_abs / cmax) as f32;
+        out.extend_from_slice(&scale.to_le_bytes());
+        let codes: Vec<i32> = chunk
+            .iter()
+            .map(|&v| {
+                let v = if v.is_finite() { v as f32 } else { 0.0 };
+                (v / scale).round().clamp(-cmax as f32, cmax as f32) as i32
+            })
+            .collect();
+        if bits == 4 {
+            let mut it = codes.into_iter();
+            while let Some(lo) = it.next() {
+                let hi = it.next().unwrap_or(0);
+                out.push(((lo as i8 as u8) & 0x0f) | (((hi as i8 as u8) & 0x0f) << 4));
+            }
+        } else {
+            for c in codes {
+                out.push(c as i8 as u8);
+            }
+        }
+    }
+    out
+}
+
+/// Decode a block produced by [`encode_quant`] at `off` within `buf`.
+pub fn decode_quant(buf: &[u8], off: usize) -> Result<Vec<f64>, String> {
+    if off + HEADER_LEN > buf.len() {
+        return Err("quant: short header".into());
+    }
+    let bits = buf[off];
+    let block = (u16::from_le_bytes(buf[off + 1..off + 3].try_into().unwrap()) as usize).max(1);
+    let n = u32::from_le_bytes(buf[off + 4..off + 8].try_into().unwrap()) as usize;
+    if n == 0 {
+        return Ok(Vec::new());
+    }
+    let mut out = Vec::with_capacity(n);
+    let mut p = off + HEADER_LEN;
+    let mut remaining = n;
+    while remaining > 0 {
+        let take = remaining.min(block);
+        if p + 4 > buf.len() {
+            return Err("quant: truncated scale".into());
+        }
+        let scale = f32::from_le_bytes(buf[p..p + 4].try_into().unwrap());
+        p += 4;
+        if scale == 0.0 {
+            out.extend(std::iter::repeat(0.0).take(take));
+            remaining -= take;
+            continue;
+        }
+        let code_bytes = if bits == 4 { (take + 1) / 2 } else { take };
+        if p + code_bytes > buf.len() {
+            return Err("quant: truncated codes".into());
+        }
+        if bits == 4 {
+            for i in 0..take {
+                let byte = buf[p + i / 2];
+                let nib = if i % 2 == 0 { byte & 0x0f } else { byte >> 4 };
+                let signed = ((nib as i8) << 4) >> 4;
+                out.push(signed as f64 * scale as f64);
+            }
+        } else {
+            for i in 0..take {
+                out.push((buf[p + i] as i8) as f64 * scale as f64);
+            }
+        }
+        p += code_bytes;
+        remaining -= take;
+    }
+    Ok(out)
+}
+
+/// Append an [`encode_quant`] block to `out`, 8-byte aligned, returning `(offset, element count)`.
+pub fn write_quant_le(out: &mut Vec<u8>, pos: &mut usize, vals: &[f64], bits: u8, block: usize) -> (u64, u32) {
+    *pos = (*pos + 7) & !7;
+    let off = *pos as u64;
+    let bytes = encode_quant(vals, bits, block);
+    ensure_cap(out, *pos + bytes.len());
+    out[*pos..*pos + bytes.len()].copy_from_slice(&bytes);
+    *pos += bytes.len();
+    (off, vals.len() as u32)
+}
@@ -0,0 +1,627 @@
+// Serializes an `MzML` back out to spec-conformant indexed mzML: every
+// `<spectrum>`/`<chromatogram>` start offset is recorded as it's written,
+// used to emit a fresh `<indexList>`, followed by `<indexListOffset>` and a
+// SHA-1 `<fileChecksum>` recomputed over everything written so far. This
+// mirrors `encode`/`decode`'s roundtrip guarantee for the crate's own BIN1
+// container, but for the XML document itself — useful once an `MzML` has
+// been slimmed or filtered and its original `index_list_offset`/
+// `file_checksum` no longer describe the bytes being emitted.
+//
+// `SpectrumSummary`/`Precursor` don't retain every field a real mzML
+// `<spectrum>`/`<precursor>` carries (native id strings, activation
+// method): those are written as a minimal schema-shaped placeholder rather
+// than fabricated content.
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use miniz_oxide::deflate::compress_to_vec_zlib;
+
+use crate::utilities::parse::cv_terms::CvTerm;
+use crate::utilities::parse::parse_mzml::{
+    AcquisitionSettings, ChromatogramSummary, CvEntry, CvPair, DataProcessing, FileDescription,
+    IndexOffset, InstrumentConfiguration, MzML, RefParamGroup, Run, Sample, Software,
+    SpectrumSummary,
+};
+use crate::utilities::parse::sha1::sha1_hex;
+
+pub fn write_mzml(mzml: &MzML, compress: bool) -> Vec<u8> {
+    let mut out = Vec::with_capacity(1 << 16);
+    out.extend_from_slice(b"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.extend_from_slice(b"<indexedmzML xmlns=\"http://psi.hupo.org/ms/mzml\">\n");
+    out.extend_from_slice(b"<mzML version=\"1.1.0\">\n");
+
+    write_cv_list(&mut out, &mzml.cv_list);
+    write_file_description(&mut out, mzml.file_description.as_ref());
+    write_ref_param_groups(&mut out, &mzml.referenceable_param_groups);
+    write_sample_list(&mut out, &mzml.sample_list);
+    write_software_list(&mut out, &mzml.software_list);
+    write_instrument_configurations(&mut out, &mzml.instrument_configurations);
+    write_data_processing_list(&mut out, &mzml.data_processing_list);
+    write_acquisition_settings_list(&mut out, &mzml.acquisition_settings_list);
+
+    let mut spectrum_offsets = Vec::new();
+    let mut chromatogram_offsets = Vec::new();
+    if let Some(run) = &mzml.run {
+        write_run(
+            &mut out,
+            run,
+            compress,
+            &mut spectrum_offsets,
+            &mut chromatogram_offsets,
+        );
+    }
+
+    out.extend_from_slice(b"</mzML>\n");
+
+    let index_list_offset = out.len() as u64;
+    write_index_list(&mut out, &spectrum_offsets, &chromatogram_offsets);
+    out.extend_from_slice(
+        format!("<indexListOffset>{index_list_offset}</indexListOffset>\n").as_bytes(),
+    );
+
+    // The mzML spec defines the checksum as the SHA-1 of every byte up to
+    // and including this opening tag, so it must be hashed before the
+    // digest itself is appended.
+    out.extend_from_slice(b"<fileChecksum>");
+    let digest = sha1_hex(&out);
+    out.extend_from_slice(digest.as_bytes());
+    out.extend_from_slice(b"</fileChecksum>\n");
+    out.extend_from_slice(b"</indexedmzML>\n");
+    out
+}
+
+fn esc(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn write_cv_param(out: &mut Vec<u8>, p: &CvPair) {
+    out.extend_from_slice(b"<cvParam");
+    if let Some(acc) = &p.accession {
+        out.extend_from_slice(format!(" accession=\"{}\"", esc(acc)).as_bytes());
+    }
+    out.extend_from_slice(format!(" name=\"{}\"", esc(&p.name)).as_bytes());
+    out.extend_from_slice(
+        format!(" value=\"{}\"", esc(p.value.as_deref().unwrap_or(""))).as_bytes(),
+    );
+    out.extend_from_slice(
+        format!(" cvRef=\"{}\"", esc(p.cv_ref.as_deref().unwrap_or("MS"))).as_bytes(),
+    );
+    if let Some(u) = &p.unit_name {
+        out.extend_from_slice(format!(" unitName=\"{}\"", esc(u)).as_bytes());
+    }
+    out.extend_from_slice(b"/>\n");
+}
+
+/// [`write_inline_cv`] for a [`CvTerm`], so call sites don't repeat its
+/// accession/name pair as literals.
+fn write_term_cv(
+    out: &mut Vec<u8>,
+    term: CvTerm,
+    value: Option<String>,
+    unit: Option<(&str, &str, &str)>,
+) {
+    write_inline_cv(out, term.accession(), term.canonical_name(), value, unit);
+}
+
+fn write_inline_cv(
+    out: &mut Vec<u8>,
+    accession: &str,
+    name: &str,
+    value: Option<String>,
+    unit: Option<(&str, &str, &str)>,
+) {
+    out.extend_from_slice(
+        format!(" <cvParam accession=\"{accession}\" name=\"{name}\" cvRef=\"MS\"").as_bytes(),
+    );
+    out.extend_from_slice(format!(" value=\"{}\"", esc(&value.unwrap_or_default())).as_bytes());
+    if let Some((unit_accession, unit_name, unit_cv_ref)) = unit {
+        out.extend_from_slice(
+            format!(
+                " unitAccession=\"{unit_accession}\" unitName=\"{unit_name}\" unitCvRef=\"{unit_cv_ref}\""
+            )
+            .as_bytes(),
+        );
+    }
+    out.extend_from_slice(b"/>\n");
+}
+
+fn write_cv_list(out: &mut Vec<u8>, list: &[CvEntry]) {
+    out.extend_from_slice(format!("<cvList count=\"{}\">\n", list.len()).as_bytes());
+    for cv in list {
+        out.extend_from_slice(format!("<cv id=\"{}\"", esc(&cv.id)).as_bytes());
+        if let Some(v) = &cv.full_name {
+            out.extend_from_slice(format!(" fullName=\"{}\"", esc(v)).as_bytes());
+        }
+        if let Some(v) = &cv.version {
+            out.extend_from_slice(format!(" version=\"{}\"", esc(v)).as_bytes());
+        }
+        if let Some(v) = &cv.uri {
+            out.extend_from_slice(format!(" URI=\"{}\"", esc(v)).as_bytes());
+        }
+        out.extend_from_slice(b"/>\n");
+    }
+    out.extend_from_slice(b"</cvList>\n");
+}
+
+fn write_file_description(out: &mut Vec<u8>, fd: Option<&FileDescription>) {
+    let Some(fd) = fd else {
+        return;
+    };
+    out.extend_from_slice(b"<fileDescription>\n<fileContent>\n");
+    for p in &fd.file_content {
+        write_cv_param(out, p);
+    }
+    out.extend_from_slice(b"</fileContent>\n");
+    if !fd.source_files.is_empty() {
+        out.extend_from_slice(
+            format!(
+                "<sourceFileList count=\"{}\">\n",
+                fd.source_files.len()
+            )
+            .as_bytes(),
+        );
+        for sf in &fd.source_files {
+            out.extend_from_slice(format!("<sourceFile id=\"{}\"", esc(&sf.id)).as_bytes());
+            if let Some(n) = &sf.name {
+                out.extend_from_slice(format!(" name=\"{}\"", esc(n)).as_bytes());
+            }
+            if let Some(l) = &sf.location {
+                out.extend_from_slice(format!(" location=\"{}\"", esc(l)).as_bytes());
+            }
+            out.extend_from_slice(b">\n");
+            for p in &sf.cv_params {
+                write_cv_param(out, p);
+            }
+            out.extend_from_slice(b"</sourceFile>\n");
+        }
+        out.extend_from_slice(b"</sourceFileList>\n");
+    }
+    out.extend_from_slice(b"</fileDescription>\n");
+}
+
+fn write_ref_param_groups(out: &mut Vec<u8>, groups: &[RefParamGroup]) {
+    if groups.is_empty() {
+        return;
+    }
+    out.extend_from_slice(
+        format!("<referenceableParamGroupList count=\"{}\">\n", groups.len()).as_bytes(),
+    );
+    for g in groups {
+        out.extend_from_slice(
+            format!("<referenceableParamGroup id=\"{}\">\n", esc(&g.id)).as_bytes(),
+        );
+        for p in &g.cv_params {
+            write_cv_param(out, p);
+        }
+        out.extend_from_slice(b"</referenceableParamGroup>\n");
+    }
+    out.extend_from_slice(b"</referenceableParamGroupList>\n");
+}
+
+fn write_sample_list(out: &mut Vec<u8>, samples: &[Sample]) {
+    if samples.is_empty() {
+        return;
+    }
+    out.extend_from_slice(format!("<sampleList count=\"{}\">\n", samples.len()).as_bytes());
+    for s in samples {
+        out.extend_from_slice(format!("<sample id=\"{}\"", esc(&s.id)).as_bytes());
+        if let Some(n) = &s.name {
+            out.extend_from_slice(format!(" name=\"{}\"", esc(n)).as_bytes());
+        }
+        out.extend_from_slice(b">\n");
+        for p in &s.cv_params {
+            write_cv_param(out, p);
+        }
+        out.extend_from_slice(b"</sample>\n");
+    }
+    out.extend_from_slice(b"</sampleList>\n");
+}
+
+fn write_software_list(out: &mut Vec<u8>, list: &[Software]) {
+    if list.is_empty() {
+        return;
+    }
+    out.extend_from_slice(format!("<softwareList count=\"{}\">\n", list.len()).as_bytes());
+    for s in list {
+        out.extend_from_slice(format!("<software id=\"{}\"", esc(&s.id)).as_bytes());
+        if let Some(v) = &s.version {
+            out.extend_from_slice(format!(" version=\"{}\"", esc(v)).as_bytes());
+        }
+        out.extend_from_slice(b">\n");
+        for p in &s.cv_params {
+            write_cv_param(out, p);
+        }
+        for (name, value) in &s.user_params {
+            out.extend_from_slice(format!("<userParam name=\"{}\"", esc(name)).as_bytes());
+            if let Some(v) = value {
+                out.extend_from_slice(format!(" value=\"{}\"", esc(v)).as_bytes());
+            }
+            out.extend_from_slice(b"/>\n");
+        }
+        out.extend_from_slice(b"</software>\n");
+    }
+    out.extend_from_slice(b"</softwareList>\n");
+}
+
+fn write_instrument_configurations(out: &mut Vec<u8>, list: &[InstrumentConfiguration]) {
+    if list.is_empty() {
+        return;
+    }
+    out.extend_from_slice(
+        format!(
+            "<instrumentConfigurationList count=\"{}\">\n",
+            list.len()
+        )
+        .as_bytes(),
+    );
+    for ic in list {
+        out.extend_from_slice(
+            format!("<instrumentConfiguration id=\"{}\">\n", esc(&ic.id)).as_bytes(),
+        );
+        if let Some(r) = &ic.ref_param_group {
+            out.extend_from_slice(
+                format!("<referenceableParamGroupRef ref=\"{}\"/>\n", esc(r)).as_bytes(),
+            );
+        }
+        for p in &ic.cv_params {
+            write_cv_param(out, p);
+        }
+        if !ic.components.is_empty() {
+            out.extend_from_slice(
+                format!("<componentList count=\"{}\">\n", ic.components.len()).as_bytes(),
+            );
+            for c in &ic.components {
+                out.extend_from_slice(format!("<{}", c.kind).as_bytes());
+                if let Some(o) = c.order {
+                    out.extend_from_slice(format!(" order=\"{o}\"").as_bytes());
+                }
+                out.extend_from_slice(b">\n");
+                for p in &c.cv_params {
+                    write_cv_param(out, p);
+                }
+                out.extend_from_slice(format!("</{}>\n", c.kind).as_bytes());
+            }
+            out.extend_from_slice(b"</componentList>\n");
+        }
+        if let Some(sref) = &ic.software_ref {
+            out.extend_from_slice(format!("<softwareRef ref=\"{}\"/>\n", esc(sref)).as_bytes());
+        }
+        out.extend_from_slice(b"</instrumentConfiguration>\n");
+    }
+    out.extend_from_slice(b"</instrumentConfigurationList>\n");
+}
+
+fn write_data_processing_list(out: &mut Vec<u8>, list: &[DataProcessing]) {
+    if list.is_empty() {
+        return;
+    }
+    out.extend_from_slice(
+        format!("<dataProcessingList count=\"{}\">\n", list.len()).as_bytes(),
+    );
+    for dp in list {
+        out.extend_from_slice(format!("<dataProcessing id=\"{}\">\n", esc(&dp.id)).as_bytes());
+        for (i, m) in dp.methods.iter().enumerate() {
+            out.extend_from_slice(
+                format!("<processingMethod order=\"{}\"", m.order.unwrap_or(i)).as_bytes(),
+            );
+            if let Some(sref) = &m.software_ref {
+                out.extend_from_slice(format!(" softwareRef=\"{}\"", esc(sref)).as_bytes());
+            }
+            out.extend_from_slice(b">\n");
+            for p in &m.cv_params {
+                write_cv_param(out, p);
+            }
+            out.extend_from_slice(b"</processingMethod>\n");
+        }
+        out.extend_from_slice(b"</dataProcessing>\n");
+    }
+    out.extend_from_slice(b"</dataProcessingList>\n");
+}
+
+fn write_acquisition_settings_list(out: &mut Vec<u8>, list: &[AcquisitionSettings]) {
+    if list.is_empty() {
+        return;
+    }
+    out.extend_from_slice(
+        format!(
+            "<acquisitionSettingsList count=\"{}\">\n",
+            list.len()
+        )
+        .as_bytes(),
+    );
+    for a in list {
+        out.extend_from_slice(format!("<acquisitionSettings id=\"{}\"", esc(&a.id)).as_bytes());
+        if let Some(iref) = &a.instrument_ref {
+            out.extend_from_slice(
+                format!(" instrumentConfigurationRef=\"{}\"", esc(iref)).as_bytes(),
+            );
+        }
+        out.extend_from_slice(b">\n");
+        for p in &a.cv_params {
+            write_cv_param(out, p);
+        }
+        out.extend_from_slice(b"</acquisitionSettings>\n");
+    }
+    out.extend_from_slice(b"</acquisitionSettingsList>\n");
+}
+
+fn write_run(
+    out: &mut Vec<u8>,
+    run: &Run,
+    compress: bool,
+    spectrum_offsets: &mut Vec<IndexOffset>,
+    chromatogram_offsets: &mut Vec<IndexOffset>,
+) {
+    out.extend_from_slice(format!("<run id=\"{}\"", esc(&run.id)).as_bytes());
+    if let Some(ts) = &run.start_time_stamp {
+        out.extend_from_slice(format!(" startTimeStamp=\"{}\"", esc(ts)).as_bytes());
+    }
+    if let Some(iref) = &run.default_instrument_configuration_ref {
+        out.extend_from_slice(
+            format!(" defaultInstrumentConfigurationRef=\"{}\"", esc(iref)).as_bytes(),
+        );
+    }
+    out.extend_from_slice(b">\n");
+
+    out.extend_from_slice(format!("<spectrumList count=\"{}\">\n", run.spectra.len()).as_bytes());
+    for s in &run.spectra {
+        let offset = out.len() as u64;
+        let id = format!("spectrum={}", s.index);
+        write_spectrum(out, s, &id, compress);
+        spectrum_offsets.push(IndexOffset {
+            id_ref: Some(id),
+            offset,
+        });
+    }
+    out.extend_from_slice(b"</spectrumList>\n");
+
+    if !run.chromatograms.is_empty() {
+        out.extend_from_slice(
+            format!(
+                "<chromatogramList count=\"{}\">\n",
+                run.chromatograms.len()
+            )
+            .as_bytes(),
+        );
+        for c in &run.chromatograms {
+            let offset = out.len() as u64;
+            write_chromatogram(out, c, compress);
+            chromatogram_offsets.push(IndexOffset {
+                id_ref: Some(c.id.clone()),
+                offset,
+            });
+        }
+        out.extend_from_slice(b"</chromatogramList>\n");
+    }
+
+    out.extend_from_slice(b"</run>\n");
+}
+
+fn write_spectrum(out: &mut Vec<u8>, s: &SpectrumSummary, id: &str, compress: bool) {
+    out.extend_from_slice(format!("<spectrum index=\"{}\"", s.index).as_bytes());
+    out.extend_from_slice(format!(" id=\"{}\"", esc(id)).as_bytes());
+    out.extend_from_slice(format!(" defaultArrayLength=\"{}\">\n", s.array_length).as_bytes());
+
+    if let Some(level) = s.ms_level {
+        write_term_cv(out, CvTerm::MsLevel, Some(level.to_string()), None);
+    }
+    match s.spectrum_type {
+        Some(0) => write_term_cv(out, CvTerm::ProfileSpectrum, None, None),
+        Some(1) => write_term_cv(out, CvTerm::CentroidSpectrum, None, None),
+        _ => {}
+    }
+    match s.polarity {
+        Some(0) => write_term_cv(out, CvTerm::PositiveScan, None, None),
+        Some(1) => write_term_cv(out, CvTerm::NegativeScan, None, None),
+        _ => {}
+    }
+    if let Some(tic) = s.total_ion_current {
+        write_term_cv(out, CvTerm::TotalIonCurrent, Some(format!("{tic}")), None);
+    }
+    if let Some(bpi) = s.base_peak_intensity {
+        write_term_cv(out, CvTerm::BasePeakIntensity, Some(format!("{bpi}")), None);
+    }
+    if let Some(bpmz) = s.base_peak_mz {
+        write_term_cv(out, CvTerm::BasePeakMz, Some(format!("{bpmz}")), None);
+    }
+
+    let has_scan_window =
+        s.scan_window_lower_limit.is_some() || s.scan_window_upper_limit.is_some();
+    if s.retention_time.is_some() || has_scan_window {
+        out.extend_from_slice(b"<scanList count=\"1\">\n<scan>\n");
+        if let Some(rt) = s.retention_time {
+            write_term_cv(
+                out,
+                CvTerm::ScanStartTime,
+                Some(format!("{rt}")),
+                Some(("UO:0000031", "minute", "UO")),
+            );
+        }
+        if has_scan_window {
+            out.extend_from_slice(b"<scanWindowList count=\"1\">\n<scanWindow>\n");
+            if let Some(v) = s.scan_window_lower_limit {
+                write_term_cv(out, CvTerm::ScanWindowLowerLimit, Some(format!("{v}")), None);
+            }
+            if let Some(v) = s.scan_window_upper_limit {
+                write_term_cv(out, CvTerm::ScanWindowUpperLimit, Some(format!("{v}")), None);
+            }
+            out.extend_from_slice(b"</scanWindow>\n</scanWindowList>\n");
+        }
+        out.extend_from_slice(b"</scan>\n</scanList>\n");
+    }
+
+    if let Some(p) = &s.precursor {
+        out.extend_from_slice(b"<precursorList count=\"1\">\n<precursor>\n");
+        let has_window = p.isolation_window_target_mz.is_some()
+            || p.isolation_window_lower_offset.is_some()
+            || p.isolation_window_upper_offset.is_some();
+        if has_window {
+            out.extend_from_slice(b"<isolationWindow>\n");
+            if let Some(v) = p.isolation_window_target_mz {
+                write_term_cv(
+                    out,
+                    CvTerm::IsolationWindowTargetMz,
+                    Some(format!("{v}")),
+                    None,
+                );
+            }
+            if let Some(v) = p.isolation_window_lower_offset {
+                write_term_cv(
+                    out,
+                    CvTerm::IsolationWindowLowerOffset,
+                    Some(format!("{v}")),
+                    None,
+                );
+            }
+            if let Some(v) = p.isolation_window_upper_offset {
+                write_term_cv(
+                    out,
+                    CvTerm::IsolationWindowUpperOffset,
+                    Some(format!("{v}")),
+                    None,
+                );
+            }
+            out.extend_from_slice(b"</isolationWindow>\n");
+        }
+        if let Some(v) = p.selected_ion_mz {
+            out.extend_from_slice(b"<selectedIonList count=\"1\">\n<selectedIon>\n");
+            write_term_cv(out, CvTerm::SelectedIonMz, Some(format!("{v}")), None);
+            out.extend_from_slice(b"</selectedIon>\n</selectedIonList>\n");
+        }
+        // `Precursor` doesn't retain an activation method, so this is a
+        // minimal schema-shaped placeholder rather than fabricated content.
+        out.extend_from_slice(b"<activation/>\n");
+        out.extend_from_slice(b"</precursor>\n</precursorList>\n");
+    }
+
+    write_binary_data_array_list(out, s.mz_array.as_ref(), s.intensity_array.as_ref(), compress);
+
+    out.extend_from_slice(b"</spectrum>\n");
+}
+
+fn write_chromatogram(out: &mut Vec<u8>, c: &ChromatogramSummary, compress: bool) {
+    out.extend_from_slice(format!("<chromatogram index=\"{}\"", c.index).as_bytes());
+    out.extend_from_slice(format!(" id=\"{}\"", esc(&c.id)).as_bytes());
+    out.extend_from_slice(format!(" defaultArrayLength=\"{}\">\n", c.array_length).as_bytes());
+
+    let count = c.time_array.is_some() as usize + c.intensity_array.is_some() as usize;
+    if count > 0 {
+        out.extend_from_slice(format!("<binaryDataArrayList count=\"{count}\">\n").as_bytes());
+        if let Some(times) = &c.time_array {
+            let raw = f64_le_bytes(times);
+            let (b64, is_zlib) = encode_binary_payload(&raw, compress);
+            write_binary_data_array(out, CvTerm::TimeArray, &b64, is_zlib, true);
+        }
+        if let Some(ints) = &c.intensity_array {
+            let raw = f32_le_bytes(ints);
+            let (b64, is_zlib) = encode_binary_payload(&raw, compress);
+            write_binary_data_array(out, CvTerm::IntensityArray, &b64, is_zlib, false);
+        }
+        out.extend_from_slice(b"</binaryDataArrayList>\n");
+    }
+    out.extend_from_slice(b"</chromatogram>\n");
+}
+
+fn write_binary_data_array_list(
+    out: &mut Vec<u8>,
+    mz: Option<&Vec<f64>>,
+    inten: Option<&Vec<f32>>,
+    compress: bool,
+) {
+    let count = mz.is_some() as usize + inten.is_some() as usize;
+    if count == 0 {
+        return;
+    }
+    out.extend_from_slice(format!("<binaryDataArrayList count=\"{count}\">\n").as_bytes());
+    if let Some(mzs) = mz {
+        let raw = f64_le_bytes(mzs);
+        let (b64, is_zlib) = encode_binary_payload(&raw, compress);
+        write_binary_data_array(out, CvTerm::MzArray, &b64, is_zlib, true);
+    }
+    if let Some(ints) = inten {
+        let raw = f32_le_bytes(ints);
+        let (b64, is_zlib) = encode_binary_payload(&raw, compress);
+        write_binary_data_array(out, CvTerm::IntensityArray, &b64, is_zlib, false);
+    }
+    out.extend_from_slice(b"</binaryDataArrayList>\n");
+}
+
+fn write_binary_data_array(out: &mut Vec<u8>, kind: CvTerm, b64: &str, is_zlib: bool, is_f64: bool) {
+    out.extend_from_slice(format!("<binaryDataArray encodedLength=\"{}\">\n", b64.len()).as_bytes());
+    if is_f64 {
+        write_term_cv(out, CvTerm::Float64, None, None);
+    } else {
+        write_term_cv(out, CvTerm::Float32, None, None);
+    }
+    if is_zlib {
+        write_term_cv(out, CvTerm::ZlibCompression, None, None);
+    } else {
+        write_term_cv(out, CvTerm::NoCompression, None, None);
+    }
+    write_term_cv(out, kind, None, None);
+    out.extend_from_slice(b"<binary>");
+    out.extend_from_slice(b64.as_bytes());
+    out.extend_from_slice(b"</binary>\n");
+    out.extend_from_slice(b"</binaryDataArray>\n");
+}
+
+fn write_index_list(out: &mut Vec<u8>, spectra: &[IndexOffset], chromatograms: &[IndexOffset]) {
+    let count = (!spectra.is_empty()) as usize + (!chromatograms.is_empty()) as usize;
+    out.extend_from_slice(format!("<indexList count=\"{count}\">\n").as_bytes());
+    if !spectra.is_empty() {
+        write_index(out, "spectrum", spectra);
+    }
+    if !chromatograms.is_empty() {
+        write_index(out, "chromatogram", chromatograms);
+    }
+    out.extend_from_slice(b"</indexList>\n");
+}
+
+fn write_index(out: &mut Vec<u8>, name: &str, offsets: &[IndexOffset]) {
+    out.extend_from_slice(
+        format!("<index name=\"{name}\" count=\"{}\">\n", offsets.len()).as_bytes(),
+    );
+    for o in offsets {
+        out.extend_from_slice(b"<offset");
+        if let Some(id_ref) = &o.id_ref {
+            out.extend_from_slice(format!(" idRef=\"{}\"", esc(id_ref)).as_bytes());
+        }
+        out.extend_from_slice(format!(">{}</offset>\n", o.offset).as_bytes());
+    }
+    out.extend_from_slice(b"</index>\n");
+}
+
+fn f64_le_bytes(vals: &[f64]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(vals.len() * 8);
+    for v in vals {
+        out.extend_from_slice(&v.to_le_bytes());
+    }
+    out
+}
+
+fn f32_le_bytes(vals: &[f32]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(vals.len() * 4);
+    for v in vals {
+        out.extend_from_slice(&v.to_le_bytes());
+    }
+    out
+}
+
+fn encode_binary_payload(raw: &[u8], compress: bool) -> (String, bool) {
+    if compress {
+        (STANDARD.encode(compress_to_vec_zlib(raw, 6)), true)
+    } else {
+        (STANDARD.encode(raw), false)
+    }
+}
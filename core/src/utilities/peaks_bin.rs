@@ -0,0 +1,154 @@
+use crate::utilities::parse::helper::{set_f64_at, set_u32_at, set_u64_at};
+
+/// Compact columnar binary encoding for batches of peaks, used by
+/// `get_peaks_from_eic_bin`/`get_peaks_from_chrom_bin` as a lower-overhead
+/// alternative to the `serde_json::Value` arrays built by
+/// `get_peaks_from_eic`/`get_peaks_from_chrom`. For tens of thousands of
+/// peaks, building and serializing one JSON object per record dominates
+/// runtime; this format lets a caller read each numeric column straight
+/// into a typed array and skip JSON parsing entirely.
+///
+/// Layout:
+/// - a 64-byte header: 4-byte magic (`PKBE` for eic, `PKBC` for chrom),
+///   `u32` schema version, `u32` record count, `u32` column count, up to
+///   16 column field-code bytes (see the `FIELD_*` constants), then three
+///   `u64` byte offsets for the columns table, the id index and the id
+///   bytes, in that order;
+/// - the columns themselves, one after another in the order listed in the
+///   header, each a tightly packed run of `n_records` little-endian `f64`s;
+/// - an id index, one `(u32 offset, u32 len)` pair per record, pointing
+///   into the trailing id bytes;
+/// - the concatenated UTF-8 id bytes.
+///
+/// New fields can be appended to a record by adding a field code and
+/// growing the column count; a reader that only understands the field
+/// codes it was built against can keep reading the columns it recognizes
+/// and ignore the rest.
+pub const FIELD_RT: u8 = 1;
+pub const FIELD_FROM: u8 = 2;
+pub const FIELD_TO: u8 = 3;
+pub const FIELD_INTENSITY: u8 = 4;
+pub const FIELD_INTEGRAL: u8 = 5;
+pub const FIELD_NOISE: u8 = 6;
+pub const FIELD_MZ: u8 = 7;
+pub const FIELD_ORT: u8 = 8;
+pub const FIELD_INDEX: u8 = 9;
+
+const MAGIC_EIC: &[u8; 4] = b"PKBE";
+const MAGIC_CHROM: &[u8; 4] = b"PKBC";
+const SCHEMA_VERSION: u32 = 1;
+const HEADER_LEN: usize = 64;
+const MAX_COLUMNS: usize = 16;
+
+fn encode_peaks_bin(magic: &[u8; 4], columns: &[(u8, &[f64])], ids: &[String]) -> Vec<u8> {
+    let n_records = ids.len();
+    let n_columns = columns.len();
+    assert!(
+        n_columns <= MAX_COLUMNS,
+        "peaks_bin: too many columns for a 64-byte header"
+    );
+    for (_, values) in columns {
+        assert_eq!(
+            values.len(),
+            n_records,
+            "peaks_bin: column length must match record count"
+        );
+    }
+
+    let columns_offset = HEADER_LEN;
+    let id_table_offset = columns_offset + n_columns * n_records * 8;
+    let id_bytes_offset = id_table_offset + n_records * 8;
+
+    let mut id_bytes: Vec<u8> = Vec::new();
+    let mut id_index: Vec<(u32, u32)> = Vec::with_capacity(n_records);
+    for id in ids {
+        let bytes = id.as_bytes();
+        id_index.push((id_bytes.len() as u32, bytes.len() as u32));
+        id_bytes.extend_from_slice(bytes);
+    }
+
+    let mut out = vec![0u8; id_bytes_offset + id_bytes.len()];
+
+    out[0..4].copy_from_slice(magic);
+    set_u32_at(&mut out, 4, SCHEMA_VERSION);
+    set_u32_at(&mut out, 8, n_records as u32);
+    set_u32_at(&mut out, 12, n_columns as u32);
+    for (i, (code, _)) in columns.iter().enumerate() {
+        out[16 + i] = *code;
+    }
+    set_u64_at(&mut out, 32, columns_offset as u64);
+    set_u64_at(&mut out, 40, id_table_offset as u64);
+    set_u64_at(&mut out, 48, id_bytes_offset as u64);
+
+    let mut cur = columns_offset;
+    for (_, values) in columns {
+        for v in values.iter() {
+            set_f64_at(&mut out, cur, *v);
+            cur += 8;
+        }
+    }
+
+    let mut cur = id_table_offset;
+    for (off, len) in &id_index {
+        set_u32_at(&mut out, cur, *off);
+        set_u32_at(&mut out, cur + 4, *len);
+        cur += 8;
+    }
+
+    out[id_bytes_offset..].copy_from_slice(&id_bytes);
+    out
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn encode_eic_peaks_bin(
+    ids: &[String],
+    mz: &[f64],
+    ort: &[f64],
+    rt: &[f64],
+    from: &[f64],
+    to: &[f64],
+    intensity: &[f64],
+    integral: &[f64],
+    noise: &[f64],
+) -> Vec<u8> {
+    encode_peaks_bin(
+        MAGIC_EIC,
+        &[
+            (FIELD_RT, rt),
+            (FIELD_FROM, from),
+            (FIELD_TO, to),
+            (FIELD_INTENSITY, intensity),
+            (FIELD_INTEGRAL, integral),
+            (FIELD_NOISE, noise),
+            (FIELD_MZ, mz),
+            (FIELD_ORT, ort),
+        ],
+        ids,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn encode_chrom_peaks_bin(
+    ids: &[String],
+    index: &[f64],
+    ort: &[f64],
+    rt: &[f64],
+    from: &[f64],
+    to: &[f64],
+    intensity: &[f64],
+    integral: &[f64],
+) -> Vec<u8> {
+    encode_peaks_bin(
+        MAGIC_CHROM,
+        &[
+            (FIELD_INDEX, index),
+            (FIELD_ORT, ort),
+            (FIELD_RT, rt),
+            (FIELD_FROM, from),
+            (FIELD_TO, to),
+            (FIELD_INTENSITY, intensity),
+            (FIELD_INTEGRAL, integral),
+        ],
+        ids,
+    )
+}
@@ -0,0 +1,145 @@
+/// Approximate streaming quantile summary (Greenwald-Khanna style), so a
+/// percentile can be estimated over a large series without sorting it.
+///
+/// Each inserted value is kept as a tuple `(value, rmin, rmax)` bounding the
+/// true rank of that value among everything seen so far, within `epsilon *
+/// n`. Tuples whose neighbors already keep that guarantee are periodically
+/// dropped by [`compress`](Self::compress), which bounds memory to roughly
+/// `O(1/epsilon * log(epsilon * n))` entries.
+#[derive(Clone, Debug)]
+pub struct QuantileSummary {
+    epsilon: f64,
+    n: usize,
+    items: Vec<(f64, usize, usize)>,
+}
+
+impl QuantileSummary {
+    pub fn new(epsilon: f64) -> Self {
+        Self {
+            epsilon: epsilon.max(1e-6),
+            n: 0,
+            items: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.n
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+
+    /// Insert `v`, widening the allowed rank band by the summary's `epsilon`.
+    pub fn update(&mut self, v: f64) {
+        self.update_weighted(v, 1.0);
+    }
+
+    /// Insert `v` with a rank weight of `weight`, rounded to the nearest
+    /// whole multiplicity (minimum 1): a high-intensity sample counts as
+    /// several copies of `v` toward the estimated rank instead of one, so
+    /// an intensity-weighted quantile (e.g. a weighted median m/z) can be
+    /// built by feeding in each observation's own weight. Non-finite `v`
+    /// or non-positive `weight` are ignored.
+    pub fn update_weighted(&mut self, v: f64, weight: f64) {
+        if !v.is_finite() || !weight.is_finite() || weight <= 0.0 {
+            return;
+        }
+        let w = (weight.round() as usize).max(1);
+        let pos = self.items.partition_point(|&(val, _, _)| val < v);
+        let rmin_pred = if pos == 0 { 0 } else { self.items[pos - 1].1 };
+        let band = (2.0 * self.epsilon * (self.n + w) as f64).floor() as usize;
+        let rmin = rmin_pred + w;
+        let rmax = rmin + band;
+
+        for t in self.items[pos..].iter_mut() {
+            t.1 += w;
+            t.2 += w;
+        }
+        self.items.insert(pos, (v, rmin, rmax));
+        self.n += w;
+
+        let capacity_hint = ((1.0 / (2.0 * self.epsilon)).ceil() as usize).max(8) * 4;
+        if self.items.len() > capacity_hint {
+            self.compress();
+        }
+    }
+
+    /// Drop any tuple whose neighbors still keep the merged rank gap within
+    /// `2 * epsilon * n`.
+    pub fn compress(&mut self) {
+        if self.items.len() < 3 {
+            return;
+        }
+        let threshold = (2.0 * self.epsilon * self.n as f64).floor() as usize;
+        let mut out = Vec::with_capacity(self.items.len());
+        out.push(self.items[0]);
+        let last = self.items.len() - 1;
+        for i in 1..last {
+            let cur = self.items[i];
+            let next = self.items[i + 1];
+            if next.2.saturating_sub(cur.1) <= threshold {
+                continue; // cur's rank is already implied by its neighbors
+            }
+            out.push(cur);
+        }
+        out.push(self.items[last]);
+        self.items = out;
+    }
+
+    /// Merge `other` into `self`, so per-partition summaries built over
+    /// disjoint slices of a series can be combined into one summary covering
+    /// the whole series. Tuples from `self` keep their own `rmin` and gain
+    /// `other`'s total count on their `rmax`; tuples from `other` have both
+    /// `rmin` and `rmax` offset by `self`'s total count. The merged list is
+    /// then run through [`compress`](Self::compress) to bound its size again.
+    pub fn merge(self, other: Self) -> Self {
+        let epsilon = self.epsilon.max(other.epsilon);
+        let n1 = self.n;
+        let n2 = other.n;
+        let mut items = Vec::with_capacity(self.items.len() + other.items.len());
+        let (mut i, mut j) = (0, 0);
+        while i < self.items.len() && j < other.items.len() {
+            if self.items[i].0 <= other.items[j].0 {
+                let (v, rmin, rmax) = self.items[i];
+                items.push((v, rmin, rmax + n2));
+                i += 1;
+            } else {
+                let (v, rmin, rmax) = other.items[j];
+                items.push((v, rmin + n1, rmax + n1));
+                j += 1;
+            }
+        }
+        for &(v, rmin, rmax) in &self.items[i..] {
+            items.push((v, rmin, rmax + n2));
+        }
+        for &(v, rmin, rmax) in &other.items[j..] {
+            items.push((v, rmin + n1, rmax + n1));
+        }
+
+        let mut merged = Self {
+            epsilon,
+            n: n1 + n2,
+            items,
+        };
+        merged.compress();
+        merged
+    }
+
+    /// Estimate the `phi`-quantile (`phi` in `[0, 1]`). Returns `f64::NAN`
+    /// if nothing has been inserted.
+    pub fn query(&self, phi: f64) -> f64 {
+        if self.items.is_empty() {
+            return f64::NAN;
+        }
+        let phi = phi.clamp(0.0, 1.0);
+        let target = phi * self.n as f64;
+        let eps_n = self.epsilon * self.n as f64;
+        for &(v, _, rmax) in &self.items {
+            if rmax as f64 >= target - eps_n {
+                return v;
+            }
+        }
+        self.items[self.items.len() - 1].0
+    }
+}
@@ -1,3 +1,4 @@
+use crate::utilities::closest_index;
 use crate::utilities::sgg::{SggOptions, sgg};
 use crate::utilities::structs::DataXY;
 
@@ -5,6 +6,14 @@ use crate::utilities::structs::DataXY;
 pub struct ScanPeaksOptions {
     pub epsilon: f64,
     pub window_size: usize,
+    /// Savitzky-Golay polynomial order used for both the `derivative: 0`
+    /// smoothing pass and the `derivative: 1` slope pass. Clamped to `2..=5`.
+    pub polynomial: usize,
+    /// When `true`, the window-size list is derived per region from the
+    /// local dominant peak width instead of a fixed list, and the consensus
+    /// vote is counted against the windows applicable to that region rather
+    /// than the full global set.
+    pub adaptive: Option<bool>,
 }
 
 impl Default for ScanPeaksOptions {
@@ -12,10 +21,17 @@ impl Default for ScanPeaksOptions {
         Self {
             epsilon: 1e-5,
             window_size: 11,
+            polynomial: 3,
+            adaptive: None,
         }
     }
 }
 
+#[inline(always)]
+fn clamp_polynomial(p: usize) -> usize {
+    p.clamp(2, 5)
+}
+
 const DEFAULT_WINDOW_SIZES: &[usize] = &[5, 7, 9, 11, 13, 15, 17, 19, 21];
 
 pub fn scan_for_peaks(data: &DataXY, options: Option<ScanPeaksOptions>) -> Vec<f64> {
@@ -33,25 +49,31 @@ pub fn scan_for_peaks_across_windows(
     }
 
     let opts = options.unwrap_or_default();
-    let wss = window_sizes.unwrap_or(DEFAULT_WINDOW_SIZES);
+    let eps = opts.epsilon as f32;
+    let poly = clamp_polynomial(opts.polynomial);
+    let adaptive = opts.adaptive.unwrap_or(false);
 
-    let mut sizes: Vec<usize> = Vec::new();
-    for &ws in wss {
-        if let Some(eff) = odd_at_most(ws, n) {
-            sizes.push(eff);
+    let (sizes, local_targets): (Vec<usize>, Vec<usize>) = if adaptive {
+        adaptive_window_sizes(data, eps, poly)
+    } else {
+        let wss = window_sizes.unwrap_or(DEFAULT_WINDOW_SIZES);
+        let mut sizes = Vec::new();
+        for &ws in wss {
+            if let Some(eff) = odd_at_most(ws, n) {
+                sizes.push(eff);
+            }
         }
-    }
+        (sizes, Vec::new())
+    };
     if sizes.is_empty() {
         return Vec::new();
     }
 
-    let eps = opts.epsilon as f32;
-
     let mut all_x: Vec<f64> = Vec::new();
     let mut all_y: Vec<f32> = Vec::new();
 
     for ws in sizes.iter().copied() {
-        let cands = scan_one_ws(data, ws, eps);
+        let cands = scan_one_ws(data, ws, eps, poly);
         for (x, y) in cands {
             all_x.push(x);
             all_y.push(y);
@@ -64,15 +86,11 @@ pub fn scan_for_peaks_across_windows(
 
     let rt_tol = time_tolerance(&data.x);
     let m = sizes.len();
-    let need = if m == 1 {
-        1
-    } else {
-        ((m as f64) * 0.6).ceil() as usize
-    };
 
     let mut centers: Vec<f64> = Vec::new();
     let mut counts: Vec<usize> = Vec::new();
     let mut peaks_y: Vec<f32> = Vec::new();
+    let mut needs: Vec<usize> = Vec::new();
 
     for i in 0..all_x.len() {
         let x = all_x[i];
@@ -93,15 +111,26 @@ pub fn scan_for_peaks_across_windows(
             k += 1;
         }
         if !found {
+            // The consensus threshold is evaluated against the windows that
+            // are actually applicable to this candidate's own region (when
+            // adaptive), not the full global window set, so a narrow feature
+            // isn't discarded merely because broad-window passes missed it.
+            let need = if adaptive {
+                let idx = closest_index(&data.x, x);
+                applicable_need(&sizes, local_targets[idx])
+            } else {
+                ((m as f64) * 0.6).ceil().max(1.0) as usize
+            };
             centers.push(x);
             counts.push(1);
             peaks_y.push(y);
+            needs.push(need);
         }
     }
 
     let mut kept: Vec<(f64, f32)> = Vec::new();
     for i in 0..centers.len() {
-        if counts[i] >= need {
+        if counts[i] >= needs[i] {
             kept.push((centers[i], peaks_y[i]));
         }
     }
@@ -133,7 +162,7 @@ pub fn scan_for_peaks_across_windows(
     out
 }
 
-fn scan_one_ws(data: &DataXY, ws_in: usize, eps: f32) -> Vec<(f64, f32)> {
+fn scan_one_ws(data: &DataXY, ws_in: usize, eps: f32, polynomial: usize) -> Vec<(f64, f32)> {
     let n = data.x.len();
     if n < 3 {
         return Vec::new();
@@ -143,12 +172,12 @@ fn scan_one_ws(data: &DataXY, ws_in: usize, eps: f32) -> Vec<(f64, f32)> {
     let s0 = SggOptions {
         window_size: ws,
         derivative: 0,
-        polynomial: 3,
+        polynomial,
     };
     let s1 = SggOptions {
         window_size: ws,
         derivative: 1,
-        polynomial: 3,
+        polynomial,
     };
     let ys_sm: Vec<f32> = sgg(&data.y, &data.x, s0);
     let dy: Vec<f32> = sgg(&data.y, &data.x, s1);
@@ -280,6 +309,118 @@ fn odd_at_most(ws: usize, n: usize) -> Option<usize> {
     if w >= 5 && w <= n { Some(w) } else { None }
 }
 
+/// Per-region window-size set for adaptive mode: estimates the local
+/// dominant peak width from the spacing between first-derivative zero
+/// crossings at the smallest usable window, converts that width to a sample
+/// count at each position, then scales it by `{0.6, 1.0, 1.6}` to get a small
+/// window set tailored to the feature actually present there. Returns the
+/// deduplicated union of those sizes (what gets scanned) alongside a
+/// per-sample target window size (what `applicable_need` compares against).
+fn adaptive_window_sizes(data: &DataXY, eps: f32, polynomial: usize) -> (Vec<usize>, Vec<usize>) {
+    let n = data.x.len();
+    let widths = local_dominant_widths(data, eps, polynomial);
+
+    let mut targets: Vec<usize> = Vec::with_capacity(n);
+    for i in 0..n {
+        let dx = local_dx(&data.x, i);
+        let samples = if dx > 0.0 {
+            (widths[i] / dx).round().max(5.0)
+        } else {
+            11.0
+        };
+        targets.push(odd_at_most(samples as usize, n).unwrap_or_else(|| 5.min(n)));
+    }
+
+    let mut sizes: Vec<usize> = Vec::new();
+    for &t in &targets {
+        for &mult in &[0.6, 1.0, 1.6] {
+            let scaled = ((t as f64) * mult).round() as usize;
+            if let Some(eff) = odd_at_most(scaled, n) {
+                if !sizes.contains(&eff) {
+                    sizes.push(eff);
+                }
+            }
+        }
+    }
+    sizes.sort_unstable();
+    (sizes, targets)
+}
+
+/// Number of scanned `sizes` within a factor of 2 of `target`, i.e. those
+/// actually applicable to a region whose dominant width suggested `target`,
+/// converted to the usual 0.6-of-applicable consensus requirement.
+fn applicable_need(sizes: &[usize], target: usize) -> usize {
+    let lo = (target as f64 * 0.5).floor() as usize;
+    let hi = (target as f64 * 2.0).ceil() as usize;
+    let applicable = sizes.iter().filter(|&&s| s >= lo && s <= hi).count().max(1);
+    ((applicable as f64) * 0.6).ceil().max(1.0) as usize
+}
+
+/// Local dominant peak width (in `x` units) at each sample, estimated from
+/// the spacing between successive first-derivative zero crossings at the
+/// smallest usable window. Samples between two crossings share that
+/// crossing pair's spacing; samples outside any bracketed pair (or when
+/// fewer than two crossings exist at all) fall back to the mean of the
+/// widths that could be estimated, or a window-11-sized span of the trace.
+fn local_dominant_widths(data: &DataXY, eps: f32, polynomial: usize) -> Vec<f64> {
+    let n = data.x.len();
+    let ws = odd_at_most(5, n).unwrap_or(5.min(n).max(3));
+    let s1 = SggOptions {
+        window_size: ws,
+        derivative: 1,
+        polynomial,
+    };
+    let dy = sgg(&data.y, &data.x, s1);
+
+    let mut crossings: Vec<usize> = Vec::new();
+    for k in 0..n.saturating_sub(1) {
+        let a = sign_eps(dy[k], eps);
+        let b = sign_eps(dy[k + 1], eps);
+        if a != 0 && b != 0 && a != b {
+            crossings.push(k);
+        }
+    }
+
+    let mut widths = vec![f64::NAN; n];
+    for w in crossings.windows(2) {
+        let (a, b) = (w[0], w[1]);
+        let width = (data.x[b] - data.x[a]).abs().max(f64::EPSILON);
+        for slot in widths.iter_mut().take(b + 1).skip(a) {
+            *slot = width;
+        }
+    }
+
+    let fallback = {
+        let known: Vec<f64> = widths.iter().copied().filter(|v| v.is_finite()).collect();
+        if known.is_empty() {
+            let dx_avg = (data.x[n - 1] - data.x[0]).abs() / (n as f64 - 1.0).max(1.0);
+            11.0 * dx_avg.max(f64::EPSILON)
+        } else {
+            known.iter().sum::<f64>() / known.len() as f64
+        }
+    };
+    for w in widths.iter_mut() {
+        if !w.is_finite() {
+            *w = fallback;
+        }
+    }
+    widths
+}
+
+/// Local sample spacing at `i`, averaged across the neighbor(s) present.
+fn local_dx(x: &[f64], i: usize) -> f64 {
+    let n = x.len();
+    if n < 2 {
+        return 0.0;
+    }
+    let lo = i.saturating_sub(1);
+    let hi = (i + 1).min(n - 1);
+    if hi == lo {
+        return 0.0;
+    }
+    (x[hi] - x[lo]).abs() / ((hi - lo) as f64)
+}
+
 #[inline(always)]
 fn sign_eps(v: f32, eps: f32) -> i8 {
     if v > eps {
@@ -0,0 +1,106 @@
+use crate::utilities::calculate_eic::lower_bound;
+use crate::utilities::parse::parse_mzml::{MzML, SpectrumSummary};
+use crate::utilities::structs::FromTo;
+
+/// A composable predicate over a [`SpectrumSummary`], built once and
+/// evaluated against each spectrum in a run. Gives callers a declarative
+/// alternative to ad hoc field checks scattered across call sites, in the
+/// same spirit as the crate's small selector types (`FromTo`, `Roi`).
+#[derive(Clone, Debug)]
+pub enum SpectrumSelector {
+    MsLevel(u8),
+    RetentionTimeRange(f64, f64),
+    PrecursorMzWindow(f64, f64),
+    Polarity(u8),
+    And(Box<SpectrumSelector>, Box<SpectrumSelector>),
+    Or(Box<SpectrumSelector>, Box<SpectrumSelector>),
+    Not(Box<SpectrumSelector>),
+}
+
+impl SpectrumSelector {
+    pub fn and(self, other: SpectrumSelector) -> Self {
+        SpectrumSelector::And(Box::new(self), Box::new(other))
+    }
+
+    pub fn or(self, other: SpectrumSelector) -> Self {
+        SpectrumSelector::Or(Box::new(self), Box::new(other))
+    }
+
+    pub fn not(self) -> Self {
+        SpectrumSelector::Not(Box::new(self))
+    }
+
+    pub fn matches(&self, s: &SpectrumSummary) -> bool {
+        match self {
+            SpectrumSelector::MsLevel(level) => s.ms_level == Some(*level),
+            SpectrumSelector::RetentionTimeRange(from, to) => {
+                matches!(s.retention_time, Some(rt) if rt >= *from && rt <= *to)
+            }
+            SpectrumSelector::PrecursorMzWindow(target, tol) => s
+                .precursor
+                .as_ref()
+                .and_then(|p| p.selected_ion_mz.or(p.isolation_window_target_mz))
+                .map(|mz| (mz - target).abs() <= *tol)
+                .unwrap_or(false),
+            SpectrumSelector::Polarity(pol) => s.polarity == Some(*pol),
+            SpectrumSelector::And(a, b) => a.matches(s) && b.matches(s),
+            SpectrumSelector::Or(a, b) => a.matches(s) || b.matches(s),
+            SpectrumSelector::Not(a) => !a.matches(s),
+        }
+    }
+}
+
+/// Indices, in scan order, of every spectrum in `mzml.run` matching `selector`.
+pub fn select_spectrum_indices(mzml: &MzML, selector: &SpectrumSelector) -> Vec<usize> {
+    let Some(run) = &mzml.run else {
+        return Vec::new();
+    };
+    run.spectra
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| selector.matches(s))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Borrowed spectra, in scan order, matching `selector`.
+pub fn select_spectra<'a>(
+    mzml: &'a MzML,
+    selector: &'a SpectrumSelector,
+) -> impl Iterator<Item = &'a SpectrumSummary> {
+    mzml.run
+        .iter()
+        .flat_map(|r| r.spectra.iter())
+        .filter(move |s| selector.matches(s))
+}
+
+/// Extracted-ion chromatogram over `[mz - tol, mz + tol]`, summing
+/// `intensity_array` entries whose `mz_array` falls in that window across
+/// every MS1 scan within `rt_range`. Built on [`select_spectra`] rather than
+/// `calculate_eic_from_mzml`'s direct scan so callers can compose the MS1 +
+/// retention-time predicate with additional selectors first.
+pub fn query_xic(mzml: &MzML, mz: f64, tol: f64, rt_range: FromTo) -> (Vec<f64>, Vec<f64>) {
+    let selector = SpectrumSelector::MsLevel(1).and(SpectrumSelector::RetentionTimeRange(
+        rt_range.from,
+        rt_range.to,
+    ));
+    let lo = mz - tol;
+    let hi = mz + tol;
+
+    let mut rts = Vec::new();
+    let mut ys = Vec::new();
+    for s in select_spectra(mzml, &selector) {
+        let (Some(mzs), Some(ints)) = (s.mz_array.as_ref(), s.intensity_array.as_ref()) else {
+            continue;
+        };
+        let mut acc = 0.0f64;
+        let mut j = lower_bound(mzs, lo);
+        while j < mzs.len() && mzs[j] <= hi {
+            acc += ints[j] as f64;
+            j += 1;
+        }
+        rts.push(s.retention_time.unwrap_or_default());
+        ys.push(acc);
+    }
+    (rts, ys)
+}
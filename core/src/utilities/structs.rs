@@ -0,0 +1,78 @@
+#[derive(Clone, Debug, Default)]
+pub struct DataXY {
+    pub x: Vec<f64>,
+    pub y: Vec<f32>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Roi {
+    pub rt: f64,
+    pub window: f64,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct FromTo {
+    pub from: f64,
+    pub to: f64,
+}
+
+#[derive(Clone, Debug)]
+pub struct ChromRoi {
+    pub id: String,
+    pub idx: usize,
+    pub rt: f64,
+    pub window: f64,
+}
+
+#[derive(Clone, Debug)]
+pub struct EicRoi {
+    pub id: String,
+    pub rt: f64,
+    pub mz: f64,
+    pub window: f64,
+}
+
+#[derive(Clone, Debug)]
+pub struct Peak {
+    pub from: f64,
+    pub to: f64,
+    pub rt: f64,
+    pub integral: f64,
+    pub intensity: f64,
+    pub ratio: f64,
+    pub np: usize,
+    pub noise: f64,
+    /// Populated only when `FindPeaksOptions::peak_model` requests a shape fit.
+    pub fit: Option<PeakFit>,
+    /// `(apex_intensity - local_baseline) / local_noise_sigma`, estimated from
+    /// the MAD of the raw-minus-smoothed residual flanking the peak.
+    pub snr: f64,
+    /// Local baseline subtracted from `intensity`, populated by
+    /// `with_eic_apex_intensity_refined`; `0.0` otherwise.
+    pub baseline: f64,
+}
+
+/// Result of refining a detected peak against a parametric shape model.
+#[derive(Clone, Copy, Debug)]
+pub struct PeakFit {
+    pub model: PeakModel,
+    pub center: f64,
+    pub amplitude: f64,
+    /// `sigma` for `Gaussian`/`Emg`, `gamma` for `Lorentzian`/`PseudoVoigt`.
+    pub width: f64,
+    /// Full width at half maximum, in `x` units.
+    pub fwhm: f64,
+    /// Area under the fitted model curve.
+    pub area: f64,
+    /// Goodness of fit, `1 - SSE/SST`.
+    pub score: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PeakModel {
+    Gaussian,
+    Lorentzian,
+    PseudoVoigt,
+    /// Exponentially modified Gaussian, for tailing chromatographic peaks.
+    Emg,
+}
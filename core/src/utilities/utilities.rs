@@ -125,3 +125,159 @@ pub fn xy_integration(x: &[f64], y: &[f32]) -> (f64, f64) {
     }
     (s, m as f64)
 }
+
+/// Build the pentadiagonal bands of `DᵀD`, where `D` is the `(n-2) x n`
+/// second-difference operator (rows `[1, -2, 1]` shifted by one each step).
+fn second_diff_gram(n: usize) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+    let mut d0 = vec![0.0; n];
+    let mut d1 = vec![0.0; n.saturating_sub(1)];
+    let mut d2 = vec![0.0; n.saturating_sub(2)];
+    if n < 3 {
+        return (d0, d1, d2);
+    }
+    let cols = [0usize, 1, 2];
+    let vals = [1.0f64, -2.0, 1.0];
+    for row in 0..(n - 2) {
+        for a in 0..3 {
+            for b in a..3 {
+                let ci = row + cols[a];
+                let cj = row + cols[b];
+                let v = vals[a] * vals[b];
+                match cj - ci {
+                    0 => d0[ci] += v,
+                    1 => d1[ci] += v,
+                    2 => d2[ci] += v,
+                    _ => {}
+                }
+            }
+        }
+    }
+    (d0, d1, d2)
+}
+
+/// Solve `A x = b` for a symmetric positive-definite pentadiagonal `A`, given
+/// as its main diagonal `d0` and the two upper bands `d1` (offset 1) and `d2`
+/// (offset 2). Uses a banded Cholesky factorization (bandwidth 2) followed by
+/// forward/back substitution, so it stays `O(n)` instead of falling back to a
+/// dense solve. Reusable by any other smoothing/baseline code in the crate.
+pub fn solve_pentadiagonal_spd(mut d0: Vec<f64>, mut d1: Vec<f64>, mut d2: Vec<f64>, b: Vec<f64>) -> Vec<f64> {
+    let n = d0.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    if n == 1 {
+        return vec![b[0] / d0[0]];
+    }
+
+    #[inline]
+    fn get(d0: &[f64], d1: &[f64], d2: &[f64], i: usize, j: usize) -> f64 {
+        let (i, j) = if i <= j { (i, j) } else { (j, i) };
+        match j - i {
+            0 => d0[i],
+            1 => d1[i],
+            2 => d2[i],
+            _ => 0.0,
+        }
+    }
+    #[inline]
+    fn set(d0: &mut [f64], d1: &mut [f64], d2: &mut [f64], i: usize, j: usize, v: f64) {
+        let (i, j) = if i <= j { (i, j) } else { (j, i) };
+        match j - i {
+            0 => d0[i] = v,
+            1 => d1[i] = v,
+            2 => d2[i] = v,
+            _ => {}
+        }
+    }
+
+    // Right-looking banded Cholesky: A = RᵀR with R upper-triangular, bandwidth 2.
+    for k in 0..n {
+        let lkk = get(&d0, &d1, &d2, k, k).max(0.0).sqrt();
+        set(&mut d0, &mut d1, &mut d2, k, k, lkk);
+        let hi = (k + 2).min(n - 1);
+        for i in (k + 1)..=hi {
+            let v = get(&d0, &d1, &d2, k, i) / lkk;
+            set(&mut d0, &mut d1, &mut d2, k, i, v);
+        }
+        for j in (k + 1)..=hi {
+            for i in j..=hi {
+                let v = get(&d0, &d1, &d2, i, j) - get(&d0, &d1, &d2, k, i) * get(&d0, &d1, &d2, k, j);
+                set(&mut d0, &mut d1, &mut d2, i, j, v);
+            }
+        }
+    }
+
+    // Forward substitution: Rᵀ y = b.
+    let mut y = vec![0.0; n];
+    for i in 0..n {
+        let mut s = b[i];
+        let lo = i.saturating_sub(2);
+        for k in lo..i {
+            s -= get(&d0, &d1, &d2, k, i) * y[k];
+        }
+        y[i] = s / get(&d0, &d1, &d2, i, i);
+    }
+
+    // Back substitution: R x = y.
+    let mut x = vec![0.0; n];
+    for i in (0..n).rev() {
+        let mut s = y[i];
+        let hi = (i + 2).min(n - 1);
+        for k in (i + 1)..=hi {
+            s -= get(&d0, &d1, &d2, i, k) * x[k];
+        }
+        x[i] = s / get(&d0, &d1, &d2, i, i);
+    }
+    x
+}
+
+/// Asymmetric-least-squares (Eilers) baseline: solves
+/// `(W + lambda * DᵀD) z = W y` with reweighting `w_i = p` where `y_i > z_i`
+/// and `1 - p` otherwise, iterated `iters` times. `lambda` controls smoothness
+/// and `p` (typically `0.001..0.1`) controls asymmetry.
+pub fn asls_baseline(y: &[f64], lambda: f64, p: f64, iters: usize) -> Vec<f64> {
+    let n = y.len();
+    if n < 3 {
+        return y.to_vec();
+    }
+    let (dtd0, dtd1, dtd2) = second_diff_gram(n);
+    let mut w = vec![1.0; n];
+    let mut z = y.to_vec();
+    for _ in 0..iters.max(1) {
+        let d0: Vec<f64> = (0..n).map(|i| w[i] + lambda * dtd0[i]).collect();
+        let d1: Vec<f64> = dtd1.iter().map(|&v| lambda * v).collect();
+        let d2: Vec<f64> = dtd2.iter().map(|&v| lambda * v).collect();
+        let b: Vec<f64> = (0..n).map(|i| w[i] * y[i]).collect();
+        z = solve_pentadiagonal_spd(d0, d1, d2, b);
+        for i in 0..n {
+            w[i] = if y[i] > z[i] { p } else { 1.0 - p };
+        }
+    }
+    z
+}
+
+/// Baseline-corrected peak-area integration: subtracts an AsLS baseline from
+/// `y` before trapezoidal integration. Returns the area and peak height above
+/// the baseline, plus the baseline itself so callers can plot/inspect it.
+pub fn xy_integration_baseline(
+    x: &[f64],
+    y: &[f32],
+    lambda: f64,
+    p: f64,
+    iters: usize,
+) -> (f64, f64, Vec<f64>) {
+    let n = x.len();
+    if n == 0 || n != y.len() {
+        return (0.0, f64::NEG_INFINITY, Vec::new());
+    }
+    let y64: Vec<f64> = y.iter().map(|&v| v as f64).collect();
+    let baseline = asls_baseline(&y64, lambda, p, iters);
+    let corrected: Vec<f32> = y64
+        .iter()
+        .zip(&baseline)
+        .map(|(&yi, &bi)| (yi - bi).max(0.0) as f32)
+        .collect();
+    let (area, _) = xy_integration(x, &corrected);
+    let height = corrected.iter().fold(0.0f32, |m, &v| m.max(v)) as f64;
+    (area, height, baseline)
+}
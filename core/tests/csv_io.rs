@@ -0,0 +1,50 @@
+use msut::utilities::csv_io::CsvOptions;
+use msut::utilities::structs::DataXY;
+
+#[test]
+fn round_trips_with_header() {
+    let data = DataXY {
+        x: vec![0.0, 1.5, 3.0],
+        y: vec![10.0, 20.5, 5.0],
+    };
+    let opts = CsvOptions::default();
+
+    let mut buf = Vec::new();
+    data.to_csv_writer(&mut buf, opts).unwrap();
+
+    let parsed = DataXY::from_csv_reader(buf.as_slice(), opts).unwrap();
+    assert_eq!(parsed.x, data.x);
+    assert_eq!(parsed.y, data.y);
+}
+
+#[test]
+fn skips_blank_lines_and_supports_scientific_notation() {
+    let csv = "x,y\n\n1.0e2,3.5e-1\n\n200,7\n";
+    let opts = CsvOptions::default();
+    let parsed = DataXY::from_csv_reader(csv.as_bytes(), opts).unwrap();
+    assert_eq!(parsed.x, vec![100.0, 200.0]);
+    assert_eq!(parsed.y, vec![0.35, 7.0]);
+}
+
+#[test]
+fn reports_line_and_column_on_bad_number() {
+    let csv = "x,y\n1.0,oops\n";
+    let opts = CsvOptions::default();
+    let err = DataXY::from_csv_reader(csv.as_bytes(), opts).unwrap_err();
+    assert!(err.contains("line 2"));
+    assert!(err.contains("column 2"));
+}
+
+#[test]
+fn custom_delimiter_and_column_mapping() {
+    let csv = "label;y;x\nfoo;9.0;1.0\n";
+    let opts = CsvOptions {
+        delimiter: b';',
+        header: true,
+        x_col: 2,
+        y_col: 1,
+    };
+    let parsed = DataXY::from_csv_reader(csv.as_bytes(), opts).unwrap();
+    assert_eq!(parsed.x, vec![1.0]);
+    assert_eq!(parsed.y, vec![9.0]);
+}
@@ -0,0 +1,74 @@
+use msut::utilities::parse::decode::{DecodeOptions, decode_with_options};
+use msut::utilities::parse::encode::encode;
+use msut::utilities::parse::parse_mzml::{ChromatogramSummary, MzML, Run, SpectrumSummary};
+
+fn sample_mzml() -> MzML {
+    let spectra = vec![SpectrumSummary {
+        index: 0,
+        array_length: 3,
+        ms_level: Some(1),
+        polarity: Some(0),
+        spectrum_type: None,
+        retention_time: Some(12.5),
+        scan_window_lower_limit: Some(50.0),
+        scan_window_upper_limit: Some(1200.0),
+        total_ion_current: Some(98765.25),
+        base_peak_intensity: Some(4321.5),
+        base_peak_mz: Some(300.125),
+        mz_array: Some(vec![100.25, 200.5, 300.75]),
+        intensity_array: Some(vec![10.0, 20.0, 30.0]),
+        precursor: None,
+    }];
+    let chromatograms = vec![ChromatogramSummary {
+        index: 0,
+        array_length: 2,
+        time_array: Some(vec![0.0, 1.5]),
+        intensity_array: Some(vec![5.0, 6.5]),
+        id: "TIC".to_string(),
+    }];
+    MzML {
+        cv_list: Vec::new(),
+        file_description: None,
+        referenceable_param_groups: Vec::new(),
+        sample_list: Vec::new(),
+        instrument_configurations: Vec::new(),
+        software_list: Vec::new(),
+        data_processing_list: Vec::new(),
+        acquisition_settings_list: Vec::new(),
+        run: Some(Run {
+            id: String::new(),
+            start_time_stamp: None,
+            default_instrument_configuration_ref: None,
+            spectrum_list_count: Some(1),
+            chromatogram_list_count: Some(1),
+            spectra,
+            chromatograms,
+        }),
+        index_list: None,
+    }
+}
+
+#[test]
+fn verify_checksums_accepts_a_clean_buffer() {
+    let bin = encode(&sample_mzml());
+    let opts = DecodeOptions {
+        verify_checksums: true,
+    };
+    assert!(decode_with_options(&bin, Some(opts)).is_ok());
+}
+
+#[test]
+fn verify_checksums_rejects_a_corrupted_array() {
+    let mut bin = encode(&sample_mzml());
+    // The data region is written last, so the final byte belongs to one of
+    // the raw arrays; flipping it corrupts that array's bytes without
+    // touching the header/index/meta regions.
+    let last = bin.len() - 1;
+    bin[last] ^= 0xff;
+
+    let opts = DecodeOptions {
+        verify_checksums: true,
+    };
+    let err = decode_with_options(&bin, Some(opts)).unwrap_err();
+    assert!(err.contains("crc mismatch"), "unexpected error: {err}");
+}
@@ -0,0 +1,143 @@
+use msut::utilities::parse::decode::decode;
+use msut::utilities::parse::encode::{ArrayDtype, EncodeOptions, encode_with_options, encode_with_order};
+use msut::utilities::parse::helper::ByteOrder;
+use msut::utilities::parse::parse_mzml::{ChromatogramSummary, MzML, Run, SpectrumSummary};
+
+fn sample_mzml() -> MzML {
+    let spectra = vec![SpectrumSummary {
+        index: 0,
+        array_length: 3,
+        ms_level: Some(1),
+        polarity: Some(0),
+        spectrum_type: None,
+        retention_time: Some(12.5),
+        scan_window_lower_limit: Some(50.0),
+        scan_window_upper_limit: Some(1200.0),
+        total_ion_current: Some(98765.25),
+        base_peak_intensity: Some(4321.5),
+        base_peak_mz: Some(300.125),
+        mz_array: Some(vec![100.25, 200.5, 300.75]),
+        intensity_array: Some(vec![10.0, 20.0, 30.0]),
+        precursor: None,
+    }];
+    let chromatograms = vec![ChromatogramSummary {
+        index: 0,
+        array_length: 2,
+        time_array: Some(vec![0.0, 1.5]),
+        intensity_array: Some(vec![5.0, 6.5]),
+        id: "TIC".to_string(),
+    }];
+    MzML {
+        cv_list: Vec::new(),
+        file_description: None,
+        referenceable_param_groups: Vec::new(),
+        sample_list: Vec::new(),
+        instrument_configurations: Vec::new(),
+        software_list: Vec::new(),
+        data_processing_list: Vec::new(),
+        acquisition_settings_list: Vec::new(),
+        run: Some(Run {
+            id: String::new(),
+            start_time_stamp: None,
+            default_instrument_configuration_ref: None,
+            spectrum_list_count: Some(1),
+            chromatogram_list_count: Some(1),
+            spectra,
+            chromatograms,
+        }),
+        index_list: None,
+    }
+}
+
+fn sample_mzml_arrays_only() -> MzML {
+    let mut mzml = sample_mzml();
+    let run = mzml.run.as_mut().unwrap();
+    run.spectra[0].ms_level = None;
+    run.spectra[0].polarity = None;
+    run.spectra[0].retention_time = None;
+    run.spectra[0].scan_window_lower_limit = None;
+    run.spectra[0].scan_window_upper_limit = None;
+    run.spectra[0].total_ion_current = None;
+    run.spectra[0].base_peak_intensity = None;
+    run.spectra[0].base_peak_mz = None;
+    run.chromatograms[0].id = String::new();
+    mzml
+}
+
+#[test]
+fn encode_with_options_round_trips_bin1_metadata_and_arrays() {
+    let mzml = sample_mzml();
+    let bin = encode_with_options(&mzml, None);
+    assert_eq!(&bin[0..4], b"BIN1");
+
+    let decoded = decode(&bin).unwrap();
+    let run = decoded.run.unwrap();
+    assert_eq!(run.spectra[0].mz_array, Some(vec![100.25, 200.5, 300.75]));
+    assert_eq!(run.spectra[0].intensity_array, Some(vec![10.0, 20.0, 30.0]));
+    assert_eq!(run.spectra[0].retention_time, Some(12.5));
+    assert_eq!(run.chromatograms[0].time_array, Some(vec![0.0, 1.5]));
+    assert_eq!(run.chromatograms[0].intensity_array, Some(vec![5.0, 6.5]));
+    assert_eq!(run.chromatograms[0].id, "TIC");
+}
+
+#[test]
+fn encode_with_options_round_trips_f64_intensity() {
+    let mzml = sample_mzml();
+    let opts = EncodeOptions {
+        intensity_dtype: ArrayDtype::F64,
+    };
+    let bin = encode_with_options(&mzml, Some(opts));
+    assert_eq!(bin[13], 2);
+    assert_eq!(bin[15], 2);
+
+    let run = decode(&bin).unwrap().run.unwrap();
+    assert_eq!(run.spectra[0].intensity_array, Some(vec![10.0, 20.0, 30.0]));
+    assert_eq!(run.chromatograms[0].intensity_array, Some(vec![5.0, 6.5]));
+}
+
+#[test]
+fn encode_with_options_picks_bins_when_no_metadata() {
+    let mzml = sample_mzml_arrays_only();
+    let bin = encode_with_options(&mzml, None);
+    assert_eq!(&bin[0..4], b"BINS");
+
+    let run = decode(&bin).unwrap().run.unwrap();
+    assert_eq!(run.spectra[0].mz_array, Some(vec![100.25, 200.5, 300.75]));
+    assert_eq!(run.spectra[0].intensity_array, Some(vec![10.0, 20.0, 30.0]));
+    assert_eq!(run.chromatograms[0].time_array, Some(vec![0.0, 1.5]));
+    assert_eq!(run.chromatograms[0].id, "");
+}
+
+#[test]
+fn big_endian_round_trip_matches_little_endian() {
+    let mzml = sample_mzml();
+
+    let le = encode_with_order(&mzml, ByteOrder::Little);
+    let be = encode_with_order(&mzml, ByteOrder::Big);
+    assert_ne!(le, be);
+    assert!(be[12] & 0x80 != 0);
+
+    let from_le = decode(&le).unwrap();
+    let from_be = decode(&be).unwrap();
+
+    let rs_le = &from_le.run.unwrap().spectra;
+    let rs_be = &from_be.run.unwrap().spectra;
+    assert_eq!(rs_le[0].mz_array, rs_be[0].mz_array);
+    assert_eq!(rs_le[0].intensity_array, rs_be[0].intensity_array);
+    assert_eq!(rs_le[0].retention_time, rs_be[0].retention_time);
+}
+
+#[test]
+fn big_endian_index_table_offsets_decode_to_same_arrays() {
+    let mzml = sample_mzml();
+    let be = encode_with_order(&mzml, ByteOrder::Big);
+    let decoded = decode(&be).unwrap();
+    let run = decoded.run.unwrap();
+
+    assert_eq!(run.spectra[0].mz_array, Some(vec![100.25, 200.5, 300.75]));
+    assert_eq!(
+        run.chromatograms[0].time_array,
+        Some(vec![0.0, 1.5])
+    );
+    assert_eq!(run.chromatograms[0].id, "TIC");
+}
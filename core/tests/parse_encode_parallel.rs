@@ -0,0 +1,66 @@
+use msut::utilities::parse::decode::{DecodeOptions, decode_with_options};
+use msut::utilities::parse::encode::encode_parallel;
+use msut::utilities::parse::parse_mzml::{MzML, Run, SpectrumSummary};
+
+fn large_mzml() -> MzML {
+    let n = 600_001;
+    let mz_array: Vec<f64> = (0..n).map(|i| i as f64 * 0.001).collect();
+    let intensity_array: Vec<f32> = (0..n).map(|i| (i % 1000) as f32).collect();
+    let spectra = vec![SpectrumSummary {
+        index: 0,
+        array_length: n,
+        ms_level: Some(1),
+        polarity: Some(0),
+        spectrum_type: None,
+        retention_time: Some(12.5),
+        scan_window_lower_limit: None,
+        scan_window_upper_limit: None,
+        total_ion_current: None,
+        base_peak_intensity: None,
+        base_peak_mz: None,
+        mz_array: Some(mz_array),
+        intensity_array: Some(intensity_array),
+        precursor: None,
+    }];
+    MzML {
+        cv_list: Vec::new(),
+        file_description: None,
+        referenceable_param_groups: Vec::new(),
+        sample_list: Vec::new(),
+        instrument_configurations: Vec::new(),
+        software_list: Vec::new(),
+        data_processing_list: Vec::new(),
+        acquisition_settings_list: Vec::new(),
+        run: Some(Run {
+            id: String::new(),
+            start_time_stamp: None,
+            default_instrument_configuration_ref: None,
+            spectrum_list_count: Some(1),
+            chromatogram_list_count: Some(0),
+            spectra,
+            chromatograms: Vec::new(),
+        }),
+        index_list: None,
+    }
+}
+
+#[test]
+fn encode_parallel_output_passes_checksum_verification() {
+    let mzml = large_mzml();
+    let bin = encode_parallel(&mzml, 4);
+    assert_eq!(&bin[0..4], b"BIN1");
+
+    let opts = DecodeOptions {
+        verify_checksums: true,
+    };
+    let decoded = decode_with_options(&bin, Some(opts)).unwrap();
+    let run = decoded.run.unwrap();
+    assert_eq!(
+        run.spectra[0].mz_array.as_ref().unwrap(),
+        mzml.run.as_ref().unwrap().spectra[0].mz_array.as_ref().unwrap()
+    );
+    assert_eq!(
+        run.spectra[0].intensity_array.as_ref().unwrap(),
+        mzml.run.as_ref().unwrap().spectra[0].intensity_array.as_ref().unwrap()
+    );
+}
@@ -0,0 +1,76 @@
+use msut::utilities::parse::encode::encode;
+use msut::utilities::parse::integrity::{IntegrityError, seal, verify};
+use msut::utilities::parse::parse_mzml::{ChromatogramSummary, MzML, Run, SpectrumSummary};
+
+fn sample_mzml() -> MzML {
+    let spectra = vec![SpectrumSummary {
+        index: 0,
+        array_length: 3,
+        ms_level: Some(1),
+        polarity: Some(0),
+        spectrum_type: None,
+        retention_time: Some(12.5),
+        scan_window_lower_limit: Some(50.0),
+        scan_window_upper_limit: Some(1200.0),
+        total_ion_current: Some(98765.25),
+        base_peak_intensity: Some(4321.5),
+        base_peak_mz: Some(300.125),
+        mz_array: Some(vec![100.25, 200.5, 300.75]),
+        intensity_array: Some(vec![10.0, 20.0, 30.0]),
+        precursor: None,
+    }];
+    let chromatograms = vec![ChromatogramSummary {
+        index: 0,
+        array_length: 2,
+        time_array: Some(vec![0.0, 1.5]),
+        intensity_array: Some(vec![5.0, 6.5]),
+        id: "TIC".to_string(),
+    }];
+    MzML {
+        cv_list: Vec::new(),
+        file_description: None,
+        referenceable_param_groups: Vec::new(),
+        sample_list: Vec::new(),
+        instrument_configurations: Vec::new(),
+        software_list: Vec::new(),
+        data_processing_list: Vec::new(),
+        acquisition_settings_list: Vec::new(),
+        run: Some(Run {
+            id: String::new(),
+            start_time_stamp: None,
+            default_instrument_configuration_ref: None,
+            spectrum_list_count: Some(1),
+            chromatogram_list_count: Some(1),
+            spectra,
+            chromatograms,
+        }),
+        index_list: None,
+    }
+}
+
+#[test]
+fn seal_then_verify_succeeds() {
+    let sealed = seal(encode(&sample_mzml()));
+    assert!(verify(&sealed).is_ok());
+}
+
+#[test]
+fn verify_rejects_a_flipped_byte() {
+    let mut sealed = seal(encode(&sample_mzml()));
+    let last = sealed.len() - 1;
+    sealed[last] ^= 0xff;
+    assert_eq!(verify(&sealed), Err(IntegrityError::Mismatch));
+}
+
+#[test]
+fn verify_rejects_a_truncated_buffer() {
+    let sealed = seal(encode(&sample_mzml()));
+    let truncated = &sealed[..sealed.len() - 1];
+    assert_eq!(verify(truncated), Err(IntegrityError::Unverified));
+}
+
+#[test]
+fn verify_reports_unverified_for_an_unsealed_buffer() {
+    let bin = encode(&sample_mzml());
+    assert_eq!(verify(&bin), Err(IntegrityError::Unverified));
+}
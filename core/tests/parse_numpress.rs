@@ -0,0 +1,22 @@
+use msut::utilities::parse::numpress::{decode_linear, encode_linear};
+
+#[test]
+fn round_trips_monotonic_mz_values() {
+    let vals = vec![100.0, 100.5, 101.25, 105.0, 200.0];
+    let encoded = encode_linear(&vals, None);
+    let decoded = decode_linear(&encoded, 0).unwrap();
+    for (a, b) in decoded.iter().zip(&vals) {
+        assert!((a - b).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn corrupt_nibble_header_is_rejected_not_panicking() {
+    let vals = vec![100.0, 100.5, 101.25, 105.0, 200.0];
+    let mut encoded = encode_linear(&vals, None);
+    // First byte of the residual stream: low nibble is the "dropped" count,
+    // which encode_linear only ever emits in 0..=7. Force it out of range.
+    let residual_start = 12 + 8;
+    encoded[residual_start] = (encoded[residual_start] & 0xf0) | 0x0f;
+    assert!(decode_linear(&encoded, 0).is_err());
+}
@@ -0,0 +1,18 @@
+use msut::utilities::parse::predictive::{decode_pred, encode_pred};
+
+#[test]
+fn round_trips_ascending_mz_values() {
+    let vals = vec![100.25, 100.5, 101.0, 105.75, 200.125];
+    let encoded = encode_pred(&vals);
+    let decoded = decode_pred(&encoded, 0).unwrap();
+    assert_eq!(decoded, vals);
+}
+
+#[test]
+fn truncated_buffer_is_rejected_not_panicking() {
+    let vals = vec![100.25, 100.5, 101.0, 105.75, 200.125];
+    let encoded = encode_pred(&vals);
+    for len in 0..encoded.len() {
+        assert!(decode_pred(&encoded[..len], 0).is_err());
+    }
+}
@@ -0,0 +1,94 @@
+use msut::utilities::parse::parse_mzml::{
+    ChromatogramSummary, MzML, Run, SpectrumSummary, parse_mzml,
+};
+use msut::utilities::parse::write_mzml::write_mzml;
+
+fn sample_mzml() -> MzML {
+    let spectra = vec![SpectrumSummary {
+        index: 0,
+        array_length: 3,
+        ms_level: Some(1),
+        polarity: Some(0),
+        spectrum_type: None,
+        retention_time: Some(12.5),
+        scan_window_lower_limit: Some(50.0),
+        scan_window_upper_limit: Some(1200.0),
+        total_ion_current: Some(98765.25),
+        base_peak_intensity: Some(4321.5),
+        base_peak_mz: Some(300.125),
+        mz_array: Some(vec![100.25, 200.5, 300.75]),
+        intensity_array: Some(vec![10.0, 20.0, 30.0]),
+        precursor: None,
+    }];
+    let chromatograms = vec![ChromatogramSummary {
+        index: 0,
+        array_length: 2,
+        time_array: Some(vec![0.0, 1.5]),
+        intensity_array: Some(vec![5.0, 6.5]),
+        id: "TIC".to_string(),
+    }];
+    MzML {
+        cv_list: Vec::new(),
+        file_description: None,
+        referenceable_param_groups: Vec::new(),
+        sample_list: Vec::new(),
+        instrument_configurations: Vec::new(),
+        software_list: Vec::new(),
+        data_processing_list: Vec::new(),
+        acquisition_settings_list: Vec::new(),
+        run: Some(Run {
+            id: "run1".to_string(),
+            start_time_stamp: None,
+            default_instrument_configuration_ref: None,
+            spectrum_list_count: Some(1),
+            chromatogram_list_count: Some(1),
+            spectra,
+            chromatograms,
+        }),
+        index_list: None,
+    }
+}
+
+#[test]
+fn write_then_parse_round_trips_spectra_and_chromatograms() {
+    let mzml = sample_mzml();
+    let bytes = write_mzml(&mzml, false);
+
+    let parsed = parse_mzml(&bytes, false).unwrap();
+    let run = parsed.run.unwrap();
+
+    assert_eq!(run.spectra[0].mz_array, Some(vec![100.25, 200.5, 300.75]));
+    assert_eq!(run.spectra[0].intensity_array, Some(vec![10.0, 20.0, 30.0]));
+    assert_eq!(run.spectra[0].retention_time, Some(12.5));
+    assert_eq!(run.spectra[0].ms_level, Some(1));
+
+    assert_eq!(run.chromatograms[0].time_array, Some(vec![0.0, 1.5]));
+    assert_eq!(run.chromatograms[0].intensity_array, Some(vec![5.0, 6.5]));
+    assert_eq!(run.chromatograms[0].id, "TIC");
+}
+
+#[test]
+fn index_list_offset_and_checksum_are_present_and_consistent() {
+    let mzml = sample_mzml();
+    let bytes = write_mzml(&mzml, true);
+    let text = String::from_utf8(bytes.clone()).unwrap();
+
+    let offset_str = text
+        .split("<indexListOffset>")
+        .nth(1)
+        .and_then(|s| s.split("</indexListOffset>").next())
+        .unwrap();
+    let offset: usize = offset_str.parse().unwrap();
+    assert_eq!(&text[offset..offset + "<indexList".len()], "<indexList");
+
+    let checksum = text
+        .split("<fileChecksum>")
+        .nth(1)
+        .and_then(|s| s.split("</fileChecksum>").next())
+        .unwrap();
+    assert_eq!(checksum.len(), 40);
+
+    let parsed = parse_mzml(&bytes, false).unwrap();
+    let run = parsed.run.unwrap();
+    assert_eq!(run.chromatograms[0].id, "TIC");
+}